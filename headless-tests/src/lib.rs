@@ -0,0 +1,269 @@
+//! A runner for a real headless Weechat that plugins can be loaded into.
+//!
+//! `HeadlessWeechat` starts `weechat-headless` with a temporary home
+//! directory. Commands and buffer input are sent to it over the FIFO plugin
+//! and the lines that were printed on buffers are read back from the files of
+//! the logger plugin.
+//!
+//! The `weechat-headless` binary is searched in the `PATH`, the
+//! `WEECHAT_HEADLESS` environment variable can be used to point to another
+//! binary.
+
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long to wait for Weechat to start up or to print a line.
+const TIMEOUT: Duration = Duration::from_secs(10);
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+static NEXT_HOME: AtomicUsize = AtomicUsize::new(0);
+
+/// Get the path of the `weechat-headless` binary.
+///
+/// Returns None if the binary can't be run, tests should be skipped in that
+/// case.
+pub fn weechat_binary() -> Option<PathBuf> {
+    let binary = env::var_os("WEECHAT_HEADLESS")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("weechat-headless"));
+
+    let output = Command::new(&binary)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    if output.status.success() {
+        Some(binary)
+    } else {
+        None
+    }
+}
+
+/// Build the sample plugin and return the path of its dynamic library.
+///
+/// The plugin is built with the same cargo that runs the tests, into a
+/// separate target directory so the build doesn't wait on the lock of the
+/// running test build.
+pub fn build_sample_plugin() -> io::Result<PathBuf> {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let sample_dir = manifest_dir.join("..").join("sample");
+    let target_dir = manifest_dir.join("target").join("sample");
+
+    let cargo = env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+
+    let status = Command::new(cargo)
+        .arg("build")
+        .arg("--manifest-path")
+        .arg(sample_dir.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .status()?;
+
+    if !status.success() {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("Building the sample plugin failed: {}", status),
+        ));
+    }
+
+    Ok(target_dir.join("debug").join(format!(
+        "{}weechat_rust_sample_plugin{}",
+        env::consts::DLL_PREFIX,
+        env::consts::DLL_SUFFIX
+    )))
+}
+
+/// A running headless Weechat instance.
+///
+/// The instance is shut down and its home directory is removed once this
+/// is dropped.
+pub struct HeadlessWeechat {
+    child: Child,
+    home: PathBuf,
+    fifo: File,
+}
+
+impl HeadlessWeechat {
+    /// Start Weechat and load a plugin.
+    ///
+    /// * `binary` - The path of the `weechat-headless` binary.
+    /// * `plugin` - The path of the plugin that should be loaded.
+    ///
+    /// Only the logger and fifo plugins are loaded besides the given plugin.
+    pub fn start(binary: &Path, plugin: &Path) -> io::Result<Self> {
+        let home = env::temp_dir().join(format!(
+            "rust-weechat-test-{}-{}",
+            std::process::id(),
+            NEXT_HOME.fetch_add(1, Ordering::SeqCst)
+        ));
+
+        let _ = fs::remove_dir_all(&home);
+        fs::create_dir_all(&home)?;
+
+        let fifo_path = home.join("fifo");
+        let log_dir = home.join("logs");
+
+        // The logger is loaded first so the lines that the plugin prints in
+        // its init function end up in the log files. The FIFO is enabled
+        // last, once it's available everything else is loaded as well.
+        let commands = [
+            "/plugin load logger".to_owned(),
+            format!("/set logger.file.path \"{}\"", log_dir.display()),
+            "/set logger.file.flush_delay 0".to_owned(),
+            format!("/plugin load {}", plugin.display()),
+            "/plugin load fifo".to_owned(),
+            format!("/set fifo.file.path \"{}\"", fifo_path.display()),
+        ];
+
+        let child = Command::new(binary)
+            .arg("--dir")
+            .arg(&home)
+            .arg("--no-plugin")
+            .arg("--run-command")
+            .arg(commands.join(";"))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let mut guard = DropGuard(Some(child), home.clone());
+
+        if !wait_for(|| fifo_path.exists()) {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "Weechat didn't create its FIFO",
+            ));
+        }
+
+        let fifo = OpenOptions::new().write(true).open(&fifo_path)?;
+
+        Ok(HeadlessWeechat {
+            child: guard.0.take().unwrap(),
+            home,
+            fifo,
+        })
+    }
+
+    /// Send input to a buffer as if it was typed in by the user.
+    ///
+    /// * `buffer` - The full name of the buffer, e.g. "core.weechat".
+    /// * `input` - The input, commands are run if it starts with a slash.
+    pub fn input(&mut self, buffer: &str, input: &str) -> io::Result<()> {
+        writeln!(self.fifo, "{} *{}", buffer, input)?;
+        self.fifo.flush()
+    }
+
+    /// Get the messages that were printed on a buffer so far.
+    ///
+    /// * `buffer` - The full name of the buffer.
+    pub fn buffer_lines(&self, buffer: &str) -> Vec<String> {
+        let log = match self.log_file(buffer) {
+            Some(log) => log,
+            None => return Vec::new(),
+        };
+
+        // Every line is logged as date, prefix and message separated by
+        // tabs.
+        fs::read_to_string(log)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| line.splitn(3, '\t').nth(2))
+            .map(str::to_owned)
+            .collect()
+    }
+
+    /// Wait until a message shows up on a buffer.
+    ///
+    /// * `buffer` - The full name of the buffer.
+    /// * `message` - The message that should be printed.
+    ///
+    /// Returns the lines of the buffer, panics if the message doesn't show
+    /// up in time.
+    pub fn wait_for_line(&self, buffer: &str, message: &str) -> Vec<String> {
+        let found = wait_for(|| {
+            self.buffer_lines(buffer).iter().any(|line| line == message)
+        });
+
+        let lines = self.buffer_lines(buffer);
+
+        if !found {
+            panic!(
+                "The message {:?} wasn't printed on the buffer {}, the \
+                 buffer contains: {:#?}",
+                message, buffer, lines
+            );
+        }
+
+        lines
+    }
+
+    fn log_file(&self, buffer: &str) -> Option<PathBuf> {
+        // The logger lowercases the buffer name and may replace some
+        // characters of it, so compare the names loosely.
+        let normalize = |name: &str| name.to_lowercase().replace('_', " ");
+        let expected = normalize(&format!("{}.weechatlog", buffer));
+
+        fs::read_dir(self.home.join("logs"))
+            .ok()?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .find(|path| {
+                path.file_name()
+                    .map(|name| normalize(&name.to_string_lossy()) == expected)
+                    .unwrap_or(false)
+            })
+    }
+}
+
+impl Drop for HeadlessWeechat {
+    fn drop(&mut self) {
+        let _ = writeln!(self.fifo, "*/quit");
+        let _ = self.fifo.flush();
+
+        let exited = wait_for(|| match self.child.try_wait() {
+            Ok(status) => status.is_some(),
+            Err(_) => true,
+        });
+
+        if !exited {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+
+        let _ = fs::remove_dir_all(&self.home);
+    }
+}
+
+/// Kills Weechat and removes its home if starting it up fails.
+struct DropGuard(Option<Child>, PathBuf);
+
+impl Drop for DropGuard {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.0.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+            let _ = fs::remove_dir_all(&self.1);
+        }
+    }
+}
+
+fn wait_for<F: FnMut() -> bool>(mut condition: F) -> bool {
+    let start = Instant::now();
+
+    while start.elapsed() < TIMEOUT {
+        if condition() {
+            return true;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+
+    false
+}
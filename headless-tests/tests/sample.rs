@@ -0,0 +1,86 @@
+use weechat_headless_tests::{
+    build_sample_plugin, weechat_binary, HeadlessWeechat,
+};
+
+const CORE_BUFFER: &str = "core.weechat";
+const TEST_BUFFER: &str = "rust_sample.Test buffer";
+
+fn start() -> Option<HeadlessWeechat> {
+    let binary = match weechat_binary() {
+        Some(binary) => binary,
+        None => {
+            eprintln!("weechat-headless wasn't found, skipping the test");
+            return None;
+        }
+    };
+
+    let plugin = build_sample_plugin().expect("Can't build the sample plugin");
+
+    Some(
+        HeadlessWeechat::start(&binary, &plugin)
+            .expect("Can't start weechat-headless"),
+    )
+}
+
+#[test]
+fn plugin_loads_and_unloads() {
+    let mut weechat = match start() {
+        Some(weechat) => weechat,
+        None => return,
+    };
+
+    weechat.wait_for_line(CORE_BUFFER, "Hello Rust!");
+    weechat.wait_for_line(CORE_BUFFER, "Nick name getting test: Emma");
+    weechat.wait_for_line(TEST_BUFFER, "Hello test buffer");
+
+    weechat
+        .input(CORE_BUFFER, "/plugin unload rust_sample")
+        .unwrap();
+    weechat.wait_for_line(CORE_BUFFER, "Bye rust!");
+}
+
+#[test]
+fn command_prints_arguments() {
+    let mut weechat = match start() {
+        Some(weechat) => weechat,
+        None => return,
+    };
+
+    weechat.input(TEST_BUFFER, "/rustcommand a b").unwrap();
+
+    let lines = weechat.wait_for_line(TEST_BUFFER, "b");
+    assert_eq!(
+        lines[lines.len() - 4..],
+        ["Hello rust command", "/rustcommand", "a", "b"]
+    );
+}
+
+#[test]
+fn input_is_echoed() {
+    let mut weechat = match start() {
+        Some(weechat) => weechat,
+        None => return,
+    };
+
+    weechat.input(TEST_BUFFER, "first").unwrap();
+    weechat.wait_for_line(TEST_BUFFER, "Hello");
+
+    weechat.input(TEST_BUFFER, "second").unwrap();
+    weechat.wait_for_line(TEST_BUFFER, "Hello world.");
+}
+
+#[test]
+fn option_change_runs_callback() {
+    let mut weechat = match start() {
+        Some(weechat) => weechat,
+        None => return,
+    };
+
+    weechat
+        .input(
+            CORE_BUFFER,
+            "/set rust_sample.sample_section.test_option changed",
+        )
+        .unwrap();
+    weechat.wait_for_line(CORE_BUFFER, "Changing rust option");
+}
@@ -1,6 +1,6 @@
 use std::borrow::Cow;
 use std::time::Instant;
-use weechat::bar::{BarItem, LightBarItem};
+use weechat::bar::BarItem;
 use weechat::{
     weechat_plugin, ArgsWeechat, Buffer, CommandDescription, CommandHook,
     Config, ConfigOption, ConfigSectionInfo, NickArgs, StringOption, Weechat,
@@ -9,9 +9,9 @@ use weechat::{
 
 struct SamplePlugin {
     weechat: Weechat,
-    _rust_hook: CommandHook<String>,
-    _rust_config: Config<String>,
-    _item: BarItem<String>,
+    _rust_hook: CommandHook,
+    _rust_config: Config,
+    _item: BarItem,
 }
 
 impl SamplePlugin {
@@ -22,9 +22,9 @@ impl SamplePlugin {
         }
     }
 
-    fn close_cb(_data: &(), buffer: Buffer) {
-        let w = buffer.get_weechat();
-        w.print("Closing buffer")
+    fn close_cb(_data: &(), _buffer: Buffer) {
+        let weechat = Weechat::instance();
+        weechat.print("Closing buffer")
     }
 
     fn rust_command_cb(data: &String, buffer: Buffer, args: ArgsWeechat) {
@@ -38,14 +38,6 @@ impl SamplePlugin {
         let weechat = option.get_weechat();
         weechat.print("Changing rust option");
     }
-
-    fn bar_cb(
-        _data: &String,
-        _item: &LightBarItem,
-        _buffer: &Buffer,
-    ) -> String {
-        "rust/sample".to_owned()
-    }
 }
 
 impl WeechatPlugin for SamplePlugin {
@@ -55,10 +47,10 @@ impl WeechatPlugin for SamplePlugin {
         let buffer: Buffer = weechat.buffer_new(
             "Test buffer",
             Some(SamplePlugin::input_cb),
-            Some("Hello".to_owned()),
+            "Hello".to_owned(),
             Some(SamplePlugin::close_cb),
-            None,
-        );
+            (),
+        )?;
 
         buffer.print("Hello test buffer");
 
@@ -103,18 +95,17 @@ impl WeechatPlugin for SamplePlugin {
         let command = weechat.hook_command(
             sample_command,
             SamplePlugin::rust_command_cb,
-            Some("Hello rust command".to_owned()),
-        );
+            "Hello rust command".to_owned(),
+        )?;
 
-        let mut config =
-            weechat.config_new("rust_sample", None, None::<String>);
+        let mut config = Config::new(&weechat, "rust_sample")?;
 
-        let section_info: ConfigSectionInfo<String> = ConfigSectionInfo {
+        let section_info = ConfigSectionInfo {
             name: "sample_section",
             ..Default::default()
         };
 
-        let section = config.new_section(section_info);
+        let section = config.new_section(section_info)?;
 
         section.new_string_option(
             "test_option",
@@ -123,11 +114,12 @@ impl WeechatPlugin for SamplePlugin {
             "",
             false,
             Some(SamplePlugin::option_change_cb),
-            None::<String>,
-        );
+            String::new(),
+        )?;
 
-        let item =
-            weechat.new_bar_item("buffer_plugin", SamplePlugin::bar_cb, None);
+        let item = BarItem::new(&weechat, "buffer_plugin", |_, _, _, _, _| {
+            "rust/sample".to_owned()
+        })?;
 
         Ok(SamplePlugin {
             weechat,
@@ -152,3 +144,92 @@ weechat_plugin!(
     version: "0.1.0",
     license: "MIT"
 );
+
+#[cfg(test)]
+mod tests {
+    use super::SamplePlugin;
+    use weechat::testing::TestWeechat;
+    use weechat::{AnyOption, ConfigOption, ReturnCode};
+
+    #[test]
+    fn init_prints_greetings() {
+        let test = TestWeechat::new();
+        test.load_plugin::<SamplePlugin>(&[]).unwrap();
+
+        let lines = test.printed_lines(&test.core_buffer());
+        assert_eq!(lines[0], "Hello Rust!");
+        assert_eq!(lines[1], "Nick name getting test: Emma");
+    }
+
+    #[test]
+    fn command_prints_arguments() {
+        let test = TestWeechat::new();
+        test.load_plugin::<SamplePlugin>(&[]).unwrap();
+
+        let buffer = test.weechat().buffer_search("", "Test buffer").unwrap();
+
+        assert_eq!(
+            test.run_command(&buffer, "/rustcommand a b"),
+            ReturnCode::Ok
+        );
+        let lines = test.printed_lines(&buffer);
+        assert_eq!(
+            lines[lines.len() - 4..],
+            ["Hello rust command", "/rustcommand", "a", "b"]
+        );
+    }
+
+    #[test]
+    fn input_is_echoed() {
+        let test = TestWeechat::new();
+        test.load_plugin::<SamplePlugin>(&[]).unwrap();
+
+        let buffer = test.weechat().buffer_search("", "Test buffer").unwrap();
+
+        test.input(&buffer, "first");
+        test.input(&buffer, "second");
+
+        let lines = test.printed_lines(&buffer);
+        assert_eq!(lines[lines.len() - 2..], ["Hello", "Hello world."]);
+    }
+
+    #[test]
+    fn option_change_runs_callback() {
+        let test = TestWeechat::new();
+        test.load_plugin::<SamplePlugin>(&[]).unwrap();
+
+        let weechat = test.weechat();
+        let option = weechat
+            .config_get("rust_sample.sample_section.test_option")
+            .unwrap();
+
+        match option {
+            AnyOption::String(option) => option.set("changed", true),
+            _ => panic!("The test option should be a string option"),
+        };
+
+        assert_eq!(
+            test.option_value("rust_sample.sample_section.test_option"),
+            Some("changed".to_owned())
+        );
+        assert_eq!(
+            test.printed_lines(&test.core_buffer()).last().unwrap(),
+            "Changing rust option"
+        );
+    }
+
+    #[test]
+    fn bar_item_shows_plugin() {
+        let test = TestWeechat::new();
+        test.load_plugin::<SamplePlugin>(&[]).unwrap();
+
+        assert_eq!(
+            test.bar_item_content("buffer_plugin"),
+            Some("rust/sample".to_owned())
+        );
+
+        let item = test.weechat().bar_item_search("buffer_plugin").unwrap();
+        assert_eq!(item.get_name(), "buffer_plugin");
+        assert!(test.weechat().bar_item_search("missing").is_none());
+    }
+}
@@ -130,22 +130,27 @@ pub fn weechat_plugin(input: proc_macro::TokenStream) -> proc_macro::TokenStream
         #[no_mangle]
         pub static weechat_plugin_license: [u8; #license_len] = *#license;
 
-        static mut __PLUGIN: Option<#plugin> = None;
-
         #[no_mangle]
         pub extern "C" fn weechat_plugin_init(
             plugin: *mut weechat_sys::t_weechat_plugin,
             argc: libc::c_int,
             argv: *mut *mut ::libc::c_char,
         ) -> libc::c_int {
-            let plugin = Weechat::from_ptr(plugin);
+            let plugin = unsafe { ::weechat::Weechat::init_from_ptr(plugin) };
             let args = ArgsWeechat::new(argc, argv);
             match <#plugin as ::weechat::WeechatPlugin>::init(plugin, args) {
                 Ok(p) => {
-                    unsafe { __PLUGIN = Some(p) }
+                    ::weechat::Weechat::set_plugin(p);
                     return weechat_sys::WEECHAT_RC_OK;
                 }
-                Err(_e) => {
+                Err(e) => {
+                    let weechat = ::weechat::Weechat::instance();
+                    weechat.print(&format!(
+                        "{}{}",
+                        weechat.get_prefix("error"),
+                        e
+                    ));
+                    ::weechat::Weechat::free();
                     return weechat_sys::WEECHAT_RC_ERROR;
                 }
             }
@@ -153,9 +158,7 @@ pub fn weechat_plugin(input: proc_macro::TokenStream) -> proc_macro::TokenStream
 
         #[no_mangle]
         pub extern "C" fn weechat_plugin_end(_plugin: *mut weechat_sys::t_weechat_plugin) -> ::libc::c_int {
-            unsafe {
-                __PLUGIN = None;
-            }
+            ::weechat::Weechat::free();
             weechat_sys::WEECHAT_RC_OK
         }
     };
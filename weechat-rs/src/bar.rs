@@ -1,32 +1,49 @@
 use core::ptr;
 use libc::c_char;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::ffi::CStr;
+use std::ops::Deref;
 use std::os::raw::c_void;
 use weechat_sys::{
-    t_gui_bar_item, t_gui_buffer, t_gui_window, t_hashtable, t_weechat_plugin,
+    t_gui_bar, t_gui_bar_item, t_gui_buffer, t_gui_window, t_hashtable,
+    t_weechat_plugin,
 };
 
-use crate::{Buffer, LossyCString, Weechat};
+use crate::{Buffer, Error, Hashtable, Hdata, LossyCString, Weechat, Window};
 
-struct BarItemCbData<T> {
-    callback: fn(&T, &LightBarItem, &Buffer) -> String,
-    callback_data: T,
+type BarItemCbT = Box<
+    dyn FnMut(
+        &Weechat,
+        &LightBarItem,
+        Option<Window>,
+        &Buffer,
+        Option<&Hashtable>,
+    ) -> String,
+>;
+
+struct BarItemCbData {
+    callback: BarItemCbT,
+    name: String,
     weechat_ptr: *mut t_weechat_plugin,
 }
 
 /// A handle to a bar item. The bar item is automatically removed when the object is
 /// dropped.
-pub struct BarItem<T> {
+pub struct BarItem {
     item: LightBarItem,
-    _data: Box<BarItemCbData<T>>,
+    _data: Box<BarItemCbData>,
 }
 
-/// A handle to a bar item that is passed to callbacks.
+/// A handle to a bar item that is passed to callbacks, the bar item isn't
+/// removed when the object is dropped.
 pub struct LightBarItem {
     ptr: *mut t_gui_bar_item,
     weechat_ptr: *mut t_weechat_plugin,
+    name: String,
 }
 
-impl<T> Drop for BarItem<T> {
+impl Drop for BarItem {
     fn drop(&mut self) {
         let weechat = Weechat::from_ptr(self.item.weechat_ptr);
         let bar_item_remove = weechat.get().bar_item_remove.unwrap();
@@ -34,58 +51,88 @@ impl<T> Drop for BarItem<T> {
     }
 }
 
-impl Weechat {
+impl BarItem {
     /// Create a new bar item that can be added by a user.
-    // TODO: Provide window object, the callback should accept a Window object wrapping a t_gui_window
-    pub fn new_bar_item<T>(
-        &self,
+    /// * `weechat` - The weechat object the bar item is created with.
+    /// * `name` - The name of the bar item.
+    /// * `callback` - A closure that will be called when the bar item needs
+    ///     to be redrawn, the returned string is the content of the item. The
+    ///     window is None if the item is displayed in a root bar. The extra
+    ///     info hashtable contains details about the place the item is
+    ///     displayed at, for example the bar the item belongs to, it's None
+    ///     if Weechat didn't pass one.
+    ///
+    /// Returns an error if Weechat failed to create the bar item.
+    pub fn new<C>(
+        weechat: &Weechat,
         name: &str,
-        callback: fn(data: &T, item: &LightBarItem, buffer: &Buffer) -> String,
-        callback_data: Option<T>,
-    ) -> BarItem<T>
+        callback: C,
+    ) -> Result<BarItem, Error>
     where
-        T: Default,
+        C: FnMut(
+                &Weechat,
+                &LightBarItem,
+                Option<Window>,
+                &Buffer,
+                Option<&Hashtable>,
+            ) -> String
+            + 'static,
     {
-        unsafe extern "C" fn c_item_cb<T>(
+        unsafe extern "C" fn c_item_cb(
             pointer: *const c_void,
             _data: *mut c_void,
             bar_item: *mut t_gui_bar_item,
-            _window: *mut t_gui_window,
+            window: *mut t_gui_window,
             buffer: *mut t_gui_buffer,
-            _extra_info: *mut t_hashtable,
+            extra_info: *mut t_hashtable,
         ) -> *mut c_char {
-            let data: &mut BarItemCbData<T> =
-                { &mut *(pointer as *mut BarItemCbData<T>) };
-            let callback = data.callback;
-            let callback_data = &data.callback_data;
+            let data: &mut BarItemCbData =
+                { &mut *(pointer as *mut BarItemCbData) };
+            let weechat = Weechat::from_ptr(data.weechat_ptr);
+            let window = Window::from_raw(data.weechat_ptr, window);
             let buffer = Buffer::from_ptr(data.weechat_ptr, buffer);
 
+            // The hashtable is borrowed from Weechat and the callback only
+            // gets a shared reference to it, so it can't be modified.
+            let extra_info = if extra_info.is_null() {
+                None
+            } else {
+                Some(Hashtable::from_ptr(data.weechat_ptr, extra_info))
+            };
+
             let item = LightBarItem {
                 ptr: bar_item,
                 weechat_ptr: data.weechat_ptr,
+                name: data.name.clone(),
             };
 
-            let ret = callback(&callback_data, &item, &buffer);
+            let ret = (data.callback)(
+                &weechat,
+                &item,
+                window,
+                &buffer,
+                extra_info.as_ref(),
+            );
             // weechat wants malloc'ed string
             libc::strdup(LossyCString::new(ret).as_ptr())
         }
 
-        let data = Box::new(BarItemCbData::<T> {
-            callback,
-            callback_data: callback_data.unwrap_or_default(),
-            weechat_ptr: self.ptr,
+        let data = Box::new(BarItemCbData {
+            callback: Box::new(callback),
+            name: name.to_owned(),
+            weechat_ptr: weechat.ptr,
         });
 
         let data_ref = Box::leak(data);
-        let bar_item_new = self.get().bar_item_new.unwrap();
+        let bar_item_new = weechat.get().bar_item_new.unwrap();
 
         let bar_item_name = LossyCString::new(name);
 
         let hook_ptr = unsafe {
             bar_item_new(
-                self.ptr,
+                weechat.ptr,
                 bar_item_name.as_ptr(),
-                Some(c_item_cb::<T>),
+                Some(c_item_cb),
                 data_ref as *const _ as *const c_void,
                 ptr::null_mut(),
             )
@@ -93,13 +140,62 @@ impl Weechat {
 
         let hook_data = unsafe { Box::from_raw(data_ref) };
 
-        BarItem {
+        if hook_ptr.is_null() {
+            return Err(Error::BarItemCreation(name.to_owned()));
+        }
+
+        Ok(BarItem {
             item: LightBarItem {
                 ptr: hook_ptr,
-                weechat_ptr: self.ptr,
+                weechat_ptr: weechat.ptr,
+                name: name.to_owned(),
             },
             _data: hook_data,
-        }
+        })
+    }
+}
+
+impl Deref for BarItem {
+    type Target = LightBarItem;
+
+    fn deref(&self) -> &LightBarItem {
+        &self.item
+    }
+}
+
+impl LightBarItem {
+    /// Get the name of the bar item.
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Trigger an update of the bar item, the content of the item is
+    /// rebuilt in all the bars that contain it.
+    pub fn update(&self) {
+        let weechat = Weechat::from_ptr(self.weechat_ptr);
+        weechat.update_bar_item(&self.name);
+    }
+}
+
+impl Weechat {
+    /// Create a new bar item that can be added by a user.
+    ///
+    /// See `BarItem::new()` to create the bar item with a closure.
+    pub fn new_bar_item<T: 'static>(
+        &self,
+        name: &str,
+        callback: fn(
+            data: &T,
+            item: &LightBarItem,
+            window: Option<Window>,
+            buffer: &Buffer,
+            extra_info: Option<&Hashtable>,
+        ) -> String,
+        callback_data: T,
+    ) -> Result<BarItem, Error> {
+        BarItem::new(self, name, move |_, item, window, buffer, extra_info| {
+            callback(&callback_data, item, window, buffer, extra_info)
+        })
     }
 
     /// Triggers a bar update to update by calling its callback
@@ -110,4 +206,521 @@ impl Weechat {
 
         unsafe { bar_item_update(name.as_ptr()) }
     }
+
+    /// Search a bar item by its name, the item can be defined by any plugin.
+    /// * `name` - The name of the bar item.
+    ///
+    /// Returns a handle to the bar item, or None if no item with the name
+    /// exists.
+    pub fn bar_item_search(&self, name: &str) -> Option<LightBarItem> {
+        let bar_item_search = self.get().bar_item_search.unwrap();
+        let c_name = LossyCString::new(name);
+
+        let ptr = unsafe { bar_item_search(c_name.as_ptr()) };
+
+        if ptr.is_null() {
+            None
+        } else {
+            Some(LightBarItem {
+                ptr,
+                weechat_ptr: self.ptr,
+                name: name.to_owned(),
+            })
+        }
+    }
+
+    /// Search a bar by its name.
+    /// * `name` - The name of the bar.
+    ///
+    /// Returns a handle to the bar, or None if no bar with the name exists.
+    pub fn bar_search(&self, name: &str) -> Option<LightBar> {
+        let bar_search = self.get().bar_search.unwrap();
+        let c_name = LossyCString::new(name);
+
+        let ptr = unsafe { bar_search(c_name.as_ptr()) };
+
+        if ptr.is_null() {
+            None
+        } else {
+            Some(LightBar {
+                ptr,
+                weechat_ptr: self.ptr,
+                name: RefCell::new(name.to_owned()),
+            })
+        }
+    }
+}
+
+/// The type of a bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarType {
+    /// The bar is displayed once, outside of the windows.
+    Root,
+    /// The bar is displayed in every window.
+    Window,
+}
+
+impl BarType {
+    pub(crate) fn value(&self) -> &str {
+        match self {
+            BarType::Root => "root",
+            BarType::Window => "window",
+        }
+    }
+}
+
+/// The position of a bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarPosition {
+    /// The bar is displayed above the chat area.
+    Top,
+    /// The bar is displayed below the chat area.
+    Bottom,
+    /// The bar is displayed left of the chat area.
+    Left,
+    /// The bar is displayed right of the chat area.
+    Right,
+}
+
+impl BarPosition {
+    pub(crate) fn value(&self) -> &str {
+        match self {
+            BarPosition::Top => "top",
+            BarPosition::Bottom => "bottom",
+            BarPosition::Left => "left",
+            BarPosition::Right => "right",
+        }
+    }
+}
+
+/// The way the items of a bar are laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarFilling {
+    /// The items are displayed next to each other.
+    Horizontal,
+    /// The items are displayed below each other.
+    Vertical,
+    /// The items are displayed in columns, filled row by row.
+    ColumnsHorizontal,
+    /// The items are displayed in columns, filled column by column.
+    ColumnsVertical,
+}
+
+impl BarFilling {
+    pub(crate) fn value(&self) -> &str {
+        match self {
+            BarFilling::Horizontal => "horizontal",
+            BarFilling::Vertical => "vertical",
+            BarFilling::ColumnsHorizontal => "columns_horizontal",
+            BarFilling::ColumnsVertical => "columns_vertical",
+        }
+    }
+}
+
+/// Builder for a new Weechat bar.
+///
+/// Properties that aren't set use the same defaults as the `/bar add`
+/// command.
+pub struct BarBuilder {
+    name: String,
+    hidden: bool,
+    priority: i32,
+    bar_type: BarType,
+    conditions: String,
+    position: BarPosition,
+    filling_top_bottom: BarFilling,
+    filling_left_right: BarFilling,
+    size: u32,
+    size_max: u32,
+    color_fg: String,
+    color_delim: String,
+    color_bg: String,
+    separator: bool,
+    items: Vec<String>,
+}
+
+impl BarBuilder {
+    /// Create a new bar builder.
+    /// * `name` - The name of the new bar.
+    pub fn new(name: &str) -> BarBuilder {
+        BarBuilder {
+            name: name.to_owned(),
+            hidden: false,
+            priority: 0,
+            bar_type: BarType::Window,
+            conditions: String::new(),
+            position: BarPosition::Top,
+            filling_top_bottom: BarFilling::Horizontal,
+            filling_left_right: BarFilling::Vertical,
+            size: 0,
+            size_max: 0,
+            color_fg: "default".to_owned(),
+            color_delim: "default".to_owned(),
+            color_bg: "default".to_owned(),
+            separator: false,
+            items: Vec::new(),
+        }
+    }
+
+    /// Should the bar be hidden.
+    pub fn hidden(mut self, hidden: bool) -> BarBuilder {
+        self.hidden = hidden;
+        self
+    }
+
+    /// Set the priority of the bar, bars with a higher priority are
+    /// displayed closer to the edge of the terminal.
+    pub fn priority(mut self, priority: i32) -> BarBuilder {
+        self.priority = priority;
+        self
+    }
+
+    /// Set the type of the bar.
+    pub fn bar_type(mut self, bar_type: BarType) -> BarBuilder {
+        self.bar_type = bar_type;
+        self
+    }
+
+    /// Set the conditions that control if the bar is displayed.
+    /// * `conditions` - One of "active", "inactive", "nicklist" or an
+    ///     expression that is evaluated for every window, e.g.
+    ///     `${buffer.number} == 1`.
+    pub fn conditions(mut self, conditions: &str) -> BarBuilder {
+        self.conditions = conditions.to_owned();
+        self
+    }
+
+    /// Set the position of the bar.
+    pub fn position(mut self, position: BarPosition) -> BarBuilder {
+        self.position = position;
+        self
+    }
+
+    /// Set the filling of the bar if it's at the top or bottom.
+    pub fn filling_top_bottom(mut self, filling: BarFilling) -> BarBuilder {
+        self.filling_top_bottom = filling;
+        self
+    }
+
+    /// Set the filling of the bar if it's at the left or right.
+    pub fn filling_left_right(mut self, filling: BarFilling) -> BarBuilder {
+        self.filling_left_right = filling;
+        self
+    }
+
+    /// Set the size of the bar in chars, 0 means that the size is computed
+    /// from the content of the bar.
+    pub fn size(mut self, size: u32) -> BarBuilder {
+        self.size = size;
+        self
+    }
+
+    /// Set the maximum size of the bar in chars, 0 means no limit.
+    pub fn size_max(mut self, size_max: u32) -> BarBuilder {
+        self.size_max = size_max;
+        self
+    }
+
+    /// Set the color of the text in the bar.
+    pub fn color_fg(mut self, color: &str) -> BarBuilder {
+        self.color_fg = color.to_owned();
+        self
+    }
+
+    /// Set the color of the delimiters in the bar.
+    pub fn color_delim(mut self, color: &str) -> BarBuilder {
+        self.color_delim = color.to_owned();
+        self
+    }
+
+    /// Set the background color of the bar.
+    pub fn color_bg(mut self, color: &str) -> BarBuilder {
+        self.color_bg = color.to_owned();
+        self
+    }
+
+    /// Should a separator line be drawn between the bar and the other bars
+    /// or the chat area.
+    pub fn separator(mut self, separator: bool) -> BarBuilder {
+        self.separator = separator;
+        self
+    }
+
+    /// Set the items of the bar.
+    /// * `items` - The names of the items, items that are joined with a `+`
+    ///     are displayed without a space between them.
+    pub fn items(mut self, items: &[&str]) -> BarBuilder {
+        self.items = items.iter().map(|item| (*item).to_owned()).collect();
+        self
+    }
+
+    /// Create the bar.
+    /// * `weechat` - The weechat object the bar is created with.
+    ///
+    /// Returns an error if Weechat failed to create the bar, e.g. if a bar
+    /// with the same name already exists.
+    pub fn build(self, weechat: &Weechat) -> Result<Bar, Error> {
+        let bar_new = weechat.get().bar_new.unwrap();
+
+        let on_off = |value| if value { "on" } else { "off" };
+
+        let name = LossyCString::new(&self.name);
+        let hidden = LossyCString::new(on_off(self.hidden));
+        let priority = LossyCString::new(self.priority.to_string());
+        let bar_type = LossyCString::new(self.bar_type.value());
+        let conditions = LossyCString::new(&self.conditions);
+        let position = LossyCString::new(self.position.value());
+        let filling_top_bottom =
+            LossyCString::new(self.filling_top_bottom.value());
+        let filling_left_right =
+            LossyCString::new(self.filling_left_right.value());
+        let size = LossyCString::new(self.size.to_string());
+        let size_max = LossyCString::new(self.size_max.to_string());
+        let color_fg = LossyCString::new(&self.color_fg);
+        let color_delim = LossyCString::new(&self.color_delim);
+        let color_bg = LossyCString::new(&self.color_bg);
+        let separator = LossyCString::new(on_off(self.separator));
+        let items = LossyCString::new(self.items.join(","));
+
+        let bar_ptr = unsafe {
+            bar_new(
+                name.as_ptr(),
+                hidden.as_ptr(),
+                priority.as_ptr(),
+                bar_type.as_ptr(),
+                conditions.as_ptr(),
+                position.as_ptr(),
+                filling_top_bottom.as_ptr(),
+                filling_left_right.as_ptr(),
+                size.as_ptr(),
+                size_max.as_ptr(),
+                color_fg.as_ptr(),
+                color_delim.as_ptr(),
+                color_bg.as_ptr(),
+                separator.as_ptr(),
+                items.as_ptr(),
+            )
+        };
+
+        if bar_ptr.is_null() {
+            return Err(Error::BarCreation(self.name));
+        }
+
+        Ok(Bar {
+            bar: LightBar {
+                ptr: bar_ptr,
+                weechat_ptr: weechat.ptr,
+                name: RefCell::new(self.name),
+            },
+        })
+    }
+}
+
+/// A handle to a bar created by the plugin. The bar is automatically removed
+/// when the object is dropped.
+///
+/// The bar can be modified using the methods of `LightBar`.
+pub struct Bar {
+    bar: LightBar,
+}
+
+/// A handle to a bar, the bar isn't removed when the object is dropped.
+///
+/// The user may delete the bar with `/bar del` at any time, the methods check
+/// that the bar still exists before they touch it. Handles to the same bar
+/// don't share their name, `update()` uses the name the bar was created,
+/// found or last renamed with through this handle.
+pub struct LightBar {
+    ptr: *mut t_gui_bar,
+    weechat_ptr: *mut t_weechat_plugin,
+    // Used to update the bar without touching the pointer.
+    name: RefCell<String>,
+}
+
+impl Drop for Bar {
+    fn drop(&mut self) {
+        let weechat = Weechat::from_ptr(self.bar.weechat_ptr);
+
+        // The user may have deleted the bar with `/bar del`, the pointer is
+        // dangling in that case. The pointer is checked instead of the name
+        // so renamed bars are removed as well.
+        if self.bar.exists() {
+            let bar_remove = weechat.get().bar_remove.unwrap();
+            unsafe { bar_remove(self.bar.ptr) };
+        }
+    }
+}
+
+impl Deref for Bar {
+    type Target = LightBar;
+
+    fn deref(&self) -> &LightBar {
+        &self.bar
+    }
+}
+
+impl LightBar {
+    fn get_weechat(&self) -> Weechat {
+        Weechat::from_ptr(self.weechat_ptr)
+    }
+
+    /// Check if the bar is still in the list of bars of Weechat.
+    fn exists(&self) -> bool {
+        let weechat = self.get_weechat();
+
+        let check = |hdata: Hdata| {
+            let list = hdata.get_list("gui_bars")?;
+            // The pointer isn't dereferenced, it's only compared with the
+            // pointers in the list.
+            let bar =
+                unsafe { hdata.pointer_from_raw(self.ptr as *mut c_void)? };
+
+            Some(hdata.check_pointer(&list, &bar))
+        };
+
+        weechat.hdata_get("bar").and_then(check).unwrap_or(false)
+    }
+
+    /// Get the name of the bar.
+    ///
+    /// Returns None if the bar doesn't exist anymore or if Weechat doesn't
+    /// know the name of the bar.
+    pub fn get_name(&self) -> Option<Cow<str>> {
+        if !self.exists() {
+            return None;
+        }
+
+        let weechat = self.get_weechat();
+        let hdata_get = weechat.get().hdata_get.unwrap();
+        let hdata_string = weechat.get().hdata_string.unwrap();
+
+        let hdata_name = LossyCString::new("bar");
+        let name = LossyCString::new("name");
+
+        unsafe {
+            let hdata = hdata_get(weechat.ptr, hdata_name.as_ptr());
+            if hdata.is_null() {
+                return None;
+            }
+
+            let ret =
+                hdata_string(hdata, self.ptr as *mut c_void, name.as_ptr());
+            if ret.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(ret).to_string_lossy())
+            }
+        }
+    }
+
+    /// Set a property of the bar.
+    /// * `property` - The name of the property, see the Weechat API
+    ///     documentation of `bar_set()` for the list of properties.
+    /// * `value` - The new value of the property.
+    ///
+    /// Returns true if the property was set, false if the value was invalid
+    /// or the bar doesn't exist anymore.
+    pub fn set(&self, property: &str, value: &str) -> bool {
+        if !self.exists() {
+            return false;
+        }
+
+        let weechat = self.get_weechat();
+        let bar_set = weechat.get().bar_set.unwrap();
+
+        let property = LossyCString::new(property);
+        let value = LossyCString::new(value);
+
+        unsafe { bar_set(self.ptr, property.as_ptr(), value.as_ptr()) == 1 }
+    }
+
+    /// Rename the bar.
+    ///
+    /// Returns false if the bar couldn't be renamed.
+    pub fn set_name(&self, name: &str) -> bool {
+        let renamed = self.set("name", name);
+
+        if renamed {
+            *self.name.borrow_mut() = name.to_owned();
+        }
+
+        renamed
+    }
+
+    /// Hide or show the bar.
+    pub fn set_hidden(&self, hidden: bool) -> bool {
+        self.set("hidden", if hidden { "on" } else { "off" })
+    }
+
+    /// Set the priority of the bar.
+    pub fn set_priority(&self, priority: i32) -> bool {
+        self.set("priority", &priority.to_string())
+    }
+
+    /// Set the conditions that control if the bar is displayed.
+    pub fn set_conditions(&self, conditions: &str) -> bool {
+        self.set("conditions", conditions)
+    }
+
+    /// Set the position of the bar.
+    pub fn set_position(&self, position: BarPosition) -> bool {
+        self.set("position", position.value())
+    }
+
+    /// Set the filling of the bar if it's at the top or bottom.
+    pub fn set_filling_top_bottom(&self, filling: BarFilling) -> bool {
+        self.set("filling_top_bottom", filling.value())
+    }
+
+    /// Set the filling of the bar if it's at the left or right.
+    pub fn set_filling_left_right(&self, filling: BarFilling) -> bool {
+        self.set("filling_left_right", filling.value())
+    }
+
+    /// Set the size of the bar in chars, 0 means that the size is computed
+    /// from the content of the bar.
+    pub fn set_size(&self, size: u32) -> bool {
+        self.set("size", &size.to_string())
+    }
+
+    /// Set the maximum size of the bar in chars, 0 means no limit.
+    pub fn set_size_max(&self, size_max: u32) -> bool {
+        self.set("size_max", &size_max.to_string())
+    }
+
+    /// Set the color of the text in the bar.
+    pub fn set_color_fg(&self, color: &str) -> bool {
+        self.set("color_fg", color)
+    }
+
+    /// Set the color of the delimiters in the bar.
+    pub fn set_color_delim(&self, color: &str) -> bool {
+        self.set("color_delim", color)
+    }
+
+    /// Set the background color of the bar.
+    pub fn set_color_bg(&self, color: &str) -> bool {
+        self.set("color_bg", color)
+    }
+
+    /// Enable or disable the separator line of the bar.
+    pub fn set_separator(&self, separator: bool) -> bool {
+        self.set("separator", if separator { "on" } else { "off" })
+    }
+
+    /// Set the items of the bar.
+    /// * `items` - The names of the items, items that are joined with a `+`
+    ///     are displayed without a space between them.
+    pub fn set_items(&self, items: &[&str]) -> bool {
+        self.set("items", &items.join(","))
+    }
+
+    /// Redraw the bar on the screen.
+    pub fn update(&self) {
+        let weechat = self.get_weechat();
+        let bar_update = weechat.get().bar_update.unwrap();
+
+        let name = LossyCString::new(self.name.borrow().as_str());
+        unsafe { bar_update(name.as_ptr()) };
+    }
 }
@@ -1,17 +1,21 @@
 //! Weechat Buffer module containing Buffer and Nick types.
-use crate::{LossyCString, Weechat};
+use crate::{CallbackResult, Error, LossyCString, ReturnCode, Weechat};
 use libc::{c_char, c_int};
 use std::borrow::Cow;
 use std::ffi::CStr;
 use std::os::raw::c_void;
 use std::ptr;
 use weechat_sys::{
-    t_gui_buffer, t_gui_nick, t_gui_nick_group, t_weechat_plugin, WEECHAT_RC_OK,
+    t_gui_buffer, t_gui_nick, t_gui_nick_group, t_weechat_plugin,
 };
 
+mod lines;
+
+pub use lines::{BufferLine, BufferLines};
+
 /// A high level Buffer type encapsulating weechats C buffer pointer.
 /// The buffer won't be closed if the object is destroyed.
-#[derive(Eq)]
+#[derive(Debug, Eq)]
 pub struct Buffer {
     pub(crate) weechat: *mut t_weechat_plugin,
     pub(crate) ptr: *mut t_gui_buffer,
@@ -23,12 +27,152 @@ impl PartialEq for Buffer {
     }
 }
 
-pub(crate) struct BufferPointers<A, B> {
+type BufferInputCbT = Box<dyn FnMut(&Weechat, Buffer, Cow<str>) -> ReturnCode>;
+type BufferCloseCbT = Box<dyn FnMut(&Weechat, Buffer) -> ReturnCode>;
+
+pub(crate) struct BufferPointers {
     pub(crate) weechat: *mut t_weechat_plugin,
-    pub(crate) input_cb: Option<fn(&mut A, Buffer, Cow<str>)>,
-    pub(crate) input_data: A,
-    pub(crate) close_cb: Option<fn(&B, Buffer)>,
-    pub(crate) close_cb_data: B,
+    pub(crate) input_cb: Option<BufferInputCbT>,
+    pub(crate) close_cb: Option<BufferCloseCbT>,
+}
+
+/// Builder for a new Weechat buffer.
+///
+/// The callbacks of the buffer are freed when the buffer is closed.
+pub struct BufferBuilder {
+    name: String,
+    input_cb: Option<BufferInputCbT>,
+    close_cb: Option<BufferCloseCbT>,
+}
+
+impl BufferBuilder {
+    /// Create a new buffer builder.
+    /// * `name` - Name of the new buffer
+    pub fn new(name: &str) -> BufferBuilder {
+        BufferBuilder {
+            name: name.to_owned(),
+            input_cb: None,
+            close_cb: None,
+        }
+    }
+
+    /// Set the input callback of the buffer.
+    /// * `callback` - A closure that will be called when something is entered
+    ///     into the input bar of the buffer
+    pub fn input_callback<C, R>(mut self, mut callback: C) -> BufferBuilder
+    where
+        C: FnMut(&Weechat, Buffer, Cow<str>) -> R + 'static,
+        R: CallbackResult,
+    {
+        self.input_cb = Some(Box::new(move |weechat, buffer, input| {
+            callback(weechat, buffer, input).into_return_code(weechat)
+        }));
+        self
+    }
+
+    /// Set the close callback of the buffer.
+    /// * `callback` - A closure that will be called when the buffer is
+    ///     closed.
+    pub fn close_callback<C, R>(mut self, mut callback: C) -> BufferBuilder
+    where
+        C: FnMut(&Weechat, Buffer) -> R + 'static,
+        R: CallbackResult,
+    {
+        self.close_cb = Some(Box::new(move |weechat, buffer| {
+            callback(weechat, buffer).into_return_code(weechat)
+        }));
+        self
+    }
+
+    /// Create the buffer.
+    /// * `weechat` - The weechat object the buffer is created with.
+    ///
+    /// Returns an error if Weechat failed to create the buffer, e.g. if a
+    /// buffer with the same name already exists.
+    pub fn build(self, weechat: &Weechat) -> Result<Buffer, Error> {
+        unsafe extern "C" fn c_input_cb(
+            pointer: *const c_void,
+            _data: *mut c_void,
+            buffer: *mut t_gui_buffer,
+            input_data: *const c_char,
+        ) -> c_int {
+            let input_data = CStr::from_ptr(input_data).to_string_lossy();
+
+            let pointers: &mut BufferPointers =
+                { &mut *(pointer as *mut BufferPointers) };
+
+            let weechat = Weechat::from_ptr(pointers.weechat);
+            let buffer = Buffer::from_ptr(pointers.weechat, buffer);
+
+            match pointers.input_cb.as_mut() {
+                Some(callback) => callback(&weechat, buffer, input_data) as i32,
+                None => ReturnCode::Ok as i32,
+            }
+        }
+
+        unsafe extern "C" fn c_close_cb(
+            pointer: *const c_void,
+            _data: *mut c_void,
+            buffer: *mut t_gui_buffer,
+        ) -> c_int {
+            // We use from_raw() here so that the box get's freed at the end
+            // of this scope.
+            let mut pointers = Box::from_raw(pointer as *mut BufferPointers);
+            let weechat = Weechat::from_ptr(pointers.weechat);
+            let buffer = Buffer::from_ptr(pointers.weechat, buffer);
+
+            match pointers.close_cb.as_mut() {
+                Some(callback) => callback(&weechat, buffer) as i32,
+                None => ReturnCode::Ok as i32,
+            }
+        }
+
+        let c_input_cb: Option<WeechatInputCbT> = match self.input_cb {
+            Some(_) => Some(c_input_cb),
+            None => None,
+        };
+
+        // We create a box and use leak to stop rust from freeing our data,
+        // we are giving weechat ownership over the data and will free it in
+        // the buffer close callback.
+        let buffer_pointers = Box::new(BufferPointers {
+            weechat: weechat.ptr,
+            input_cb: self.input_cb,
+            close_cb: self.close_cb,
+        });
+        let buffer_pointers_ref: &BufferPointers = Box::leak(buffer_pointers);
+
+        let buf_new = weechat.get().buffer_new.unwrap();
+        let c_name = LossyCString::new(&self.name);
+
+        let buf_ptr = unsafe {
+            buf_new(
+                weechat.ptr,
+                c_name.as_ptr(),
+                c_input_cb,
+                buffer_pointers_ref as *const _ as *const c_void,
+                ptr::null_mut(),
+                Some(c_close_cb),
+                buffer_pointers_ref as *const _ as *const c_void,
+                ptr::null_mut(),
+            )
+        };
+
+        if buf_ptr.is_null() {
+            // The close callback will never run, free the callbacks here.
+            drop(unsafe {
+                Box::from_raw(
+                    buffer_pointers_ref as *const _ as *mut BufferPointers,
+                )
+            });
+            return Err(Error::BufferCreation(self.name));
+        }
+
+        Ok(Buffer {
+            weechat: weechat.ptr,
+            ptr: buf_ptr,
+        })
+    }
 }
 
 impl Weechat {
@@ -74,6 +218,8 @@ impl Weechat {
     }
 
     /// Create a new Weechat buffer
+    ///
+    /// See `BufferBuilder` to create the buffer with closures.
     /// * `name` - Name of the new buffer
     /// * `input_cb` - Callback that will be called when something is entered
     ///     into the input bar of the buffer
@@ -82,91 +228,30 @@ impl Weechat {
     /// * `close_cb` - Callback that will be called when the buffer is closed.
     /// * `close_cb_data` - Reference to some data that will be passed to the
     ///     close callback.
-    pub fn buffer_new<A: Default, B: Default>(
+    /// Returns an error if the buffer couldn't be created.
+    pub fn buffer_new<A: 'static, B: 'static>(
         &self,
         name: &str,
         input_cb: Option<fn(&mut A, Buffer, Cow<str>)>,
-        input_data: Option<A>,
+        mut input_data: A,
         close_cb: Option<fn(&B, Buffer)>,
-        close_cb_data: Option<B>,
-    ) -> Buffer {
-        unsafe extern "C" fn c_input_cb<A, B>(
-            pointer: *const c_void,
-            _data: *mut c_void,
-            buffer: *mut t_gui_buffer,
-            input_data: *const c_char,
-        ) -> c_int {
-            let input_data = CStr::from_ptr(input_data).to_string_lossy();
-
-            let pointers: &mut BufferPointers<A, B> =
-                { &mut *(pointer as *mut BufferPointers<A, B>) };
-
-            let buffer = Buffer::from_ptr(pointers.weechat, buffer);
-            let data = &mut pointers.input_data;
-
-            if let Some(callback) = pointers.input_cb {
-                callback(data, buffer, input_data)
-            }
-
-            WEECHAT_RC_OK
+        close_cb_data: B,
+    ) -> Result<Buffer, Error> {
+        let mut builder = BufferBuilder::new(name);
+
+        if let Some(callback) = input_cb {
+            builder = builder.input_callback(move |_, buffer, input| {
+                callback(&mut input_data, buffer, input)
+            });
         }
 
-        unsafe extern "C" fn c_close_cb<A, B>(
-            pointer: *const c_void,
-            _data: *mut c_void,
-            buffer: *mut t_gui_buffer,
-        ) -> c_int {
-            // We use from_raw() here so that the box get's freed at the end
-            // of this scope.
-            let pointers = Box::from_raw(pointer as *mut BufferPointers<A, B>);
-            let buffer = Buffer::from_ptr(pointers.weechat, buffer);
-            let data = &pointers.close_cb_data;
-
-            if let Some(callback) = pointers.close_cb {
-                callback(data, buffer)
-            }
-            WEECHAT_RC_OK
+        if let Some(callback) = close_cb {
+            builder = builder.close_callback(move |_, buffer| {
+                callback(&close_cb_data, buffer)
+            });
         }
 
-        // We create a box and use leak to stop rust from freeing our data,
-        // we are giving weechat ownership over the data and will free it in
-        // the buffer close callback.
-        let buffer_pointers = Box::new(BufferPointers::<A, B> {
-            weechat: self.ptr,
-            input_cb,
-            input_data: input_data.unwrap_or_default(),
-            close_cb,
-            close_cb_data: close_cb_data.unwrap_or_default(),
-        });
-        let buffer_pointers_ref: &BufferPointers<A, B> =
-            Box::leak(buffer_pointers);
-
-        let buf_new = self.get().buffer_new.unwrap();
-        let c_name = LossyCString::new(name);
-
-        let c_input_cb: Option<WeechatInputCbT> = match input_cb {
-            Some(_) => Some(c_input_cb::<A, B>),
-            None => None,
-        };
-
-        // TODO this can fail, return a Option type
-        let buf_ptr = unsafe {
-            buf_new(
-                self.ptr,
-                c_name.as_ptr(),
-                c_input_cb,
-                buffer_pointers_ref as *const _ as *const c_void,
-                ptr::null_mut(),
-                Some(c_close_cb::<A, B>),
-                buffer_pointers_ref as *const _ as *const c_void,
-                ptr::null_mut(),
-            )
-        };
-
-        Buffer {
-            weechat: self.ptr,
-            ptr: buf_ptr,
-        }
+        builder.build(self)
     }
 }
 
@@ -292,40 +377,13 @@ impl Buffer {
     /// Display a message on the buffer.
     pub fn print(&self, message: &str) {
         let weechat = Weechat::from_ptr(self.weechat);
-        let printf_date_tags = weechat.get().printf_date_tags.unwrap();
-
-        let fmt_str = LossyCString::new("%s");
-        let c_message = LossyCString::new(message);
-
-        unsafe {
-            printf_date_tags(
-                self.ptr,
-                0,
-                ptr::null(),
-                fmt_str.as_ptr(),
-                c_message.as_ptr(),
-            )
-        }
+        weechat.print_date_tags(self.ptr, 0, None, message);
     }
 
     /// Display a message on the buffer with attached date and tags
     pub fn print_tags_dated(&self, date: i64, tags: &str, message: &str) {
         let weechat = Weechat::from_ptr(self.weechat);
-        let printf_date_tags = weechat.get().printf_date_tags.unwrap();
-
-        let fmt_str = LossyCString::new("%s");
-        let tags = LossyCString::new(tags);
-        let message = LossyCString::new(message);
-
-        unsafe {
-            printf_date_tags(
-                self.ptr,
-                date,
-                tags.as_ptr(),
-                fmt_str.as_ptr(),
-                message.as_ptr(),
-            )
-        }
+        weechat.print_date_tags(self.ptr, date, Some(tags), message);
     }
 
     /// Search for a nicklist group by name
@@ -0,0 +1,137 @@
+//! Access to the lines of a buffer.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+use crate::{Buffer, HdataIter, HdataPointer};
+
+/// A line of a buffer.
+///
+/// The line mutably borrows the buffer, weechat frees lines when the buffer
+/// is cleared or when new lines are printed on a buffer that is full. Holding
+/// the borrow prevents this from happening through the buffer while the line
+/// is alive.
+pub struct BufferLine<'a> {
+    line_data: HdataPointer,
+    buffer: PhantomData<&'a mut Buffer>,
+}
+
+/// An iterator over the lines of a buffer, see `Buffer::lines()`.
+///
+/// The iterator mutably borrows the buffer so the buffer can't be printed to
+/// or cleared while the lines are walked.
+pub struct BufferLines<'a> {
+    lines: Option<HdataIter>,
+    buffer: PhantomData<&'a mut Buffer>,
+}
+
+impl<'a> Iterator for BufferLines<'a> {
+    type Item = BufferLine<'a>;
+
+    fn next(&mut self) -> Option<BufferLine<'a>> {
+        loop {
+            let line = self.lines.as_mut()?.next()?;
+
+            if let Some(line_data) = line.get_hdata_pointer("data") {
+                return Some(BufferLine {
+                    line_data,
+                    buffer: PhantomData,
+                });
+            }
+        }
+    }
+}
+
+impl Buffer {
+    /// Get an iterator over the lines of the buffer, starting with the oldest
+    /// line.
+    ///
+    /// The buffer is mutably borrowed for as long as the iterator and its
+    /// lines are alive, printing to or clearing the buffer could free the
+    /// lines that are being accessed.
+    pub fn lines(&mut self) -> BufferLines {
+        let weechat = self.get_weechat();
+
+        let lines = weechat.hdata_get("buffer").and_then(|hdata| {
+            let buffer = unsafe { hdata.pointer_from_raw(self.ptr as *mut _)? };
+            let first_line = buffer
+                .get_hdata_pointer("own_lines")?
+                .get_hdata_pointer("first_line")?;
+            Some(first_line.iter())
+        });
+
+        BufferLines {
+            lines,
+            buffer: PhantomData,
+        }
+    }
+}
+
+impl<'a> BufferLine<'a> {
+    /// Get the date of the line as a unix timestamp.
+    pub fn date(&self) -> i64 {
+        self.line_data.get_time("date")
+    }
+
+    /// Get the date when the line was printed as a unix timestamp.
+    pub fn date_printed(&self) -> i64 {
+        self.line_data.get_time("date_printed")
+    }
+
+    /// Get the tags of the line.
+    pub fn tags(&self) -> Vec<Cow<str>> {
+        let count = self.line_data.get_integer("tags_count");
+
+        (0..count)
+            .filter_map(|i| {
+                self.line_data.get_string(&format!("{}|tags_array", i))
+            })
+            .collect()
+    }
+
+    /// Get the prefix of the line.
+    pub fn prefix(&self) -> Cow<str> {
+        self.line_data
+            .get_string("prefix")
+            .unwrap_or_else(|| Cow::from(""))
+    }
+
+    /// Get the message of the line.
+    pub fn message(&self) -> Cow<str> {
+        self.line_data
+            .get_string("message")
+            .unwrap_or_else(|| Cow::from(""))
+    }
+
+    /// Is the line displayed, lines can be hidden by filters.
+    pub fn displayed(&self) -> bool {
+        self.line_data.get_char("displayed") != 0
+    }
+
+    /// Is the line a highlight.
+    pub fn highlight(&self) -> bool {
+        self.line_data.get_char("highlight") != 0
+    }
+
+    fn update(&self, name: &str, value: &str) -> bool {
+        let mut values = HashMap::new();
+        values.insert(name.to_owned(), value.to_owned());
+
+        self.line_data.update(&values) == 1
+    }
+
+    /// Replace the message of the line.
+    /// * `message` - The new message of the line.
+    /// Returns true if the line was updated.
+    pub fn set_message(&self, message: &str) -> bool {
+        self.update("message", message)
+    }
+
+    /// Replace the tags of the line.
+    /// * `tags` - The new tags of the line.
+    /// Returns true if the line was updated.
+    pub fn set_tags(&self, tags: &[&str]) -> bool {
+        self.update("tags_array", &tags.join(","))
+    }
+}
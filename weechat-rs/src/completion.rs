@@ -7,7 +7,7 @@ use std::ptr;
 use weechat_sys::{t_gui_buffer, t_gui_completion, t_weechat_plugin};
 
 use crate::hooks::Hook;
-use crate::{Buffer, LossyCString, ReturnCode, Weechat};
+use crate::{Buffer, CallbackResult, Error, LossyCString, ReturnCode, Weechat};
 
 /// A handle to a completion item.
 pub struct Completion {
@@ -79,59 +79,56 @@ impl Completion {
 }
 
 /// Hook for a completion item, the hook is removed when the object is dropped.
-pub struct CompletionHook<T> {
+pub struct CompletionHook {
     _hook: Hook,
-    _hook_data: Box<CompletionHookData<T>>,
+    _hook_data: Box<CompletionHookData>,
 }
 
-struct CompletionHookData<T> {
-    callback: fn(&T, Buffer, Cow<str>, Completion) -> ReturnCode,
-    callback_data: T,
+type CompletionHookCbT =
+    Box<dyn FnMut(&Weechat, Buffer, Cow<str>, Completion) -> ReturnCode>;
+
+struct CompletionHookData {
+    callback: CompletionHookCbT,
     weechat_ptr: *mut t_weechat_plugin,
 }
 
-impl Weechat {
+impl CompletionHook {
     /// Hook a completion.
     ///
+    /// * `weechat` - The weechat object the hook is created with.
     /// * `completion_item` - The name of the completion item
     /// * `description` - The description of the completion item
-    /// * `callback` - A function that will be called when the completion is used, the callback must
+    /// * `callback` - A closure that will be called when the completion is used, the callback must
     ///     populate the words for the completion
-    /// * `callback_data` - Data that will be passed to the callback every time
-    ///     the callback runs. This data will be freed when the hook is unhooked.
-    pub fn hook_completion<T>(
-        &self,
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn new<C, R>(
+        weechat: &Weechat,
         completion_item: &str,
         description: &str,
-        callback: fn(
-            data: &T,
-            buffer: Buffer,
-            item: Cow<str>,
-            completion: Completion,
-        ) -> ReturnCode,
-        callback_data: Option<T>,
-    ) -> CompletionHook<T>
+        mut callback: C,
+    ) -> Result<CompletionHook, Error>
     where
-        T: Default,
+        C: FnMut(&Weechat, Buffer, Cow<str>, Completion) -> R + 'static,
+        R: CallbackResult,
     {
-        unsafe extern "C" fn c_hook_cb<T>(
+        unsafe extern "C" fn c_hook_cb(
             pointer: *const c_void,
             _data: *mut c_void,
             completion_item: *const c_char,
             buffer: *mut t_gui_buffer,
             completion: *mut t_gui_completion,
         ) -> c_int {
-            let hook_data: &mut CompletionHookData<T> =
-                { &mut *(pointer as *mut CompletionHookData<T>) };
-            let callback = hook_data.callback;
-            let callback_data = &hook_data.callback_data;
+            let hook_data: &mut CompletionHookData =
+                { &mut *(pointer as *mut CompletionHookData) };
+            let weechat = Weechat::from_ptr(hook_data.weechat_ptr);
             let buffer = Buffer::from_ptr(hook_data.weechat_ptr, buffer);
 
             let completion_item =
                 CStr::from_ptr(completion_item).to_string_lossy();
 
-            callback(
-                callback_data,
+            (hook_data.callback)(
+                &weechat,
                 buffer,
                 completion_item,
                 Completion::from_raw(hook_data.weechat_ptr, completion),
@@ -139,36 +136,78 @@ impl Weechat {
         }
 
         let data = Box::new(CompletionHookData {
-            callback,
-            callback_data: callback_data.unwrap_or_default(),
-            weechat_ptr: self.ptr,
+            callback: Box::new(move |weechat, buffer, item, completion| {
+                callback(weechat, buffer, item, completion)
+                    .into_return_code(weechat)
+            }),
+            weechat_ptr: weechat.ptr,
         });
 
         let data_ref = Box::leak(data);
-        let hook_completion = self.get().hook_completion.unwrap();
+        let hook_completion = weechat.get().hook_completion.unwrap();
 
         let completion_item = LossyCString::new(completion_item);
         let description = LossyCString::new(description);
 
         let hook_ptr = unsafe {
             hook_completion(
-                self.ptr,
+                weechat.ptr,
                 completion_item.as_ptr(),
                 description.as_ptr(),
-                Some(c_hook_cb::<T>),
+                Some(c_hook_cb),
                 data_ref as *const _ as *const c_void,
                 ptr::null_mut(),
             )
         };
         let hook_data = unsafe { Box::from_raw(data_ref) };
+
+        if hook_ptr.is_null() {
+            return Err(Error::HookCreation);
+        }
+
         let hook = Hook {
             ptr: hook_ptr,
-            weechat_ptr: self.ptr,
+            weechat_ptr: weechat.ptr,
         };
 
-        CompletionHook::<T> {
+        Ok(CompletionHook {
             _hook: hook,
             _hook_data: hook_data,
-        }
+        })
+    }
+}
+
+impl Weechat {
+    /// Hook a completion.
+    ///
+    /// See `CompletionHook::new()` to create the hook with a closure.
+    /// * `completion_item` - The name of the completion item
+    /// * `description` - The description of the completion item
+    /// * `callback` - A function that will be called when the completion is used, the callback must
+    ///     populate the words for the completion
+    /// * `callback_data` - Data that will be passed to the callback every time
+    ///     the callback runs. This data will be freed when the hook is unhooked.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn hook_completion<T: 'static>(
+        &self,
+        completion_item: &str,
+        description: &str,
+        callback: fn(
+            data: &T,
+            buffer: Buffer,
+            item: Cow<str>,
+            completion: Completion,
+        ) -> ReturnCode,
+        callback_data: T,
+    ) -> Result<CompletionHook, Error> {
+        CompletionHook::new(
+            self,
+            completion_item,
+            description,
+            move |_, buffer, item, completion| {
+                callback(&callback_data, buffer, item, completion)
+            },
+        )
     }
 }
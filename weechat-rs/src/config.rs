@@ -1,33 +1,41 @@
 //! Weechat Configuration module
 
 use libc::{c_char, c_int};
+use std::any::Any;
+use std::borrow::Cow;
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::ffi::CStr;
 use std::os::raw::c_void;
 use std::ptr;
 
 use crate::config_options::{
-    BooleanOption, ColorOption, ConfigOption, IntegerOption, OptionDescription,
-    OptionPointers, OptionType, StringOption,
+    BooleanOption, ColorOption, ConfigOption, IntegerOption, OptionChangeCbT,
+    OptionCheckCbT, OptionDescription, OptionPointers, OptionType,
+    StringOption,
+};
+use crate::{
+    CallbackResult, Error, LossyCString, OptionChanged, OptionUnset,
+    ReturnCode, Weechat,
 };
-use crate::{LossyCString, Weechat};
-use std::borrow::Cow;
 use weechat_sys::{
     t_config_file, t_config_option, t_config_section, t_weechat_plugin,
-    WEECHAT_RC_OK,
+    WEECHAT_CONFIG_WRITE_ERROR, WEECHAT_CONFIG_WRITE_OK,
 };
 
 /// Weechat configuration file
-pub struct Config<T> {
+pub struct Config {
     ptr: *mut t_config_file,
     weechat_ptr: *mut t_weechat_plugin,
-    _config_data: Box<ConfigPointers<T>>,
+    _config_data: Box<ConfigPointers>,
     sections: HashMap<String, ConfigSection>,
 }
 
-struct ConfigPointers<T> {
-    reload_cb: Option<fn(&mut T)>,
-    reload_data: T,
+type ConfigReloadCbT = Box<dyn FnMut(&Weechat) -> ReturnCode>;
+
+struct ConfigPointers {
+    reload_cb: Option<ConfigReloadCbT>,
+    weechat_ptr: *mut t_weechat_plugin,
 }
 
 /// Weechat Configuration section
@@ -35,11 +43,29 @@ pub struct ConfigSection {
     ptr: *mut t_config_section,
     config_ptr: *mut t_config_file,
     weechat_ptr: *mut t_weechat_plugin,
+    _section_data: Box<ConfigSectionPointers>,
+    // The callbacks of the options, they are freed together with the
+    // section.
+    option_pointers: RefCell<Vec<Box<dyn Any>>>,
+}
+
+type SectionReadCbT =
+    Box<dyn FnMut(&Weechat, &str, Option<&str>) -> OptionChanged>;
+type SectionWriteCbT = Box<dyn FnMut(&Weechat, &str) -> ReturnCode>;
+type SectionDeleteCbT = Box<dyn FnMut(&Weechat, &str) -> OptionUnset>;
+
+struct ConfigSectionPointers {
+    read_cb: Option<SectionReadCbT>,
+    write_cb: Option<SectionWriteCbT>,
+    write_default_cb: Option<SectionWriteCbT>,
+    create_option_cb: Option<SectionReadCbT>,
+    delete_option_cb: Option<SectionDeleteCbT>,
+    weechat_ptr: *mut t_weechat_plugin,
 }
 
 /// Represents the options when creating a new config section.
 #[derive(Default)]
-pub struct ConfigSectionInfo<'a, T> {
+pub struct ConfigSectionInfo<'a> {
     /// Name of the config section
     pub name: &'a str,
 
@@ -48,33 +74,29 @@ pub struct ConfigSectionInfo<'a, T> {
     /// Can the user delete options?
     pub user_can_delete_option: bool,
 
-    /// A function called when an option from the section is read from the disk
-    pub read_callback: Option<fn(&T)>,
-    /// Data passed to the `read_callback`
-    pub read_callback_data: Option<T>,
-
-    /// A function called when the section is written to the disk
-    pub write_callback: Option<fn(&T)>,
-    /// Data passed to the `write_callback`
-    pub write_callback_data: Option<T>,
-
-    /// A function called when default values for the section must be written to the disk
-    pub write_default_callback: Option<fn(&T)>,
-    /// Data passed to the `write_default_callback`
-    pub write_default_callback_data: Option<T>,
-
-    /// A function called when a new option is created in the section
-    pub create_option_callback: Option<fn(&T)>,
-    /// Data passed to the `create_option_callback`
-    pub create_option_callback_data: Option<T>,
-
-    /// A function called when an option is deleted in the section
-    pub delete_option_callback: Option<fn(&T)>,
-    /// Data passed to the `delete_option_callback`
-    pub delete_option_callback_data: Option<T>,
+    /// A closure called when an option from the section is read from the
+    /// disk. It receives the name and the value of the option.
+    pub read_callback: Option<SectionReadCbT>,
+
+    /// A closure called when the section is written to the disk. It
+    /// receives the name of the section. Weechat doesn't write the options
+    /// of the section by itself if this is set.
+    pub write_callback: Option<SectionWriteCbT>,
+
+    /// A closure called when default values for the section must be written
+    /// to the disk. It receives the name of the section.
+    pub write_default_callback: Option<SectionWriteCbT>,
+
+    /// A closure called when a new option is created in the section. It
+    /// receives the name and the value of the option.
+    pub create_option_callback: Option<SectionReadCbT>,
+
+    /// A closure called when an option is deleted in the section. It
+    /// receives the name of the option.
+    pub delete_option_callback: Option<SectionDeleteCbT>,
 }
 
-impl<T> Drop for Config<T> {
+impl Drop for Config {
     fn drop(&mut self) {
         let weechat = Weechat::from_ptr(self.weechat_ptr);
         let config_free = weechat.get().config_free.unwrap();
@@ -103,51 +125,348 @@ impl Drop for ConfigSection {
     }
 }
 
-impl<T> Config<T> {
+impl Config {
+    /// Create a new Weechat configuration file, returns a `Config` object.
+    /// The configuration file is freed when the `Config` object is dropped.
+    /// * `weechat` - The weechat object the configuration file is created
+    ///     with.
+    /// * `name` - Name of the new configuration file
+    ///
+    /// Returns an error if Weechat failed to create the configuration file.
+    pub fn new(weechat: &Weechat, name: &str) -> Result<Config, Error> {
+        Config::create(weechat, name, None)
+    }
+
+    /// Create a new Weechat configuration file with a reload callback.
+    /// * `weechat` - The weechat object the configuration file is created
+    ///     with.
+    /// * `name` - Name of the new configuration file
+    /// * `reload_callback` - A closure that will be called when the
+    ///     configuration file is reloaded.
+    ///
+    /// Returns an error if Weechat failed to create the configuration file.
+    pub fn with_reload_callback<C, R>(
+        weechat: &Weechat,
+        name: &str,
+        mut reload_callback: C,
+    ) -> Result<Config, Error>
+    where
+        C: FnMut(&Weechat) -> R + 'static,
+        R: CallbackResult,
+    {
+        Config::create(
+            weechat,
+            name,
+            Some(Box::new(move |weechat| {
+                reload_callback(weechat).into_return_code(weechat)
+            })),
+        )
+    }
+
+    fn create(
+        weechat: &Weechat,
+        name: &str,
+        reload_callback: Option<ConfigReloadCbT>,
+    ) -> Result<Config, Error> {
+        unsafe extern "C" fn c_reload_cb(
+            pointer: *const c_void,
+            _data: *mut c_void,
+            _config_pointer: *mut t_config_file,
+        ) -> c_int {
+            let pointers: &mut ConfigPointers =
+                { &mut *(pointer as *mut ConfigPointers) };
+
+            let weechat = Weechat::from_ptr(pointers.weechat_ptr);
+
+            match pointers.reload_cb.as_mut() {
+                Some(callback) => callback(&weechat) as i32,
+                None => ReturnCode::Ok as i32,
+            }
+        }
+
+        let c_name = LossyCString::new(name);
+
+        let c_reload_cb: Option<WeechatReloadT> = match reload_callback {
+            Some(_) => Some(c_reload_cb),
+            None => None,
+        };
+
+        let config_pointers = Box::new(ConfigPointers {
+            reload_cb: reload_callback,
+            weechat_ptr: weechat.ptr,
+        });
+        let config_pointers_ref = Box::leak(config_pointers);
+
+        let config_new = weechat.get().config_new.unwrap();
+        let config_ptr = unsafe {
+            config_new(
+                weechat.ptr,
+                c_name.as_ptr(),
+                c_reload_cb,
+                config_pointers_ref as *const _ as *const c_void,
+                ptr::null_mut(),
+            )
+        };
+
+        let config_data = unsafe { Box::from_raw(config_pointers_ref) };
+
+        if config_ptr.is_null() {
+            return Err(Error::ConfigCreation(name.to_owned()));
+        }
+
+        Ok(Config {
+            ptr: config_ptr,
+            weechat_ptr: weechat.ptr,
+            _config_data: config_data,
+            sections: HashMap::new(),
+        })
+    }
+
     /// Create a new section in the configuration file.
-    pub fn new_section<S: Default>(
+    ///
+    /// Returns an error if Weechat failed to create the section, e.g. if a
+    /// section with the same name already exists.
+    pub fn new_section(
         &mut self,
-        section_info: ConfigSectionInfo<S>,
-    ) -> &ConfigSection {
+        section_info: ConfigSectionInfo,
+    ) -> Result<&ConfigSection, Error> {
+        unsafe fn option_cb(
+            callback: Option<&mut SectionReadCbT>,
+            weechat_ptr: *mut t_weechat_plugin,
+            option_name: *const c_char,
+            value: *const c_char,
+        ) -> c_int {
+            let weechat = Weechat::from_ptr(weechat_ptr);
+            let option_name = CStr::from_ptr(option_name).to_string_lossy();
+            let value = if value.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(value).to_string_lossy())
+            };
+
+            match callback {
+                Some(callback) => {
+                    callback(&weechat, &option_name, value.as_deref()) as i32
+                }
+                None => OptionChanged::Error as i32,
+            }
+        }
+
+        unsafe fn write_cb(
+            callback: Option<&mut SectionWriteCbT>,
+            weechat_ptr: *mut t_weechat_plugin,
+            section_name: *const c_char,
+        ) -> c_int {
+            let weechat = Weechat::from_ptr(weechat_ptr);
+            let section_name = CStr::from_ptr(section_name).to_string_lossy();
+
+            match callback.map(|callback| callback(&weechat, &section_name)) {
+                Some(ReturnCode::Error) => WEECHAT_CONFIG_WRITE_ERROR,
+                _ => WEECHAT_CONFIG_WRITE_OK,
+            }
+        }
+
+        unsafe extern "C" fn c_read_cb(
+            pointer: *const c_void,
+            _data: *mut c_void,
+            _config: *mut t_config_file,
+            _section: *mut t_config_section,
+            option_name: *const c_char,
+            value: *const c_char,
+        ) -> c_int {
+            let pointers: &mut ConfigSectionPointers =
+                { &mut *(pointer as *mut ConfigSectionPointers) };
+
+            option_cb(
+                pointers.read_cb.as_mut(),
+                pointers.weechat_ptr,
+                option_name,
+                value,
+            )
+        }
+
+        unsafe extern "C" fn c_write_cb(
+            pointer: *const c_void,
+            _data: *mut c_void,
+            _config: *mut t_config_file,
+            section_name: *const c_char,
+        ) -> c_int {
+            let pointers: &mut ConfigSectionPointers =
+                { &mut *(pointer as *mut ConfigSectionPointers) };
+
+            write_cb(
+                pointers.write_cb.as_mut(),
+                pointers.weechat_ptr,
+                section_name,
+            )
+        }
+
+        unsafe extern "C" fn c_write_default_cb(
+            pointer: *const c_void,
+            _data: *mut c_void,
+            _config: *mut t_config_file,
+            section_name: *const c_char,
+        ) -> c_int {
+            let pointers: &mut ConfigSectionPointers =
+                { &mut *(pointer as *mut ConfigSectionPointers) };
+
+            write_cb(
+                pointers.write_default_cb.as_mut(),
+                pointers.weechat_ptr,
+                section_name,
+            )
+        }
+
+        unsafe extern "C" fn c_create_option_cb(
+            pointer: *const c_void,
+            _data: *mut c_void,
+            _config: *mut t_config_file,
+            _section: *mut t_config_section,
+            option_name: *const c_char,
+            value: *const c_char,
+        ) -> c_int {
+            let pointers: &mut ConfigSectionPointers =
+                { &mut *(pointer as *mut ConfigSectionPointers) };
+
+            option_cb(
+                pointers.create_option_cb.as_mut(),
+                pointers.weechat_ptr,
+                option_name,
+                value,
+            )
+        }
+
+        unsafe extern "C" fn c_delete_option_cb(
+            pointer: *const c_void,
+            _data: *mut c_void,
+            _config: *mut t_config_file,
+            _section: *mut t_config_section,
+            option: *mut t_config_option,
+        ) -> c_int {
+            let pointers: &mut ConfigSectionPointers =
+                { &mut *(pointer as *mut ConfigSectionPointers) };
+
+            let weechat = Weechat::from_ptr(pointers.weechat_ptr);
+            let config_option_get_string =
+                weechat.get().config_option_get_string.unwrap();
+            let property = LossyCString::new("name");
+
+            let option_name =
+                config_option_get_string(option, property.as_ptr());
+            let option_name = if option_name.is_null() {
+                Cow::from("")
+            } else {
+                CStr::from_ptr(option_name).to_string_lossy()
+            };
+
+            match pointers.delete_option_cb.as_mut() {
+                Some(callback) => callback(&weechat, &option_name) as i32,
+                None => OptionUnset::Error as i32,
+            }
+        }
+
         let weechat = Weechat::from_ptr(self.weechat_ptr);
 
         let new_section = weechat.get().config_new_section.unwrap();
 
         let name = LossyCString::new(section_info.name);
 
+        let c_read_cb: Option<WeechatSectionReadCbT> =
+            section_info.read_callback.as_ref().map(|_| c_read_cb as _);
+        let c_write_cb: Option<WeechatSectionWriteCbT> = section_info
+            .write_callback
+            .as_ref()
+            .map(|_| c_write_cb as _);
+        let c_write_default_cb: Option<WeechatSectionWriteCbT> = section_info
+            .write_default_callback
+            .as_ref()
+            .map(|_| c_write_default_cb as _);
+        let c_create_option_cb: Option<WeechatSectionReadCbT> = section_info
+            .create_option_callback
+            .as_ref()
+            .map(|_| c_create_option_cb as _);
+        let c_delete_option_cb: Option<WeechatSectionDeleteCbT> = section_info
+            .delete_option_callback
+            .as_ref()
+            .map(|_| c_delete_option_cb as _);
+
+        let section_pointers = Box::new(ConfigSectionPointers {
+            read_cb: section_info.read_callback,
+            write_cb: section_info.write_callback,
+            write_default_cb: section_info.write_default_callback,
+            create_option_cb: section_info.create_option_callback,
+            delete_option_cb: section_info.delete_option_callback,
+            weechat_ptr: weechat.ptr,
+        });
+        let section_pointers_ref = Box::leak(section_pointers);
+        let pointer = section_pointers_ref as *const _ as *const c_void;
+
         let ptr = unsafe {
             new_section(
                 self.ptr,
                 name.as_ptr(),
                 section_info.user_can_add_options as i32,
                 section_info.user_can_delete_option as i32,
-                None,
-                ptr::null_mut(),
-                ptr::null_mut(),
-                None,
-                ptr::null_mut(),
-                ptr::null_mut(),
-                None,
+                c_read_cb,
+                pointer,
                 ptr::null_mut(),
+                c_write_cb,
+                pointer,
                 ptr::null_mut(),
-                None,
+                c_write_default_cb,
+                pointer,
                 ptr::null_mut(),
+                c_create_option_cb,
+                pointer,
                 ptr::null_mut(),
-                None,
-                ptr::null_mut(),
+                c_delete_option_cb,
+                pointer,
                 ptr::null_mut(),
             )
         };
+
+        let section_data = unsafe { Box::from_raw(section_pointers_ref) };
+
+        if ptr.is_null() {
+            return Err(Error::SectionCreation(section_info.name.to_owned()));
+        }
+
         let section = ConfigSection {
             ptr,
             config_ptr: self.ptr,
             weechat_ptr: weechat.ptr,
+            _section_data: section_data,
+            option_pointers: RefCell::new(Vec::new()),
         };
         self.sections.insert(section_info.name.to_string(), section);
-        &self.sections[section_info.name]
+        Ok(&self.sections[section_info.name])
     }
 }
 
+type WeechatSectionReadCbT = unsafe extern "C" fn(
+    pointer: *const c_void,
+    _data: *mut c_void,
+    _config: *mut t_config_file,
+    _section: *mut t_config_section,
+    option_name: *const c_char,
+    value: *const c_char,
+) -> c_int;
+
+type WeechatSectionWriteCbT = unsafe extern "C" fn(
+    pointer: *const c_void,
+    _data: *mut c_void,
+    _config: *mut t_config_file,
+    section_name: *const c_char,
+) -> c_int;
+
+type WeechatSectionDeleteCbT = unsafe extern "C" fn(
+    pointer: *const c_void,
+    _data: *mut c_void,
+    _config: *mut t_config_file,
+    _section: *mut t_config_section,
+    option: *mut t_config_option,
+) -> c_int;
+
 type WeechatOptChangeCbT = unsafe extern "C" fn(
     pointer: *const c_void,
     _data: *mut c_void,
@@ -163,7 +482,10 @@ type WeechatOptCheckCbT = unsafe extern "C" fn(
 
 impl ConfigSection {
     /// Create a new string Weechat configuration option.
-    pub fn new_string_option<D>(
+    ///
+    /// See `new_string_option_with_callback()` to pass a closure as the
+    /// change callback.
+    pub fn new_string_option<D: 'static>(
         &self,
         name: &str,
         description: &str,
@@ -171,11 +493,55 @@ impl ConfigSection {
         value: &str,
         null_allowed: bool,
         change_cb: Option<fn(&mut D, &StringOption)>,
-        change_cb_data: Option<D>,
-    ) -> StringOption
-    where
-        D: Default,
-    {
+        mut change_cb_data: D,
+    ) -> Result<StringOption, Error> {
+        let change_cb = change_cb.map(|cb| -> OptionChangeCbT<StringOption> {
+            Box::new(move |_, option| cb(&mut change_cb_data, option))
+        });
+
+        self.string_option(
+            name,
+            description,
+            default_value,
+            value,
+            null_allowed,
+            change_cb,
+        )
+    }
+
+    /// Create a new string Weechat configuration option with a closure that
+    /// will be called when the option changes.
+    ///
+    /// Returns an error if the option couldn't be created, e.g. if the
+    /// default value isn't valid.
+    pub fn new_string_option_with_callback(
+        &self,
+        name: &str,
+        description: &str,
+        default_value: &str,
+        value: &str,
+        null_allowed: bool,
+        change_cb: impl FnMut(&Weechat, &StringOption) + 'static,
+    ) -> Result<StringOption, Error> {
+        self.string_option(
+            name,
+            description,
+            default_value,
+            value,
+            null_allowed,
+            Some(Box::new(change_cb)),
+        )
+    }
+
+    fn string_option(
+        &self,
+        name: &str,
+        description: &str,
+        default_value: &str,
+        value: &str,
+        null_allowed: bool,
+        change_cb: Option<OptionChangeCbT<StringOption>>,
+    ) -> Result<StringOption, Error> {
         let ptr = self.new_option(
             OptionDescription {
                 name,
@@ -187,20 +553,20 @@ impl ConfigSection {
                 ..Default::default()
             },
             None,
-            None::<String>,
             change_cb,
-            change_cb_data,
             None,
-            None::<String>,
-        );
-        StringOption {
+        )?;
+        Ok(StringOption {
             ptr,
             weechat_ptr: self.weechat_ptr,
-        }
+        })
     }
 
     /// Create a new boolean Weechat configuration option.
-    pub fn new_boolean_option<D>(
+    ///
+    /// See `new_boolean_option_with_callback()` to pass a closure as the
+    /// change callback.
+    pub fn new_boolean_option<D: 'static>(
         &self,
         name: &str,
         description: &str,
@@ -208,11 +574,55 @@ impl ConfigSection {
         value: bool,
         null_allowed: bool,
         change_cb: Option<fn(&mut D, &BooleanOption)>,
-        change_cb_data: Option<D>,
-    ) -> BooleanOption
-    where
-        D: Default,
-    {
+        mut change_cb_data: D,
+    ) -> Result<BooleanOption, Error> {
+        let change_cb = change_cb.map(|cb| -> OptionChangeCbT<BooleanOption> {
+            Box::new(move |_, option| cb(&mut change_cb_data, option))
+        });
+
+        self.boolean_option(
+            name,
+            description,
+            default_value,
+            value,
+            null_allowed,
+            change_cb,
+        )
+    }
+
+    /// Create a new boolean Weechat configuration option with a closure that
+    /// will be called when the option changes.
+    ///
+    /// Returns an error if the option couldn't be created, e.g. if the
+    /// default value isn't valid.
+    pub fn new_boolean_option_with_callback(
+        &self,
+        name: &str,
+        description: &str,
+        default_value: bool,
+        value: bool,
+        null_allowed: bool,
+        change_cb: impl FnMut(&Weechat, &BooleanOption) + 'static,
+    ) -> Result<BooleanOption, Error> {
+        self.boolean_option(
+            name,
+            description,
+            default_value,
+            value,
+            null_allowed,
+            Some(Box::new(change_cb)),
+        )
+    }
+
+    fn boolean_option(
+        &self,
+        name: &str,
+        description: &str,
+        default_value: bool,
+        value: bool,
+        null_allowed: bool,
+        change_cb: Option<OptionChangeCbT<BooleanOption>>,
+    ) -> Result<BooleanOption, Error> {
         let value = if value { "on" } else { "off" };
         let default_value = if default_value { "on" } else { "off" };
         let ptr = self.new_option(
@@ -226,20 +636,20 @@ impl ConfigSection {
                 ..Default::default()
             },
             None,
-            None::<String>,
             change_cb,
-            change_cb_data,
             None,
-            None::<String>,
-        );
-        BooleanOption {
+        )?;
+        Ok(BooleanOption {
             ptr,
             weechat_ptr: self.weechat_ptr,
-        }
+        })
     }
 
     /// Create a new integer Weechat configuration option.
-    pub fn new_integer_option<D>(
+    ///
+    /// See `new_integer_option_with_callback()` to pass a closure as the
+    /// change callback.
+    pub fn new_integer_option<D: 'static>(
         &self,
         name: &str,
         description: &str,
@@ -250,12 +660,13 @@ impl ConfigSection {
         value: &str,
         null_allowed: bool,
         change_cb: Option<fn(&mut D, &IntegerOption)>,
-        change_cb_data: Option<D>,
-    ) -> IntegerOption
-    where
-        D: Default,
-    {
-        let ptr = self.new_option(
+        mut change_cb_data: D,
+    ) -> Result<IntegerOption, Error> {
+        let change_cb = change_cb.map(|cb| -> OptionChangeCbT<IntegerOption> {
+            Box::new(move |_, option| cb(&mut change_cb_data, option))
+        });
+
+        self.integer_option(
             OptionDescription {
                 name,
                 option_type: OptionType::Integer,
@@ -267,21 +678,60 @@ impl ConfigSection {
                 value,
                 null_allowed,
             },
-            None,
-            None::<String>,
             change_cb,
-            change_cb_data,
-            None,
-            None::<String>,
-        );
-        IntegerOption {
+        )
+    }
+
+    /// Create a new integer Weechat configuration option with a closure that
+    /// will be called when the option changes.
+    ///
+    /// Returns an error if the option couldn't be created, e.g. if the
+    /// default value isn't valid.
+    pub fn new_integer_option_with_callback(
+        &self,
+        name: &str,
+        description: &str,
+        string_values: &str,
+        min: i32,
+        max: i32,
+        default_value: &str,
+        value: &str,
+        null_allowed: bool,
+        change_cb: impl FnMut(&Weechat, &IntegerOption) + 'static,
+    ) -> Result<IntegerOption, Error> {
+        self.integer_option(
+            OptionDescription {
+                name,
+                option_type: OptionType::Integer,
+                description,
+                string_values,
+                min,
+                max,
+                default_value,
+                value,
+                null_allowed,
+            },
+            Some(Box::new(change_cb)),
+        )
+    }
+
+    fn integer_option(
+        &self,
+        option_description: OptionDescription,
+        change_cb: Option<OptionChangeCbT<IntegerOption>>,
+    ) -> Result<IntegerOption, Error> {
+        let ptr = self.new_option(option_description, None, change_cb, None)?;
+        Ok(IntegerOption {
             ptr,
             weechat_ptr: self.weechat_ptr,
-        }
+        })
     }
 
     /// Create a new color Weechat configuration option.
-    pub fn new_color_option<D>(
+    ///
+    /// See `new_color_option_with_callback()` to pass a closure as the
+    /// change callback.
+    pub fn new_color_option<D: 'static>(
         &self,
         name: &str,
         description: &str,
@@ -289,11 +739,55 @@ impl ConfigSection {
         value: &str,
         null_allowed: bool,
         change_cb: Option<fn(&mut D, &ColorOption)>,
-        change_cb_data: Option<D>,
-    ) -> ColorOption
-    where
-        D: Default,
-    {
+        mut change_cb_data: D,
+    ) -> Result<ColorOption, Error> {
+        let change_cb = change_cb.map(|cb| -> OptionChangeCbT<ColorOption> {
+            Box::new(move |_, option| cb(&mut change_cb_data, option))
+        });
+
+        self.color_option(
+            name,
+            description,
+            default_value,
+            value,
+            null_allowed,
+            change_cb,
+        )
+    }
+
+    /// Create a new color Weechat configuration option with a closure that
+    /// will be called when the option changes.
+    ///
+    /// Returns an error if the option couldn't be created, e.g. if the
+    /// default value isn't valid.
+    pub fn new_color_option_with_callback(
+        &self,
+        name: &str,
+        description: &str,
+        default_value: &str,
+        value: &str,
+        null_allowed: bool,
+        change_cb: impl FnMut(&Weechat, &ColorOption) + 'static,
+    ) -> Result<ColorOption, Error> {
+        self.color_option(
+            name,
+            description,
+            default_value,
+            value,
+            null_allowed,
+            Some(Box::new(change_cb)),
+        )
+    }
+
+    fn color_option(
+        &self,
+        name: &str,
+        description: &str,
+        default_value: &str,
+        value: &str,
+        null_allowed: bool,
+        change_cb: Option<OptionChangeCbT<ColorOption>>,
+    ) -> Result<ColorOption, Error> {
         let ptr = self.new_option(
             OptionDescription {
                 name,
@@ -305,35 +799,26 @@ impl ConfigSection {
                 ..Default::default()
             },
             None,
-            None::<String>,
             change_cb,
-            change_cb_data,
             None,
-            None::<String>,
-        );
-        ColorOption {
+        )?;
+        Ok(ColorOption {
             ptr,
             weechat_ptr: self.weechat_ptr,
-        }
+        })
     }
 
-    fn new_option<'a, T, A, B, C>(
+    fn new_option<T>(
         &self,
         option_description: OptionDescription,
-        check_cb: Option<fn(&mut A, &T, Cow<str>)>,
-        check_cb_data: Option<A>,
-        change_cb: Option<fn(&mut B, &T)>,
-        change_cb_data: Option<B>,
-        delete_cb: Option<fn(&mut C, &T)>,
-        delete_cb_data: Option<C>,
-    ) -> *mut t_config_option
+        check_cb: Option<OptionCheckCbT<T>>,
+        change_cb: Option<OptionChangeCbT<T>>,
+        delete_cb: Option<OptionChangeCbT<T>>,
+    ) -> Result<*mut t_config_option, Error>
     where
-        T: ConfigOption<'static>,
-        A: Default,
-        B: Default,
-        C: Default,
+        T: ConfigOption<'static> + 'static,
     {
-        unsafe extern "C" fn c_check_cb<T, A, B, C>(
+        unsafe extern "C" fn c_check_cb<T>(
             pointer: *const c_void,
             _data: *mut c_void,
             option_pointer: *mut t_config_option,
@@ -343,55 +828,52 @@ impl ConfigSection {
             T: ConfigOption<'static>,
         {
             let value = CStr::from_ptr(value).to_string_lossy();
-            let pointers: &mut OptionPointers<T, A, B, C> =
-                { &mut *(pointer as *mut OptionPointers<T, A, B, C>) };
+            let pointers: &mut OptionPointers<T> =
+                { &mut *(pointer as *mut OptionPointers<T>) };
 
+            let weechat = Weechat::from_ptr(pointers.weechat_ptr);
             let option = T::from_ptrs(option_pointer, pointers.weechat_ptr);
 
-            let data = &mut pointers.check_cb_data;
-
-            if let Some(callback) = pointers.check_cb {
-                callback(data, &option, value)
+            if let Some(callback) = pointers.check_cb.as_mut() {
+                callback(&weechat, &option, value)
             };
 
-            WEECHAT_RC_OK
+            ReturnCode::Ok as i32
         }
 
-        unsafe extern "C" fn c_change_cb<T, A, B, C>(
+        unsafe extern "C" fn c_change_cb<T>(
             pointer: *const c_void,
             _data: *mut c_void,
             option_pointer: *mut t_config_option,
         ) where
             T: ConfigOption<'static>,
         {
-            let pointers: &mut OptionPointers<T, A, B, C> =
-                { &mut *(pointer as *mut OptionPointers<T, A, B, C>) };
+            let pointers: &mut OptionPointers<T> =
+                { &mut *(pointer as *mut OptionPointers<T>) };
 
+            let weechat = Weechat::from_ptr(pointers.weechat_ptr);
             let option = T::from_ptrs(option_pointer, pointers.weechat_ptr);
 
-            let data = &mut pointers.change_cb_data;
-
-            if let Some(callback) = pointers.change_cb {
-                callback(data, &option)
+            if let Some(callback) = pointers.change_cb.as_mut() {
+                callback(&weechat, &option)
             };
         }
 
-        unsafe extern "C" fn c_delete_cb<T, A, B, C>(
+        unsafe extern "C" fn c_delete_cb<T>(
             pointer: *const c_void,
             _data: *mut c_void,
             option_pointer: *mut t_config_option,
         ) where
             T: ConfigOption<'static>,
         {
-            let pointers: &mut OptionPointers<T, A, B, C> =
-                { &mut *(pointer as *mut OptionPointers<T, A, B, C>) };
+            let pointers: &mut OptionPointers<T> =
+                { &mut *(pointer as *mut OptionPointers<T>) };
 
+            let weechat = Weechat::from_ptr(pointers.weechat_ptr);
             let option = T::from_ptrs(option_pointer, pointers.weechat_ptr);
 
-            let data = &mut pointers.delete_cb_data;
-
-            if let Some(callback) = pointers.delete_cb {
-                callback(data, &option)
+            if let Some(callback) = pointers.delete_cb.as_mut() {
+                callback(&weechat, &option)
             };
         }
 
@@ -405,37 +887,33 @@ impl ConfigSection {
         let default_value = LossyCString::new(option_description.default_value);
         let value = LossyCString::new(option_description.value);
 
-        let option_pointers = Box::new(OptionPointers::<T, A, B, C> {
-            weechat_ptr: self.weechat_ptr,
-            check_cb: check_cb,
-            check_cb_data: check_cb_data.unwrap_or_default(),
-            change_cb: change_cb,
-            change_cb_data: change_cb_data.unwrap_or_default(),
-            delete_cb: delete_cb,
-            delete_cb_data: delete_cb_data.unwrap_or_default(),
-        });
-
-        // TODO this leaks curently.
-        let option_pointers_ref: &OptionPointers<T, A, B, C> =
-            Box::leak(option_pointers);
-
         let c_check_cb: Option<WeechatOptCheckCbT> = match check_cb {
-            Some(_) => Some(c_check_cb::<T, A, B, C>),
+            Some(_) => Some(c_check_cb::<T>),
             None => None,
         };
 
         let c_change_cb: Option<WeechatOptChangeCbT> = match change_cb {
-            Some(_) => Some(c_change_cb::<T, A, B, C>),
+            Some(_) => Some(c_change_cb::<T>),
             None => None,
         };
 
         let c_delete_cb: Option<WeechatOptChangeCbT> = match delete_cb {
-            Some(_) => Some(c_delete_cb::<T, A, B, C>),
+            Some(_) => Some(c_delete_cb::<T>),
             None => None,
         };
 
+        let option_pointers = Box::new(OptionPointers::<T> {
+            weechat_ptr: self.weechat_ptr,
+            check_cb,
+            change_cb,
+            delete_cb,
+        });
+
+        let option_pointers_ref: &OptionPointers<T> =
+            Box::leak(option_pointers);
+
         let config_new_option = weechat.get().config_new_option.unwrap();
-        unsafe {
+        let option_ptr = unsafe {
             config_new_option(
                 self.config_ptr,
                 self.ptr,
@@ -458,7 +936,27 @@ impl ConfigSection {
                 option_pointers_ref as *const _ as *const c_void,
                 ptr::null_mut(),
             )
+        };
+
+        let option_pointers = unsafe {
+            Box::from_raw(
+                option_pointers_ref as *const _ as *mut OptionPointers<T>,
+            )
+        };
+
+        if option_ptr.is_null() {
+            // None of the callbacks can run, free them right away.
+            drop(option_pointers);
+            return Err(Error::InvalidOption(
+                option_description.name.to_owned(),
+            ));
         }
+
+        // The options are freed together with the section, so are their
+        // callbacks.
+        self.option_pointers.borrow_mut().push(option_pointers);
+
+        Ok(option_ptr)
     }
 }
 
@@ -472,65 +970,30 @@ type WeechatReloadT = unsafe extern "C" fn(
 impl Weechat {
     /// Create a new Weechat configuration file, returns a `Config` object.
     /// The configuration file is freed when the `Config` object is dropped.
+    ///
+    /// See `Config::with_reload_callback()` to pass a closure as the reload
+    /// callback.
     /// * `name` - Name of the new configuration file
     /// * `reload_callback` - Callback that will be called when the
     /// configuration file is reloaded.
     /// * `reload_data` - Data that will be taken over by weechat and passed
     /// to the reload callback, this data will be freed when the `Config`
     /// object returned by this method is dropped.
-    pub fn config_new<T: Default>(
+    /// Returns an error if the configuration file couldn't be created.
+    pub fn config_new<T: 'static>(
         &self,
         name: &str,
         reload_callback: Option<fn(&mut T)>,
-        reload_data: Option<T>,
-    ) -> Config<T> {
-        unsafe extern "C" fn c_reload_cb<T>(
-            pointer: *const c_void,
-            _data: *mut c_void,
-            _config_pointer: *mut t_config_file,
-        ) -> c_int {
-            let pointers: &mut ConfigPointers<T> =
-                { &mut *(pointer as *mut ConfigPointers<T>) };
-
-            let data = &mut pointers.reload_data;
-
-            if let Some(callback) = pointers.reload_cb {
-                callback(data)
-            }
-
-            WEECHAT_RC_OK
-        }
-
-        let c_name = LossyCString::new(name);
-
-        let config_pointers = Box::new(ConfigPointers::<T> {
-            reload_cb: reload_callback,
-            reload_data: reload_data.unwrap_or_default(),
-        });
-        let config_pointers_ref = Box::leak(config_pointers);
-
-        let c_reload_cb: Option<WeechatReloadT> = match reload_callback {
-            Some(_) => Some(c_reload_cb::<T>),
-            None => None,
-        };
-
-        let config_new = self.get().config_new.unwrap();
-        let config_ptr = unsafe {
-            config_new(
-                self.ptr,
-                c_name.as_ptr(),
-                c_reload_cb,
-                config_pointers_ref as *const _ as *const c_void,
-                ptr::null_mut(),
-            )
-        };
-
-        let config_data = unsafe { Box::from_raw(config_pointers_ref) };
-        Config {
-            ptr: config_ptr,
-            weechat_ptr: self.ptr,
-            _config_data: config_data,
-            sections: HashMap::new(),
-        }
+        mut reload_data: T,
+    ) -> Result<Config, Error> {
+        let reload_callback =
+            reload_callback.map(|callback| -> ConfigReloadCbT {
+                Box::new(move |_| {
+                    callback(&mut reload_data);
+                    ReturnCode::Ok
+                })
+            });
+
+        Config::create(self, name, reload_callback)
     }
 }
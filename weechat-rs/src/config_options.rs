@@ -1,6 +1,6 @@
 //! A module providing a typed api for Weechat configuration files
 
-use crate::Weechat;
+use crate::{LossyCString, OptionChanged, OptionUnset, Weechat};
 use std::borrow::Cow;
 use std::ffi::CStr;
 use weechat_sys::{t_config_option, t_weechat_plugin};
@@ -69,16 +69,95 @@ pub trait ConfigOption<'a> {
 
         crate::OptionChanged::from_int(ret)
     }
+
+    /// Set the value of the option.
+    /// * `value` - The new value of the option, the value is converted by
+    ///     weechat to the type of the option.
+    /// * `run_callback` - Should the change callback of the option run if the
+    ///     value changed.
+    fn set(&self, value: &str, run_callback: bool) -> OptionChanged {
+        let weechat = self.get_weechat();
+        let option_set = weechat.get().config_option_set.unwrap();
+
+        let value = LossyCString::new(value);
+
+        let ret = unsafe {
+            option_set(self.get_ptr(), value.as_ptr(), run_callback as i32)
+        };
+
+        OptionChanged::from_int(ret)
+    }
+
+    /// Set the value of the option to null, this is only allowed if the
+    /// option was created with null values allowed.
+    /// * `run_callback` - Should the change callback of the option run if the
+    ///     value changed.
+    fn set_null(&self, run_callback: bool) -> OptionChanged {
+        let weechat = self.get_weechat();
+        let option_set_null = weechat.get().config_option_set_null.unwrap();
+
+        let ret =
+            unsafe { option_set_null(self.get_ptr(), run_callback as i32) };
+
+        OptionChanged::from_int(ret)
+    }
+
+    /// Unset the option, depending on the option it is reset to its default
+    /// value or removed.
+    fn unset(&self) -> OptionUnset {
+        let weechat = self.get_weechat();
+        let option_unset = weechat.get().config_option_unset.unwrap();
+
+        let ret = unsafe { option_unset(self.get_ptr()) };
+
+        OptionUnset::from_int(ret)
+    }
+
+    /// Is the value of the option null.
+    fn is_null(&self) -> bool {
+        let weechat = self.get_weechat();
+        let option_is_null = weechat.get().config_option_is_null.unwrap();
+
+        let ret = unsafe { option_is_null(self.get_ptr()) };
+
+        ret == 1
+    }
+
+    /// Is the default value of the option null.
+    fn default_is_null(&self) -> bool {
+        let weechat = self.get_weechat();
+        let option_default_is_null =
+            weechat.get().config_option_default_is_null.unwrap();
+
+        let ret = unsafe { option_default_is_null(self.get_ptr()) };
+
+        ret == 1
+    }
+}
+
+/// A config option of any type, see `Weechat::config_get()`.
+///
+/// The option is borrowed, it is owned by the configuration file it belongs
+/// to.
+pub enum AnyOption {
+    /// An option with a boolean value.
+    Boolean(BooleanOption),
+    /// An option with an integer value.
+    Integer(IntegerOption),
+    /// An option with a string value.
+    String(StringOption),
+    /// An option with a color value.
+    Color(ColorOption),
 }
 
-pub(crate) struct OptionPointers<T, A, B, C> {
+pub(crate) type OptionCheckCbT<T> = Box<dyn FnMut(&Weechat, &T, Cow<str>)>;
+pub(crate) type OptionChangeCbT<T> = Box<dyn FnMut(&Weechat, &T)>;
+
+pub(crate) struct OptionPointers<T> {
     pub(crate) weechat_ptr: *mut t_weechat_plugin,
-    pub(crate) check_cb: Option<fn(&mut A, &T, Cow<str>)>,
-    pub(crate) check_cb_data: A,
-    pub(crate) change_cb: Option<fn(&mut B, &T)>,
-    pub(crate) change_cb_data: B,
-    pub(crate) delete_cb: Option<fn(&mut C, &T)>,
-    pub(crate) delete_cb_data: C,
+    pub(crate) check_cb: Option<OptionCheckCbT<T>>,
+    pub(crate) change_cb: Option<OptionChangeCbT<T>>,
+    pub(crate) delete_cb: Option<OptionChangeCbT<T>>,
 }
 
 /// A config option with a string value.
@@ -126,7 +205,11 @@ impl<'a> ConfigOption<'a> for StringOption {
         let config_string = weechat.get().config_string.unwrap();
         unsafe {
             let string = config_string(self.get_ptr());
-            CStr::from_ptr(string).to_string_lossy()
+            if string.is_null() {
+                Cow::from("")
+            } else {
+                CStr::from_ptr(string).to_string_lossy()
+            }
         }
     }
 }
@@ -199,7 +282,11 @@ impl<'a> ConfigOption<'a> for ColorOption {
         let config_color = weechat.get().config_color.unwrap();
         unsafe {
             let string = config_color(self.get_ptr());
-            CStr::from_ptr(string).to_string_lossy()
+            if string.is_null() {
+                Cow::from("")
+            } else {
+                CStr::from_ptr(string).to_string_lossy()
+            }
         }
     }
 }
@@ -209,3 +296,49 @@ impl PartialEq<bool> for BooleanOption {
         self.value() == *other
     }
 }
+
+impl Weechat {
+    /// Get a config option by its full name.
+    /// * `option_name` - The full name of the option, for example
+    ///     "weechat.look.buffer_time_format" or "irc.server.libera.nicks".
+    /// Returns the option with its type detected, or None if the option
+    /// doesn't exist.
+    pub fn config_get(&self, option_name: &str) -> Option<AnyOption> {
+        let config_get = self.get().config_get.unwrap();
+        let config_option_get_string =
+            self.get().config_option_get_string.unwrap();
+
+        let option_name = LossyCString::new(option_name);
+        let property = LossyCString::new("type");
+
+        let ptr = unsafe { config_get(option_name.as_ptr()) };
+
+        if ptr.is_null() {
+            return None;
+        }
+
+        let option_type = unsafe {
+            let option_type = config_option_get_string(ptr, property.as_ptr());
+            if option_type.is_null() {
+                return None;
+            }
+            CStr::from_ptr(option_type).to_string_lossy()
+        };
+
+        match option_type.as_ref() {
+            "boolean" => Some(AnyOption::Boolean(BooleanOption::from_ptrs(
+                ptr, self.ptr,
+            ))),
+            "integer" | "enum" => Some(AnyOption::Integer(
+                IntegerOption::from_ptrs(ptr, self.ptr),
+            )),
+            "string" => {
+                Some(AnyOption::String(StringOption::from_ptrs(ptr, self.ptr)))
+            }
+            "color" => {
+                Some(AnyOption::Color(ColorOption::from_ptrs(ptr, self.ptr)))
+            }
+            _ => None,
+        }
+    }
+}
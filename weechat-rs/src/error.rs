@@ -0,0 +1,121 @@
+//! Error handling for plugins and callbacks.
+
+use std::fmt;
+
+use crate::{ReturnCode, Weechat};
+
+/// The error type of the weechat crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// Weechat failed to create a hook.
+    HookCreation,
+    /// Weechat failed to create a buffer, contains the name of the buffer.
+    BufferCreation(String),
+    /// Weechat failed to create a bar item, contains the name of the item.
+    BarItemCreation(String),
+    /// Weechat failed to create a bar, contains the name of the bar.
+    BarCreation(String),
+    /// Weechat failed to create a hashtable.
+    HashtableCreation,
+    /// Weechat failed to create an infolist.
+    InfolistCreation,
+    /// Weechat failed to create a configuration file, contains the name of
+    /// the file.
+    ConfigCreation(String),
+    /// Weechat failed to create a configuration section, contains the name
+    /// of the section.
+    SectionCreation(String),
+    /// Weechat failed to create a configuration option, the value or the
+    /// default value of the option are invalid. Contains the name of the
+    /// option.
+    InvalidOption(String),
+    /// A custom error, for example the reason why a plugin failed to
+    /// initialize.
+    Custom(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::HookCreation => write!(f, "Error creating a hook"),
+            Error::BufferCreation(name) => {
+                write!(f, "Error creating the buffer {}", name)
+            }
+            Error::BarItemCreation(name) => {
+                write!(f, "Error creating the bar item {}", name)
+            }
+            Error::BarCreation(name) => {
+                write!(f, "Error creating the bar {}", name)
+            }
+            Error::HashtableCreation => {
+                write!(f, "Error creating a hashtable")
+            }
+            Error::InfolistCreation => {
+                write!(f, "Error creating an infolist")
+            }
+            Error::ConfigCreation(name) => {
+                write!(f, "Error creating the configuration file {}", name)
+            }
+            Error::SectionCreation(name) => {
+                write!(f, "Error creating the configuration section {}", name)
+            }
+            Error::InvalidOption(name) => {
+                write!(f, "Invalid configuration option {}", name)
+            }
+            Error::Custom(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<String> for Error {
+    fn from(message: String) -> Error {
+        Error::Custom(message)
+    }
+}
+
+impl From<&str> for Error {
+    fn from(message: &str) -> Error {
+        Error::Custom(message.to_owned())
+    }
+}
+
+/// A value returned by a callback that is converted to the return code that
+/// is passed to Weechat.
+///
+/// Callbacks can return nothing, a `ReturnCode` or a `Result`. If a callback
+/// returns an error, the error is printed on the core buffer and Weechat
+/// receives `ReturnCode::Error`.
+pub trait CallbackResult {
+    /// Convert the value to the return code for Weechat.
+    fn into_return_code(self, weechat: &Weechat) -> ReturnCode;
+}
+
+impl CallbackResult for () {
+    fn into_return_code(self, _weechat: &Weechat) -> ReturnCode {
+        ReturnCode::Ok
+    }
+}
+
+impl CallbackResult for ReturnCode {
+    fn into_return_code(self, _weechat: &Weechat) -> ReturnCode {
+        self
+    }
+}
+
+impl<T: CallbackResult, E: fmt::Display> CallbackResult for Result<T, E> {
+    fn into_return_code(self, weechat: &Weechat) -> ReturnCode {
+        match self {
+            Ok(value) => value.into_return_code(weechat),
+            Err(error) => {
+                weechat.print(&format!(
+                    "{}{}",
+                    weechat.get_prefix("error"),
+                    error
+                ));
+                ReturnCode::Error
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! Evaluation of weechat expressions.
+
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::os::raw::c_void;
+
+use crate::{Buffer, HashtableType, LossyCString, Weechat};
+
+/// A builder for the evaluation of a weechat expression.
+///
+/// The builder maps to the arguments of the weechat
+/// `string_eval_expression()` function, see the weechat API documentation for
+/// the syntax of expressions.
+pub struct EvalBuilder<'a> {
+    weechat: &'a Weechat,
+    expression: String,
+    pointers: HashMap<String, *mut c_void>,
+    extra_vars: HashMap<String, String>,
+    options: HashMap<String, String>,
+}
+
+impl<'a> EvalBuilder<'a> {
+    pub(crate) fn new(
+        weechat: &'a Weechat,
+        expression: &str,
+    ) -> EvalBuilder<'a> {
+        EvalBuilder {
+            weechat,
+            expression: expression.to_owned(),
+            pointers: HashMap::new(),
+            extra_vars: HashMap::new(),
+            options: HashMap::new(),
+        }
+    }
+
+    /// Bind a pointer that can be used in the expression.
+    /// * `name` - The name of the pointer, for example "window".
+    /// * `pointer` - The pointer that will be bound to the name.
+    pub fn pointer(
+        mut self,
+        name: &str,
+        pointer: *mut c_void,
+    ) -> EvalBuilder<'a> {
+        self.pointers.insert(name.to_owned(), pointer);
+        self
+    }
+
+    /// Bind a buffer that can be used in the expression, e.g. with
+    /// `${buffer.full_name}`. If no buffer is bound, the current buffer is
+    /// used.
+    pub fn buffer(self, buffer: &Buffer) -> EvalBuilder<'a> {
+        self.pointer("buffer", buffer.ptr as *mut c_void)
+    }
+
+    /// Add an extra variable that can be used in the expression with
+    /// `${name}`.
+    pub fn extra_var(mut self, name: &str, value: &str) -> EvalBuilder<'a> {
+        self.extra_vars.insert(name.to_owned(), value.to_owned());
+        self
+    }
+
+    /// Set an evaluation option, see the weechat API documentation of
+    /// `string_eval_expression()` for the list of options.
+    pub fn option(mut self, name: &str, value: &str) -> EvalBuilder<'a> {
+        self.options.insert(name.to_owned(), value.to_owned());
+        self
+    }
+
+    /// Evaluate the expression as a condition, the result will be "1" if the
+    /// condition is true, "0" otherwise.
+    pub fn condition(self) -> EvalBuilder<'a> {
+        self.option("type", "condition")
+    }
+
+    /// Set the prefix that is used for variables, defaults to `${`.
+    pub fn prefix(self, prefix: &str) -> EvalBuilder<'a> {
+        self.option("prefix", prefix)
+    }
+
+    /// Set the suffix that is used for variables, defaults to `}`.
+    pub fn suffix(self, suffix: &str) -> EvalBuilder<'a> {
+        self.option("suffix", suffix)
+    }
+
+    /// Set a regular expression used to replace text in the expression.
+    /// Must be used together with `regex_replace()`.
+    pub fn regex(self, regex: &str) -> EvalBuilder<'a> {
+        self.option("regex", regex)
+    }
+
+    /// Set the replacement text used with `regex()`, the replacement is
+    /// evaluated for every match.
+    pub fn regex_replace(self, replace: &str) -> EvalBuilder<'a> {
+        self.option("regex_replace", replace)
+    }
+
+    /// Evaluate the expression and return the result.
+    /// Returns None if the evaluation failed.
+    pub fn evaluate(&self) -> Option<String> {
+        let weechat = self.weechat;
+        let string_eval_expression =
+            weechat.get().string_eval_expression.unwrap();
+
+        let mut pointers = weechat
+            .hashtable_new(HashtableType::String, HashtableType::Pointer)
+            .ok()?;
+        for (name, pointer) in &self.pointers {
+            pointers.set(name.as_str(), *pointer);
+        }
+
+        let extra_vars = weechat.hashtable_from_map(&self.extra_vars).ok()?;
+        let options = weechat.hashtable_from_map(&self.options).ok()?;
+
+        let expression = LossyCString::new(&self.expression);
+
+        unsafe {
+            let result = string_eval_expression(
+                expression.as_ptr(),
+                pointers.ptr,
+                extra_vars.ptr,
+                options.ptr,
+            );
+
+            if result.is_null() {
+                None
+            } else {
+                let evaluated =
+                    CStr::from_ptr(result).to_string_lossy().into_owned();
+                // The result is malloc'ed by weechat.
+                libc::free(result as *mut c_void);
+                Some(evaluated)
+            }
+        }
+    }
+
+    /// Evaluate the expression as a condition.
+    /// Returns true if the condition is true, false if it is false or if the
+    /// evaluation failed.
+    pub fn evaluate_condition(self) -> bool {
+        self.condition().evaluate().as_deref() == Some("1")
+    }
+}
+
+impl Weechat {
+    /// Create a builder to evaluate a weechat expression with pointers, extra
+    /// variables and options.
+    /// * `expression` - The expression that should be evaluated.
+    pub fn eval_builder(&self, expression: &str) -> EvalBuilder {
+        EvalBuilder::new(self, expression)
+    }
+}
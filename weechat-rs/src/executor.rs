@@ -0,0 +1,239 @@
+//! A single threaded executor that runs futures on the Weechat main thread.
+//!
+//! Futures are polled from a file descriptor hook, wakers write to a socket
+//! that is watched by the hook so tasks can be woken up from any thread.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+
+use crate::{Error, FdHook, FdHookMode, Weechat};
+
+type BoxedTask = Pin<Box<dyn Future<Output = ()>>>;
+
+thread_local! {
+    static EXECUTOR: RefCell<Option<Executor>> = const { RefCell::new(None) };
+}
+
+/// The queue of tasks that are ready to be polled, the queue is shared with
+/// the wakers of the tasks.
+struct ReadyQueue {
+    tasks: Mutex<VecDeque<usize>>,
+    sender: UnixStream,
+}
+
+impl ReadyQueue {
+    fn schedule(&self, task_id: usize) {
+        self.tasks.lock().unwrap().push_back(task_id);
+
+        // The socket is non-blocking, if the socket buffer is full the
+        // executor is going to be woken up anyways.
+        let _ = (&self.sender).write(&[0]);
+    }
+
+    fn take_all(&self) -> Vec<usize> {
+        self.tasks.lock().unwrap().drain(..).collect()
+    }
+}
+
+struct TaskWaker {
+    task_id: usize,
+    queue: Arc<ReadyQueue>,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.queue.schedule(self.task_id);
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.queue.schedule(self.task_id);
+    }
+}
+
+struct Executor {
+    queue: Arc<ReadyQueue>,
+    tasks: HashMap<usize, BoxedTask>,
+    next_id: usize,
+    _hook: FdHook<UnixStream>,
+}
+
+impl Executor {
+    fn new(weechat: &Weechat) -> Result<Executor, Error> {
+        let socket_error = |error: std::io::Error| {
+            Error::Custom(format!(
+                "Can't create the socket for the executor: {}",
+                error
+            ))
+        };
+
+        let (sender, receiver) = UnixStream::pair().map_err(socket_error)?;
+
+        sender.set_nonblocking(true).map_err(socket_error)?;
+        receiver.set_nonblocking(true).map_err(socket_error)?;
+
+        let hook =
+            FdHook::new(weechat, receiver, FdHookMode::Read, |_, receiver| {
+                Executor::run_ready_tasks(receiver)
+            })?;
+
+        Ok(Executor {
+            queue: Arc::new(ReadyQueue {
+                tasks: Mutex::new(VecDeque::new()),
+                sender,
+            }),
+            tasks: HashMap::new(),
+            next_id: 0,
+            _hook: hook,
+        })
+    }
+
+    fn run_ready_tasks(receiver: &mut UnixStream) {
+        let mut buf = [0; 64];
+
+        // Drain the socket, the ready queue tells us which tasks need to run.
+        while let Ok(n) = receiver.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+        }
+
+        let (queue, ready) = match EXECUTOR.with(|executor| {
+            let executor = executor.borrow();
+            let executor = executor.as_ref()?;
+            Some((executor.queue.clone(), executor.queue.take_all()))
+        }) {
+            Some(ready) => ready,
+            None => return,
+        };
+
+        // Tasks that are woken up while we poll are scheduled again and will
+        // run once weechat notices that the socket is readable again, this
+        // way a busy task doesn't block the weechat main loop.
+        for task_id in ready {
+            // The task is taken out of the executor while it's polled so the
+            // task itself can spawn new tasks.
+            let task = EXECUTOR.with(|executor| {
+                executor.borrow_mut().as_mut()?.tasks.remove(&task_id)
+            });
+
+            let mut task = match task {
+                Some(task) => task,
+                // The task already finished.
+                None => continue,
+            };
+
+            let waker = Waker::from(Arc::new(TaskWaker {
+                task_id,
+                queue: queue.clone(),
+            }));
+            let mut context = Context::from_waker(&waker);
+
+            if task.as_mut().poll(&mut context).is_pending() {
+                EXECUTOR.with(|executor| {
+                    if let Some(executor) = executor.borrow_mut().as_mut() {
+                        executor.tasks.insert(task_id, task);
+                    }
+                });
+            }
+        }
+    }
+}
+
+/// Free the executor and drop all the tasks that didn't finish yet.
+pub(crate) fn free() {
+    // The executor is taken out before it's dropped, dropping a task may
+    // access the executor again.
+    let executor = EXECUTOR.with(|executor| executor.borrow_mut().take());
+    drop(executor);
+}
+
+struct JoinState<T> {
+    output: Option<T>,
+    waker: Option<Waker>,
+}
+
+/// A handle to a task spawned with `Weechat::spawn()`, awaiting the handle
+/// returns the output of the task.
+///
+/// Dropping the handle detaches the task, the task keeps running until it
+/// completes or the plugin is unloaded.
+pub struct JoinHandle<T> {
+    state: Rc<RefCell<JoinState<T>>>,
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<T> {
+        let mut state = self.state.borrow_mut();
+
+        match state.output.take() {
+            Some(output) => Poll::Ready(output),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Weechat {
+    /// Spawn a future on the Weechat main thread.
+    ///
+    /// The future is polled from the Weechat main loop, it doesn't need to be
+    /// `Send` and it can use the Weechat API. Returns a handle that can be
+    /// awaited to get the output of the future.
+    /// * `future` - The future that should run.
+    ///
+    /// Returns an error if the executor couldn't be started, the executor is
+    /// started when the first future is spawned.
+    pub fn spawn<F>(&self, future: F) -> Result<JoinHandle<F::Output>, Error>
+    where
+        F: Future + 'static,
+        F::Output: 'static,
+    {
+        let state = Rc::new(RefCell::new(JoinState {
+            output: None,
+            waker: None,
+        }));
+        let task_state = state.clone();
+
+        let task = async move {
+            let output = future.await;
+
+            let mut state = task_state.borrow_mut();
+            state.output = Some(output);
+
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        };
+
+        EXECUTOR.with(|executor| -> Result<(), Error> {
+            let mut executor = executor.borrow_mut();
+
+            if executor.is_none() {
+                *executor = Some(Executor::new(self)?);
+            }
+
+            let executor = executor.as_mut().unwrap();
+
+            let task_id = executor.next_id;
+            executor.next_id += 1;
+
+            executor.tasks.insert(task_id, Box::pin(task));
+            executor.queue.schedule(task_id);
+
+            Ok(())
+        })?;
+
+        Ok(JoinHandle { state })
+    }
+}
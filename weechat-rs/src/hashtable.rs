@@ -0,0 +1,531 @@
+//! Weechat Hashtable module.
+
+use libc::{c_char, c_int, time_t};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::os::raw::c_void;
+use std::{mem, vec};
+
+use weechat_sys::{t_hashtable, t_weechat_plugin};
+
+use crate::{Error, LossyCString, Weechat};
+
+/// The type of the keys or values of a hashtable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashtableType {
+    /// Integer keys or values.
+    Integer,
+    /// String keys or values.
+    String,
+    /// Pointer keys or values.
+    Pointer,
+    /// Raw data keys or values.
+    Buffer,
+    /// Time keys or values.
+    Time,
+}
+
+impl HashtableType {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            HashtableType::Integer => "integer",
+            HashtableType::String => "string",
+            HashtableType::Pointer => "pointer",
+            HashtableType::Buffer => "buffer",
+            HashtableType::Time => "time",
+        }
+    }
+
+    pub(crate) fn from_str(hashtable_type: &str) -> Option<HashtableType> {
+        match hashtable_type {
+            "integer" => Some(HashtableType::Integer),
+            "string" => Some(HashtableType::String),
+            "pointer" => Some(HashtableType::Pointer),
+            "buffer" => Some(HashtableType::Buffer),
+            "time" => Some(HashtableType::Time),
+            _ => None,
+        }
+    }
+}
+
+/// A key or value of a hashtable.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum HashtableValue {
+    /// Integer data.
+    Integer(i32),
+    /// String data.
+    String(String),
+    /// Pointer data.
+    Pointer(*mut c_void),
+    /// Raw data. Weechat doesn't expose the size of raw data, values of this
+    /// type that are read from a hashtable are returned as `Pointer` values.
+    Buffer(Vec<u8>),
+    /// Time data, as a unix timestamp.
+    Time(i64),
+}
+
+impl HashtableValue {
+    /// Get the hashtable type of the value.
+    pub fn hashtable_type(&self) -> HashtableType {
+        match self {
+            HashtableValue::Integer(_) => HashtableType::Integer,
+            HashtableValue::String(_) => HashtableType::String,
+            HashtableValue::Pointer(_) => HashtableType::Pointer,
+            HashtableValue::Buffer(_) => HashtableType::Buffer,
+            HashtableValue::Time(_) => HashtableType::Time,
+        }
+    }
+
+    /// Run a function with a pointer to the raw representation of the value
+    /// and its size.
+    fn with_raw<R>(&self, f: impl FnOnce(*const c_void, c_int) -> R) -> R {
+        match self {
+            HashtableValue::Integer(integer) => {
+                let integer = *integer as c_int;
+                f(
+                    &integer as *const c_int as *const c_void,
+                    mem::size_of::<c_int>() as c_int,
+                )
+            }
+            HashtableValue::String(string) => {
+                let string = LossyCString::new(string);
+                f(string.as_ptr() as *const c_void, 0)
+            }
+            HashtableValue::Pointer(pointer) => f(*pointer as *const c_void, 0),
+            HashtableValue::Buffer(data) => {
+                f(data.as_ptr() as *const c_void, data.len() as c_int)
+            }
+            HashtableValue::Time(time) => {
+                let time = *time as time_t;
+                f(
+                    &time as *const time_t as *const c_void,
+                    mem::size_of::<time_t>() as c_int,
+                )
+            }
+        }
+    }
+
+    unsafe fn from_raw(
+        hashtable_type: HashtableType,
+        data: *const c_void,
+    ) -> Option<HashtableValue> {
+        if data.is_null() && hashtable_type != HashtableType::Pointer {
+            return None;
+        }
+
+        let value = match hashtable_type {
+            HashtableType::Integer => {
+                HashtableValue::Integer(*(data as *const c_int))
+            }
+            HashtableType::String => HashtableValue::String(
+                CStr::from_ptr(data as *const c_char)
+                    .to_string_lossy()
+                    .into_owned(),
+            ),
+            HashtableType::Pointer | HashtableType::Buffer => {
+                HashtableValue::Pointer(data as *mut c_void)
+            }
+            HashtableType::Time => {
+                HashtableValue::Time(*(data as *const time_t) as i64)
+            }
+        };
+
+        Some(value)
+    }
+}
+
+impl From<i32> for HashtableValue {
+    fn from(integer: i32) -> HashtableValue {
+        HashtableValue::Integer(integer)
+    }
+}
+
+impl From<&str> for HashtableValue {
+    fn from(string: &str) -> HashtableValue {
+        HashtableValue::String(string.to_owned())
+    }
+}
+
+impl From<String> for HashtableValue {
+    fn from(string: String) -> HashtableValue {
+        HashtableValue::String(string)
+    }
+}
+
+impl From<*mut c_void> for HashtableValue {
+    fn from(pointer: *mut c_void) -> HashtableValue {
+        HashtableValue::Pointer(pointer)
+    }
+}
+
+impl From<Vec<u8>> for HashtableValue {
+    fn from(data: Vec<u8>) -> HashtableValue {
+        HashtableValue::Buffer(data)
+    }
+}
+
+/// Weechat Hashtable type.
+///
+/// Hashtables created by the plugin are freed when the object is dropped,
+/// hashtables that weechat passes to callbacks are only borrowed and are left
+/// alone.
+pub struct Hashtable {
+    pub(crate) ptr: *mut t_hashtable,
+    pub(crate) weechat_ptr: *mut t_weechat_plugin,
+    key_type: HashtableType,
+    value_type: HashtableType,
+    owned: bool,
+}
+
+impl Drop for Hashtable {
+    fn drop(&mut self) {
+        if self.owned {
+            let weechat = Weechat::from_ptr(self.weechat_ptr);
+            let hashtable_free = weechat.get().hashtable_free.unwrap();
+            unsafe { hashtable_free(self.ptr) };
+        }
+    }
+}
+
+impl Weechat {
+    /// Create a new empty hashtable.
+    /// * `key_type` - The type of the keys of the hashtable.
+    /// * `value_type` - The type of the values of the hashtable.
+    pub fn hashtable_new(
+        &self,
+        key_type: HashtableType,
+        value_type: HashtableType,
+    ) -> Result<Hashtable, Error> {
+        let hashtable_new = self.get().hashtable_new.unwrap();
+
+        let c_key_type = LossyCString::new(key_type.as_str());
+        let c_value_type = LossyCString::new(value_type.as_str());
+
+        let ptr = unsafe {
+            hashtable_new(
+                32,
+                c_key_type.as_ptr(),
+                c_value_type.as_ptr(),
+                None,
+                None,
+            )
+        };
+
+        if ptr.is_null() {
+            return Err(Error::HashtableCreation);
+        }
+
+        Ok(Hashtable {
+            ptr,
+            weechat_ptr: self.ptr,
+            key_type,
+            value_type,
+            owned: true,
+        })
+    }
+
+    /// Create a new hashtable with string keys and values out of a HashMap.
+    pub fn hashtable_from_map(
+        &self,
+        map: &HashMap<String, String>,
+    ) -> Result<Hashtable, Error> {
+        let mut hashtable =
+            self.hashtable_new(HashtableType::String, HashtableType::String)?;
+
+        for (key, value) in map {
+            hashtable.set(key.as_str(), value.as_str());
+        }
+
+        Ok(hashtable)
+    }
+}
+
+impl Hashtable {
+    /// Create a borrowed Hashtable object from a C hashtable pointer, the
+    /// hashtable won't be freed when the object is dropped.
+    pub(crate) fn from_ptr(
+        weechat_ptr: *mut t_weechat_plugin,
+        ptr: *mut t_hashtable,
+    ) -> Hashtable {
+        let weechat = Weechat::from_ptr(weechat_ptr);
+
+        Hashtable {
+            ptr,
+            weechat_ptr,
+            key_type: Hashtable::get_type(&weechat, ptr, "type_keys"),
+            value_type: Hashtable::get_type(&weechat, ptr, "type_values"),
+            owned: false,
+        }
+    }
+
+    /// Give up ownership of the hashtable and return the raw pointer, used to
+    /// hand hashtables over to weechat.
+    pub(crate) fn into_raw(self) -> *mut t_hashtable {
+        let ptr = self.ptr;
+        mem::forget(self);
+        ptr
+    }
+
+    fn get_weechat(&self) -> Weechat {
+        Weechat::from_ptr(self.weechat_ptr)
+    }
+
+    fn get_type(
+        weechat: &Weechat,
+        ptr: *mut t_hashtable,
+        property: &str,
+    ) -> HashtableType {
+        let hashtable_get_string = weechat.get().hashtable_get_string.unwrap();
+
+        let property = LossyCString::new(property);
+
+        let hashtable_type = unsafe {
+            let ret = hashtable_get_string(ptr, property.as_ptr());
+            if ret.is_null() {
+                Cow::from("")
+            } else {
+                CStr::from_ptr(ret).to_string_lossy()
+            }
+        };
+
+        HashtableType::from_str(&hashtable_type)
+            .unwrap_or(HashtableType::String)
+    }
+
+    /// Get the type of the keys of the hashtable.
+    pub fn key_type(&self) -> HashtableType {
+        self.key_type
+    }
+
+    /// Get the type of the values of the hashtable.
+    pub fn value_type(&self) -> HashtableType {
+        self.value_type
+    }
+
+    /// Get the number of items in the hashtable.
+    pub fn len(&self) -> usize {
+        let weechat = self.get_weechat();
+        let hashtable_get_integer =
+            weechat.get().hashtable_get_integer.unwrap();
+
+        let property = LossyCString::new("items_count");
+
+        let count =
+            unsafe { hashtable_get_integer(self.ptr, property.as_ptr()) };
+        count as usize
+    }
+
+    /// Returns true if the hashtable contains no items.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Add or update an item in the hashtable.
+    /// * `key` - The key of the item, must match the key type of the
+    ///     hashtable.
+    /// * `value` - The value of the item, must match the value type of the
+    ///     hashtable.
+    /// Returns false if the types don't match or the item couldn't be set.
+    pub fn set<K, V>(&mut self, key: K, value: V) -> bool
+    where
+        K: Into<HashtableValue>,
+        V: Into<HashtableValue>,
+    {
+        let key = key.into();
+        let value = value.into();
+
+        if key.hashtable_type() != self.key_type
+            || value.hashtable_type() != self.value_type
+        {
+            return false;
+        }
+
+        let weechat = self.get_weechat();
+        let hashtable_set_with_size =
+            weechat.get().hashtable_set_with_size.unwrap();
+
+        let item = key.with_raw(|key, key_size| {
+            value.with_raw(|value, value_size| unsafe {
+                hashtable_set_with_size(
+                    self.ptr, key, key_size, value, value_size,
+                )
+            })
+        });
+
+        !item.is_null()
+    }
+
+    /// Get the value of an item in the hashtable.
+    /// * `key` - The key of the item.
+    /// Returns None if the hashtable doesn't contain the key.
+    pub fn get<K: Into<HashtableValue>>(
+        &self,
+        key: K,
+    ) -> Option<HashtableValue> {
+        let key = key.into();
+
+        if key.hashtable_type() != self.key_type {
+            return None;
+        }
+
+        let weechat = self.get_weechat();
+        let hashtable_has_key = weechat.get().hashtable_has_key.unwrap();
+        let hashtable_get = weechat.get().hashtable_get.unwrap();
+        let value_type = self.value_type;
+
+        // Pointer values can be null, a null value alone doesn't tell if
+        // the key is missing.
+        key.with_raw(|key, _| unsafe {
+            if hashtable_has_key(self.ptr, key) == 0 {
+                return None;
+            }
+
+            let value = hashtable_get(self.ptr, key);
+            HashtableValue::from_raw(value_type, value)
+        })
+    }
+
+    /// Get the value of an item as a string, for string hashtables.
+    /// * `key` - The key of the item.
+    pub fn get_string(&self, key: &str) -> Option<String> {
+        match self.get(key) {
+            Some(HashtableValue::String(string)) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the hashtable contains the key.
+    /// * `key` - The key to look for.
+    pub fn contains_key<K: Into<HashtableValue>>(&self, key: K) -> bool {
+        let key = key.into();
+
+        if key.hashtable_type() != self.key_type {
+            return false;
+        }
+
+        let weechat = self.get_weechat();
+        let hashtable_has_key = weechat.get().hashtable_has_key.unwrap();
+
+        key.with_raw(|key, _| unsafe { hashtable_has_key(self.ptr, key) != 0 })
+    }
+
+    /// Remove an item from the hashtable.
+    /// * `key` - The key of the item that should be removed.
+    pub fn remove<K: Into<HashtableValue>>(&mut self, key: K) {
+        let key = key.into();
+
+        if key.hashtable_type() != self.key_type {
+            return;
+        }
+
+        let weechat = self.get_weechat();
+        let hashtable_remove = weechat.get().hashtable_remove.unwrap();
+
+        key.with_raw(|key, _| unsafe { hashtable_remove(self.ptr, key) })
+    }
+
+    /// Remove all items from the hashtable.
+    pub fn clear(&mut self) {
+        let weechat = self.get_weechat();
+        let hashtable_remove_all = weechat.get().hashtable_remove_all.unwrap();
+
+        unsafe { hashtable_remove_all(self.ptr) }
+    }
+
+    /// Iterate over the items of the hashtable. The items are copied out of
+    /// the hashtable.
+    pub fn iter(&self) -> vec::IntoIter<(HashtableValue, HashtableValue)> {
+        struct MapData {
+            key_type: HashtableType,
+            value_type: HashtableType,
+            items: Vec<(HashtableValue, HashtableValue)>,
+        }
+
+        unsafe extern "C" fn c_map_cb(
+            data: *mut c_void,
+            _hashtable: *mut t_hashtable,
+            key: *const c_void,
+            value: *const c_void,
+        ) {
+            let data: &mut MapData = { &mut *(data as *mut MapData) };
+
+            let key = HashtableValue::from_raw(data.key_type, key);
+            let value = HashtableValue::from_raw(data.value_type, value);
+
+            if let (Some(key), Some(value)) = (key, value) {
+                data.items.push((key, value));
+            }
+        }
+
+        let mut data = MapData {
+            key_type: self.key_type,
+            value_type: self.value_type,
+            items: Vec::new(),
+        };
+
+        let weechat = self.get_weechat();
+        let hashtable_map = weechat.get().hashtable_map.unwrap();
+
+        unsafe {
+            hashtable_map(
+                self.ptr,
+                Some(c_map_cb),
+                &mut data as *mut _ as *mut c_void,
+            )
+        };
+
+        data.items.into_iter()
+    }
+
+    /// Copy the items of the hashtable into a HashMap.
+    pub fn to_map(&self) -> HashMap<HashtableValue, HashtableValue> {
+        self.iter().collect()
+    }
+
+    /// Copy the items of the hashtable into a HashMap, keys and values are
+    /// converted to strings the same way weechat does it for scripts.
+    pub fn to_string_map(&self) -> HashMap<String, String> {
+        unsafe extern "C" fn c_map_cb(
+            data: *mut c_void,
+            _hashtable: *mut t_hashtable,
+            key: *const c_char,
+            value: *const c_char,
+        ) {
+            let map: &mut HashMap<String, String> =
+                { &mut *(data as *mut HashMap<String, String>) };
+
+            if key.is_null() {
+                return;
+            }
+
+            let key = CStr::from_ptr(key).to_string_lossy().into_owned();
+            let value = if value.is_null() {
+                String::new()
+            } else {
+                CStr::from_ptr(value).to_string_lossy().into_owned()
+            };
+
+            map.insert(key, value);
+        }
+
+        let mut map = HashMap::new();
+
+        if self.ptr.is_null() {
+            return map;
+        }
+
+        let weechat = self.get_weechat();
+        let hashtable_map_string = weechat.get().hashtable_map_string.unwrap();
+
+        unsafe {
+            hashtable_map_string(
+                self.ptr,
+                Some(c_map_cb),
+                &mut map as *mut _ as *mut c_void,
+            )
+        };
+
+        map
+    }
+}
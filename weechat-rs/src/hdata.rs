@@ -0,0 +1,477 @@
+//! Weechat Hdata module, a reflection API to read and update weechat and
+//! plugin data structures.
+
+use libc::{c_char, c_int};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::os::raw::c_void;
+
+use weechat_sys::{t_gui_buffer, t_hdata, t_weechat_plugin};
+
+use crate::{Buffer, Hashtable, LossyCString, Weechat};
+
+/// The type of a hdata variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HdataType {
+    /// A variable with a type that has no accessor, e.g. a struct.
+    Other,
+    /// A char variable.
+    Char,
+    /// An integer variable.
+    Integer,
+    /// A long variable.
+    Long,
+    /// A string variable.
+    String,
+    /// A pointer variable.
+    Pointer,
+    /// A time variable.
+    Time,
+    /// A hashtable variable.
+    Hashtable,
+    /// A shared string variable, can be read like a string.
+    SharedString,
+}
+
+impl HdataType {
+    pub(crate) fn from_int(hdata_type: c_int) -> Option<HdataType> {
+        match hdata_type {
+            weechat_sys::WEECHAT_HDATA_OTHER => Some(HdataType::Other),
+            weechat_sys::WEECHAT_HDATA_CHAR => Some(HdataType::Char),
+            weechat_sys::WEECHAT_HDATA_INTEGER => Some(HdataType::Integer),
+            weechat_sys::WEECHAT_HDATA_LONG => Some(HdataType::Long),
+            weechat_sys::WEECHAT_HDATA_STRING => Some(HdataType::String),
+            weechat_sys::WEECHAT_HDATA_POINTER => Some(HdataType::Pointer),
+            weechat_sys::WEECHAT_HDATA_TIME => Some(HdataType::Time),
+            weechat_sys::WEECHAT_HDATA_HASHTABLE => Some(HdataType::Hashtable),
+            weechat_sys::WEECHAT_HDATA_SHARED_STRING => {
+                Some(HdataType::SharedString)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Weechat Hdata type, describes the layout of a weechat or plugin data
+/// structure.
+///
+/// Hdata objects are owned by weechat and live as long as the plugin or
+/// weechat that created them, nothing is freed when the object is dropped.
+#[derive(Debug, Clone)]
+pub struct Hdata {
+    pub(crate) ptr: *mut t_hdata,
+    pub(crate) weechat_ptr: *mut t_weechat_plugin,
+}
+
+/// A cursor pointing to a data structure described by a Hdata object.
+///
+/// The cursor can be used to read the variables of the structure and to move
+/// through the list the structure is part of.
+#[derive(Debug, Clone)]
+pub struct HdataPointer {
+    hdata: Hdata,
+    ptr: *mut c_void,
+}
+
+/// An iterator over the elements of a hdata list, see `HdataPointer::iter()`.
+pub struct HdataIter {
+    current: Option<HdataPointer>,
+    count: i32,
+}
+
+impl Iterator for HdataIter {
+    type Item = HdataPointer;
+
+    fn next(&mut self) -> Option<HdataPointer> {
+        let current = self.current.take()?;
+        self.current = current.move_by(self.count);
+        Some(current)
+    }
+}
+
+impl Weechat {
+    /// Get the hdata for a weechat or plugin data structure.
+    /// * `name` - The name of the hdata, for example "buffer" or
+    ///     "irc_server". See the weechat API documentation for a list of
+    ///     hdata names.
+    /// Returns None if no hdata with the given name exists.
+    pub fn hdata_get(&self, name: &str) -> Option<Hdata> {
+        let hdata_get = self.get().hdata_get.unwrap();
+
+        let name = LossyCString::new(name);
+
+        let ptr = unsafe { hdata_get(self.ptr, name.as_ptr()) };
+
+        if ptr.is_null() {
+            None
+        } else {
+            Some(Hdata {
+                ptr,
+                weechat_ptr: self.ptr,
+            })
+        }
+    }
+}
+
+impl Hdata {
+    fn get_weechat(&self) -> Weechat {
+        Weechat::from_ptr(self.weechat_ptr)
+    }
+
+    /// Create a cursor for a raw pointer, returns None if the pointer is
+    /// null.
+    ///
+    /// # Safety
+    /// The pointer needs to point to a valid structure of the type that is
+    /// described by this hdata.
+    pub unsafe fn pointer_from_raw(
+        &self,
+        ptr: *mut c_void,
+    ) -> Option<HdataPointer> {
+        if ptr.is_null() {
+            None
+        } else {
+            Some(HdataPointer {
+                hdata: self.clone(),
+                ptr,
+            })
+        }
+    }
+
+    /// Get a string property of the hdata.
+    /// * `property` - The name of the property, for example "var_prev",
+    ///     "var_next", "var_keys" or "list_keys".
+    pub fn get_string(&self, property: &str) -> Option<Cow<str>> {
+        let weechat = self.get_weechat();
+        let hdata_get_string = weechat.get().hdata_get_string.unwrap();
+
+        let property = LossyCString::new(property);
+
+        unsafe {
+            let ret = hdata_get_string(self.ptr, property.as_ptr());
+            if ret.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(ret).to_string_lossy())
+            }
+        }
+    }
+
+    /// Get the type of a variable.
+    /// * `name` - The name of the variable.
+    /// Returns None if the variable doesn't exist.
+    pub fn var_type(&self, name: &str) -> Option<HdataType> {
+        let weechat = self.get_weechat();
+        let hdata_get_var_type = weechat.get().hdata_get_var_type.unwrap();
+
+        let name = LossyCString::new(name);
+
+        let ret = unsafe { hdata_get_var_type(self.ptr, name.as_ptr()) };
+
+        HdataType::from_int(ret)
+    }
+
+    /// Get the name of the hdata that describes the structure a variable
+    /// points to.
+    /// * `name` - The name of the variable.
+    /// Returns None if the variable doesn't exist or has no hdata.
+    pub fn var_hdata(&self, name: &str) -> Option<Cow<str>> {
+        let weechat = self.get_weechat();
+        let hdata_get_var_hdata = weechat.get().hdata_get_var_hdata.unwrap();
+
+        let name = LossyCString::new(name);
+
+        unsafe {
+            let ret = hdata_get_var_hdata(self.ptr, name.as_ptr());
+            if ret.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(ret).to_string_lossy())
+            }
+        }
+    }
+
+    /// Get a list of the hdata.
+    /// * `name` - The name of the list, for example "gui_buffers" or
+    ///     "last_gui_buffer" for the "buffer" hdata.
+    /// Returns a cursor pointing to the list, None if the list doesn't exist
+    /// or is empty.
+    pub fn get_list(&self, name: &str) -> Option<HdataPointer> {
+        let weechat = self.get_weechat();
+        let hdata_get_list = weechat.get().hdata_get_list.unwrap();
+
+        let name = LossyCString::new(name);
+
+        unsafe {
+            let ptr = hdata_get_list(self.ptr, name.as_ptr());
+            self.pointer_from_raw(ptr)
+        }
+    }
+
+    /// Check if a cursor still points to an element of a list of the hdata,
+    /// e.g. to check if a previously stored cursor is still valid.
+    /// * `list` - The list that should be searched.
+    /// * `pointer` - The cursor that should be checked.
+    pub fn check_pointer(
+        &self,
+        list: &HdataPointer,
+        pointer: &HdataPointer,
+    ) -> bool {
+        let weechat = self.get_weechat();
+        let hdata_check_pointer = weechat.get().hdata_check_pointer.unwrap();
+
+        let ret =
+            unsafe { hdata_check_pointer(self.ptr, list.ptr, pointer.ptr) };
+
+        ret == 1
+    }
+}
+
+impl HdataPointer {
+    fn get_weechat(&self) -> Weechat {
+        self.hdata.get_weechat()
+    }
+
+    /// Get the hdata that describes the structure of the cursor.
+    pub fn hdata(&self) -> &Hdata {
+        &self.hdata
+    }
+
+    /// Get the raw pointer of the structure the cursor points to.
+    pub fn as_ptr(&self) -> *mut c_void {
+        self.ptr
+    }
+
+    /// Move the cursor through the list the structure is part of.
+    /// * `count` - The number of elements to move, a negative number moves
+    ///     backward.
+    /// Returns a new cursor, or None if the end of the list was reached.
+    pub fn move_by(&self, count: i32) -> Option<HdataPointer> {
+        let weechat = self.get_weechat();
+        let hdata_move = weechat.get().hdata_move.unwrap();
+
+        unsafe {
+            let ptr = hdata_move(self.hdata.ptr, self.ptr, count);
+            self.hdata.pointer_from_raw(ptr)
+        }
+    }
+
+    /// Get a cursor pointing to the next element of the list.
+    pub fn next(&self) -> Option<HdataPointer> {
+        self.move_by(1)
+    }
+
+    /// Get a cursor pointing to the previous element of the list.
+    pub fn prev(&self) -> Option<HdataPointer> {
+        self.move_by(-1)
+    }
+
+    /// Iterate over the list starting at this element, moving forward.
+    pub fn iter(&self) -> HdataIter {
+        HdataIter {
+            current: Some(self.clone()),
+            count: 1,
+        }
+    }
+
+    /// Iterate over the list starting at this element, moving backward.
+    pub fn iter_rev(&self) -> HdataIter {
+        HdataIter {
+            current: Some(self.clone()),
+            count: -1,
+        }
+    }
+
+    /// Search an element of the list, starting at this element.
+    /// * `expression` - The expression that is evaluated for every element,
+    ///     the current element can be accessed using the name of the hdata,
+    ///     e.g. `${irc_server.name} == libera`.
+    /// * `move_by` - The number of elements to move after every element that
+    ///     didn't match, a negative number searches backward.
+    /// Returns a cursor pointing to the first element that matched.
+    pub fn search(
+        &self,
+        expression: &str,
+        move_by: i32,
+    ) -> Option<HdataPointer> {
+        let weechat = self.get_weechat();
+        let hdata_search = weechat.get().hdata_search.unwrap();
+
+        let expression = LossyCString::new(expression);
+
+        unsafe {
+            let ptr = hdata_search(
+                self.hdata.ptr,
+                self.ptr,
+                expression.as_ptr(),
+                move_by,
+            );
+            self.hdata.pointer_from_raw(ptr)
+        }
+    }
+
+    /// Get the value of a char variable.
+    /// * `name` - The name of the variable.
+    pub fn get_char(&self, name: &str) -> c_char {
+        let weechat = self.get_weechat();
+        let hdata_char = weechat.get().hdata_char.unwrap();
+
+        let name = LossyCString::new(name);
+
+        unsafe { hdata_char(self.hdata.ptr, self.ptr, name.as_ptr()) }
+    }
+
+    /// Get the value of an integer variable.
+    /// * `name` - The name of the variable.
+    pub fn get_integer(&self, name: &str) -> i32 {
+        let weechat = self.get_weechat();
+        let hdata_integer = weechat.get().hdata_integer.unwrap();
+
+        let name = LossyCString::new(name);
+
+        unsafe { hdata_integer(self.hdata.ptr, self.ptr, name.as_ptr()) }
+    }
+
+    /// Get the value of a long variable.
+    /// * `name` - The name of the variable.
+    pub fn get_long(&self, name: &str) -> i64 {
+        let weechat = self.get_weechat();
+        let hdata_long = weechat.get().hdata_long.unwrap();
+
+        let name = LossyCString::new(name);
+
+        unsafe { hdata_long(self.hdata.ptr, self.ptr, name.as_ptr()) as i64 }
+    }
+
+    /// Get the value of a string variable.
+    /// * `name` - The name of the variable.
+    pub fn get_string(&self, name: &str) -> Option<Cow<str>> {
+        let weechat = self.get_weechat();
+        let hdata_string = weechat.get().hdata_string.unwrap();
+
+        let name = LossyCString::new(name);
+
+        unsafe {
+            let ret = hdata_string(self.hdata.ptr, self.ptr, name.as_ptr());
+            if ret.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(ret).to_string_lossy())
+            }
+        }
+    }
+
+    /// Get the value of a pointer variable.
+    /// * `name` - The name of the variable.
+    pub fn get_pointer(&self, name: &str) -> *mut c_void {
+        let weechat = self.get_weechat();
+        let hdata_pointer = weechat.get().hdata_pointer.unwrap();
+
+        let name = LossyCString::new(name);
+
+        unsafe { hdata_pointer(self.hdata.ptr, self.ptr, name.as_ptr()) }
+    }
+
+    /// Follow a pointer variable to the structure it points to.
+    /// * `name` - The name of the variable, the variable needs to have a
+    ///     hdata, see `Hdata::var_hdata()`.
+    /// Returns a cursor for the structure, None if the pointer is null or the
+    /// variable has no hdata.
+    pub fn get_hdata_pointer(&self, name: &str) -> Option<HdataPointer> {
+        let weechat = self.get_weechat();
+        let hdata = weechat.hdata_get(&self.hdata.var_hdata(name)?)?;
+
+        unsafe { hdata.pointer_from_raw(self.get_pointer(name)) }
+    }
+
+    /// Get the value of a buffer pointer variable.
+    /// * `name` - The name of the variable, for example "buffer".
+    /// Returns None if the pointer is null.
+    pub fn get_buffer(&self, name: &str) -> Option<Buffer> {
+        let ptr = self.get_pointer(name);
+
+        if ptr.is_null() {
+            None
+        } else {
+            Some(Buffer::from_ptr(
+                self.hdata.weechat_ptr,
+                ptr as *mut t_gui_buffer,
+            ))
+        }
+    }
+
+    /// Get the value of a time variable as a unix timestamp.
+    /// * `name` - The name of the variable.
+    pub fn get_time(&self, name: &str) -> i64 {
+        let weechat = self.get_weechat();
+        let hdata_time = weechat.get().hdata_time.unwrap();
+
+        let name = LossyCString::new(name);
+
+        unsafe { hdata_time(self.hdata.ptr, self.ptr, name.as_ptr()) as i64 }
+    }
+
+    /// Get the value of a hashtable variable.
+    /// * `name` - The name of the variable.
+    /// Returns None if the hashtable pointer is null, the hashtable is
+    /// borrowed and won't be freed when dropped.
+    pub fn get_hashtable(&self, name: &str) -> Option<Hashtable> {
+        let weechat = self.get_weechat();
+        let hdata_hashtable = weechat.get().hdata_hashtable.unwrap();
+
+        let name = LossyCString::new(name);
+
+        let ptr =
+            unsafe { hdata_hashtable(self.hdata.ptr, self.ptr, name.as_ptr()) };
+
+        if ptr.is_null() {
+            None
+        } else {
+            Some(Hashtable::from_ptr(self.hdata.weechat_ptr, ptr))
+        }
+    }
+
+    /// Get the size of an array variable.
+    /// * `name` - The name of the variable.
+    /// Returns None if the variable isn't an array.
+    pub fn get_array_size(&self, name: &str) -> Option<usize> {
+        let weechat = self.get_weechat();
+        let hdata_get_var_array_size =
+            weechat.get().hdata_get_var_array_size.unwrap();
+
+        let name = LossyCString::new(name);
+
+        let ret = unsafe {
+            hdata_get_var_array_size(self.hdata.ptr, self.ptr, name.as_ptr())
+        };
+
+        if ret < 0 {
+            None
+        } else {
+            Some(ret as usize)
+        }
+    }
+
+    /// Update variables of the structure.
+    ///
+    /// Only variables that weechat allows to be updated are changed, other
+    /// variables are ignored.
+    /// * `values` - A map of variable names and their new values, the values
+    ///     are converted by weechat to the type of the variable.
+    /// Returns the number of variables that were updated.
+    pub fn update(&self, values: &HashMap<String, String>) -> usize {
+        let weechat = self.get_weechat();
+        let hdata_update = weechat.get().hdata_update.unwrap();
+
+        let hashtable = match weechat.hashtable_from_map(values) {
+            Ok(hashtable) => hashtable,
+            Err(_) => return 0,
+        };
+
+        let ret =
+            unsafe { hdata_update(self.hdata.ptr, self.ptr, hashtable.ptr) };
+
+        ret.max(0) as usize
+    }
+}
@@ -13,7 +13,38 @@ use std::time::Duration;
 
 use weechat_sys::{t_gui_buffer, t_hook, t_weechat_plugin, WEECHAT_RC_OK};
 
-use crate::{ArgsWeechat, Buffer, LossyCString, ReturnCode, Weechat};
+use crate::{
+    ArgsWeechat, Buffer, CallbackResult, Error, LossyCString, ReturnCode,
+    Weechat,
+};
+
+mod config;
+mod connect;
+#[cfg(feature = "async")]
+mod future;
+mod hsignal;
+mod info;
+mod infolist;
+mod main_thread;
+mod modifier;
+mod print;
+mod process;
+
+pub use config::ConfigHook;
+pub use connect::{
+    ConnectHook, ConnectResult, ConnectSettings, ConnectStatus, GnutlsOptions,
+};
+#[cfg(feature = "async")]
+pub use future::{ConnectFuture, ProcessFuture, ProcessResult, Sleep};
+pub use hsignal::HsignalHook;
+pub use info::{InfoHashtableHook, InfoHook};
+pub use infolist::InfolistHook;
+pub use main_thread::{MainThreadHook, MainThreadSender, MainThreadTask};
+pub use modifier::ModifierHook;
+pub use print::{LineHook, PrintHook, PrintedLine};
+pub use process::{
+    ProcessHook, ProcessOptions, ProcessOutput, ProcessReturnCode,
+};
 
 /// Weechat Hook type. The hook is unhooked automatically when the object is
 /// dropped.
@@ -32,14 +63,16 @@ impl Drop for Hook {
 
 /// Hook for a weechat command, the command is removed when the object is
 /// dropped.
-pub struct CommandHook<T> {
+pub struct CommandHook {
     _hook: Hook,
-    _hook_data: Box<CommandHookData<T>>,
+    _hook_data: Box<CommandHookData>,
 }
 
-struct CommandHookData<T> {
-    callback: fn(&T, Buffer, ArgsWeechat),
-    callback_data: T,
+type CommandHookCbT =
+    Box<dyn FnMut(&Weechat, Buffer, ArgsWeechat) -> ReturnCode>;
+
+struct CommandHookData {
+    callback: CommandHookCbT,
     weechat_ptr: *mut t_weechat_plugin,
 }
 
@@ -71,38 +104,43 @@ impl FdHookMode {
 }
 
 /// Hook for a file descriptor, the hook is removed when the object is dropped.
-pub struct FdHook<T, F> {
+pub struct FdHook<F> {
     _hook: Hook,
-    _hook_data: Box<FdHookData<T, F>>,
+    _hook_data: Box<FdHookData<F>>,
 }
 
-struct FdHookData<T, F> {
-    callback: fn(&T, fd_object: &mut F),
-    callback_data: T,
+type FdHookCbT<F> = Box<dyn FnMut(&Weechat, &mut F) -> ReturnCode>;
+
+struct FdHookData<F> {
+    callback: FdHookCbT<F>,
     fd_object: F,
+    weechat_ptr: *mut t_weechat_plugin,
 }
 
 /// Hook for a weechat command, the hook is removed when the object is dropped.
-pub struct CommandRunHook<T> {
+pub struct CommandRunHook {
     _hook: Hook,
-    _hook_data: Box<CommandRunHookData<T>>,
+    _hook_data: Box<CommandRunHookData>,
 }
 
-struct CommandRunHookData<T> {
-    callback: fn(&T, Buffer, Cow<str>) -> ReturnCode,
-    callback_data: T,
+type CommandRunHookCbT =
+    Box<dyn FnMut(&Weechat, Buffer, Cow<str>) -> ReturnCode>;
+
+struct CommandRunHookData {
+    callback: CommandRunHookCbT,
     weechat_ptr: *mut t_weechat_plugin,
 }
 
 /// Hook for a signal, the hook is removed when the object is dropped.
-pub struct SignalHook<T> {
+pub struct SignalHook {
     _hook: Hook,
-    _hook_data: Box<SignalHookData<T>>,
+    _hook_data: Box<SignalHookData>,
 }
 
-struct SignalHookData<T> {
-    callback: fn(&T, &Weechat, SignalHookValue) -> ReturnCode,
-    callback_data: T,
+type SignalHookCbT = Box<dyn FnMut(&Weechat, SignalHookValue) -> ReturnCode>;
+
+struct SignalHookData {
+    callback: SignalHookCbT,
     weechat_ptr: *mut t_weechat_plugin,
 }
 
@@ -115,6 +153,8 @@ pub enum SignalHookValue {
     Integer(i32),
     /// Pointer data
     Pointer(*mut c_void),
+    /// Buffer data, a signal sent with a buffer carries the buffer pointer.
+    Buffer(Buffer),
 }
 
 impl SignalHookValue {
@@ -130,7 +170,7 @@ impl SignalHookValue {
                         .into_owned(),
                 ))
             },
-            "integer" => {
+            "int" => {
                 let data = data as *const c_int;
                 if data.is_null() {
                     None
@@ -142,17 +182,27 @@ impl SignalHookValue {
             _ => None,
         }
     }
+
+    pub(crate) fn data_type(&self) -> &'static str {
+        match self {
+            SignalHookValue::String(_) => "string",
+            SignalHookValue::Integer(_) => "int",
+            SignalHookValue::Pointer(_) => "pointer",
+            SignalHookValue::Buffer(_) => "pointer",
+        }
+    }
 }
 
 /// A hook for a timer, the hook will be removed when the object is dropped.
-pub struct TimerHook<T> {
+pub struct TimerHook {
     _hook: Hook,
-    _hook_data: Box<TimerHookData<T>>,
+    _hook_data: Box<TimerHookData>,
 }
 
-struct TimerHookData<T> {
-    callback: fn(&T, &Weechat, i32),
-    callback_data: T,
+type TimerHookCbT = Box<dyn FnMut(&Weechat, i32) -> ReturnCode>;
+
+struct TimerHookData {
+    callback: TimerHookCbT,
     weechat_ptr: *mut t_weechat_plugin,
 }
 
@@ -173,19 +223,25 @@ pub struct CommandDescription<'a> {
     pub completion: &'a str,
 }
 
-impl Weechat {
+impl CommandHook {
     /// Create a new weechat command. Returns the hook of the command. The
     /// command is unhooked if the hook is dropped.
-    pub fn hook_command<T>(
-        &self,
+    ///
+    /// * `weechat` - The weechat object the command is created with.
+    /// * `command_info` - The description of the command.
+    /// * `callback` - A closure that will be called when the command is run.
+    ///
+    /// Returns an error if Weechat failed to create the command.
+    pub fn new<C, R>(
+        weechat: &Weechat,
         command_info: CommandDescription,
-        callback: fn(data: &T, buffer: Buffer, args: ArgsWeechat),
-        callback_data: Option<T>,
-    ) -> CommandHook<T>
+        mut callback: C,
+    ) -> Result<CommandHook, Error>
     where
-        T: Default,
+        C: FnMut(&Weechat, Buffer, ArgsWeechat) -> R + 'static,
+        R: CallbackResult,
     {
-        unsafe extern "C" fn c_hook_cb<T>(
+        unsafe extern "C" fn c_hook_cb(
             pointer: *const c_void,
             _data: *mut c_void,
             buffer: *mut t_gui_buffer,
@@ -193,16 +249,13 @@ impl Weechat {
             argv: *mut *mut c_char,
             _argv_eol: *mut *mut c_char,
         ) -> c_int {
-            let hook_data: &mut CommandHookData<T> =
-                { &mut *(pointer as *mut CommandHookData<T>) };
+            let hook_data: &mut CommandHookData =
+                { &mut *(pointer as *mut CommandHookData) };
+            let weechat = Weechat::from_ptr(hook_data.weechat_ptr);
             let buffer = Buffer::from_ptr(hook_data.weechat_ptr, buffer);
-            let callback = hook_data.callback;
-            let callback_data = &hook_data.callback_data;
             let args = ArgsWeechat::new(argc, argv);
 
-            callback(callback_data, buffer, args);
-
-            WEECHAT_RC_OK
+            (hook_data.callback)(&weechat, buffer, args) as i32
         }
 
         let name = LossyCString::new(command_info.name);
@@ -212,281 +265,301 @@ impl Weechat {
         let completion = LossyCString::new(command_info.completion);
 
         let data = Box::new(CommandHookData {
-            callback,
-            callback_data: callback_data.unwrap_or_default(),
-            weechat_ptr: self.ptr,
+            callback: Box::new(move |weechat, buffer, args| {
+                callback(weechat, buffer, args).into_return_code(weechat)
+            }),
+            weechat_ptr: weechat.ptr,
         });
 
         let data_ref = Box::leak(data);
 
-        let hook_command = self.get().hook_command.unwrap();
+        let hook_command = weechat.get().hook_command.unwrap();
         let hook_ptr = unsafe {
             hook_command(
-                self.ptr,
+                weechat.ptr,
                 name.as_ptr(),
                 description.as_ptr(),
                 args.as_ptr(),
                 args_description.as_ptr(),
                 completion.as_ptr(),
-                Some(c_hook_cb::<T>),
+                Some(c_hook_cb),
                 data_ref as *const _ as *const c_void,
                 ptr::null_mut(),
             )
         };
         let hook_data = unsafe { Box::from_raw(data_ref) };
+
+        if hook_ptr.is_null() {
+            return Err(Error::HookCreation);
+        }
+
         let hook = Hook {
             ptr: hook_ptr,
-            weechat_ptr: self.ptr,
+            weechat_ptr: weechat.ptr,
         };
 
-        CommandHook::<T> {
+        Ok(CommandHook {
             _hook: hook,
             _hook_data: hook_data,
-        }
+        })
     }
+}
 
+impl<F: AsRawFd + 'static> FdHook<F> {
     /// Hook an object that can be turned into a raw file descriptor.
     /// Returns the hook object.
+    /// * `weechat` - The weechat object the hook is created with.
     /// * `fd_object` - An object for wich the file descriptor will be watched
     ///     and the callback called when read or write operations can happen
     ///     on it.
     /// * `mode` - Configure the hook to watch for writes, reads or both on the
     ///     file descriptor.
-    /// * `callback` - A function that will be called if a watched event on the
+    /// * `callback` - A closure that will be called if a watched event on the
     ///     file descriptor happends.
-    /// * `callback_data` - Data that will be passed to the callback every time
-    ///     the callback runs. This data will be freed when the hook is
-    ///     unhooked.
-    pub fn hook_fd<T, F>(
-        &self,
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn new<C, R>(
+        weechat: &Weechat,
         fd_object: F,
         mode: FdHookMode,
-        callback: fn(data: &T, fd_object: &mut F),
-        callback_data: Option<T>,
-    ) -> FdHook<T, F>
+        mut callback: C,
+    ) -> Result<FdHook<F>, Error>
     where
-        T: Default,
-        F: AsRawFd,
+        C: FnMut(&Weechat, &mut F) -> R + 'static,
+        R: CallbackResult,
     {
-        unsafe extern "C" fn c_hook_cb<T, F>(
+        unsafe extern "C" fn c_hook_cb<F>(
             pointer: *const c_void,
             _data: *mut c_void,
             _fd: i32,
         ) -> c_int {
-            let hook_data: &mut FdHookData<T, F> =
-                { &mut *(pointer as *mut FdHookData<T, F>) };
-            let callback = hook_data.callback;
-            let callback_data = &hook_data.callback_data;
+            let hook_data: &mut FdHookData<F> =
+                { &mut *(pointer as *mut FdHookData<F>) };
+            let weechat = Weechat::from_ptr(hook_data.weechat_ptr);
             let fd_object = &mut hook_data.fd_object;
 
-            callback(callback_data, fd_object);
-
-            WEECHAT_RC_OK
+            (hook_data.callback)(&weechat, fd_object) as i32
         }
 
         let fd = fd_object.as_raw_fd();
 
         let data = Box::new(FdHookData {
-            callback,
-            callback_data: callback_data.unwrap_or_default(),
+            callback: Box::new(move |weechat, fd_object| {
+                callback(weechat, fd_object).into_return_code(weechat)
+            }),
             fd_object,
+            weechat_ptr: weechat.ptr,
         });
 
         let data_ref = Box::leak(data);
-        let hook_fd = self.get().hook_fd.unwrap();
+        let hook_fd = weechat.get().hook_fd.unwrap();
         let (read, write) = mode.as_tuple();
 
         let hook_ptr = unsafe {
             hook_fd(
-                self.ptr,
+                weechat.ptr,
                 fd,
                 read,
                 write,
                 0,
-                Some(c_hook_cb::<T, F>),
+                Some(c_hook_cb::<F>),
                 data_ref as *const _ as *const c_void,
                 ptr::null_mut(),
             )
         };
         let hook_data = unsafe { Box::from_raw(data_ref) };
+
+        if hook_ptr.is_null() {
+            return Err(Error::HookCreation);
+        }
+
         let hook = Hook {
             ptr: hook_ptr,
-            weechat_ptr: self.ptr,
+            weechat_ptr: weechat.ptr,
         };
 
-        FdHook::<T, F> {
+        Ok(FdHook::<F> {
             _hook: hook,
             _hook_data: hook_data,
-        }
+        })
     }
+}
 
+impl TimerHook {
     /// Create a timer that will repeatedly fire.
     ///
+    /// * `weechat` - The weechat object the timer is created with.
     /// * `interval` - The delay between calls in milliseconds.
     /// * `align_second` - The alignment on a second. For example, if current time is 09:00, if
     ///     interval = 60000 (60 seconds), and align_second = 60, then timer is called each minute when
     ///     second is 0.
     /// * `max_calls` - The number of calls to timer (if 0, then timer has no end)
-    /// * `callback` - A function that will be called when the timer fires, the `remaining` argument
+    /// * `callback` - A closure that will be called when the timer fires, the `remaining` argument
     ///     will be -1 if the timer has no end.
-    /// * `callback_data` - Data that will be passed to the callback every time
-    ///     the callback runs. This data will be freed when the hook is unhooked.
-    pub fn hook_timer<T>(
-        &self,
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn new<C, R>(
+        weechat: &Weechat,
         interval: Duration,
         align_second: i32,
         max_calls: i32,
-        callback: fn(data: &T, weechat: &Weechat, remaining: i32),
-        callback_data: Option<T>,
-    ) -> TimerHook<T>
+        mut callback: C,
+    ) -> Result<TimerHook, Error>
     where
-        T: Default,
+        C: FnMut(&Weechat, i32) -> R + 'static,
+        R: CallbackResult,
     {
-        unsafe extern "C" fn c_hook_cb<T>(
+        unsafe extern "C" fn c_hook_cb(
             pointer: *const c_void,
             _data: *mut c_void,
             remaining: i32,
         ) -> c_int {
-            let hook_data: &mut TimerHookData<T> =
-                { &mut *(pointer as *mut TimerHookData<T>) };
-            let callback = &hook_data.callback;
-            let callback_data = &hook_data.callback_data;
+            let hook_data: &mut TimerHookData =
+                { &mut *(pointer as *mut TimerHookData) };
 
-            callback(
-                callback_data,
+            (hook_data.callback)(
                 &Weechat::from_ptr(hook_data.weechat_ptr),
                 remaining,
-            );
-
-            WEECHAT_RC_OK
+            ) as i32
         }
 
-        let data = Box::new(TimerHookData::<T> {
-            callback,
-            callback_data: callback_data.unwrap_or_default(),
-            weechat_ptr: self.ptr,
+        let data = Box::new(TimerHookData {
+            callback: Box::new(move |weechat, remaining| {
+                callback(weechat, remaining).into_return_code(weechat)
+            }),
+            weechat_ptr: weechat.ptr,
         });
 
         let data_ref = Box::leak(data);
-        let hook_timer = self.get().hook_timer.unwrap();
+        let hook_timer = weechat.get().hook_timer.unwrap();
 
         let hook_ptr = unsafe {
             hook_timer(
-                self.ptr,
+                weechat.ptr,
                 interval.as_millis() as i64,
                 align_second,
                 max_calls,
-                Some(c_hook_cb::<T>),
+                Some(c_hook_cb),
                 data_ref as *const _ as *const c_void,
                 ptr::null_mut(),
             )
         };
         let hook_data = unsafe { Box::from_raw(data_ref) };
+
+        if hook_ptr.is_null() {
+            return Err(Error::HookCreation);
+        }
+
         let hook = Hook {
             ptr: hook_ptr,
-            weechat_ptr: self.ptr,
+            weechat_ptr: weechat.ptr,
         };
 
-        TimerHook {
+        Ok(TimerHook {
             _hook: hook,
             _hook_data: hook_data,
-        }
+        })
     }
+}
 
+impl CommandRunHook {
     /// Hook a command when Weechat runs it.
     ///
+    /// * `weechat` - The weechat object the hook is created with.
     /// * `command` - The command to hook (wildcard `*` is allowed).
-    /// * `callback` - A function that will be called when the command is run.
-    /// * `callback_data` - Data that will be passed to the callback every time
-    ///     the callback runs. This data will be freed when the hook is unhooked.
-    pub fn hook_command_run<T>(
-        &self,
+    /// * `callback` - A closure that will be called when the command is run.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn new<C, R>(
+        weechat: &Weechat,
         command: &str,
-        callback: fn(data: &T, buffer: Buffer, command: Cow<str>) -> ReturnCode,
-        callback_data: Option<T>,
-    ) -> CommandRunHook<T>
+        mut callback: C,
+    ) -> Result<CommandRunHook, Error>
     where
-        T: Default,
+        C: FnMut(&Weechat, Buffer, Cow<str>) -> R + 'static,
+        R: CallbackResult,
     {
-        unsafe extern "C" fn c_hook_cb<T>(
+        unsafe extern "C" fn c_hook_cb(
             pointer: *const c_void,
             _data: *mut c_void,
             buffer: *mut t_gui_buffer,
             command: *const std::os::raw::c_char,
         ) -> c_int {
-            let hook_data: &mut CommandRunHookData<T> =
-                { &mut *(pointer as *mut CommandRunHookData<T>) };
-            let callback = hook_data.callback;
-            let callback_data = &hook_data.callback_data;
+            let hook_data: &mut CommandRunHookData =
+                { &mut *(pointer as *mut CommandRunHookData) };
 
+            let weechat = Weechat::from_ptr(hook_data.weechat_ptr);
             let buffer = Buffer::from_ptr(hook_data.weechat_ptr, buffer);
             let command = CStr::from_ptr(command).to_string_lossy();
 
-            callback(callback_data, buffer, command) as isize as i32
+            (hook_data.callback)(&weechat, buffer, command) as isize as i32
         }
 
         let data = Box::new(CommandRunHookData {
-            callback,
-            callback_data: callback_data.unwrap_or_default(),
-            weechat_ptr: self.ptr,
+            callback: Box::new(move |weechat, buffer, command| {
+                callback(weechat, buffer, command).into_return_code(weechat)
+            }),
+            weechat_ptr: weechat.ptr,
         });
 
         let data_ref = Box::leak(data);
-        let hook_timer = self.get().hook_command_run.unwrap();
+        let hook_command_run = weechat.get().hook_command_run.unwrap();
 
         let command = LossyCString::new(command);
 
         let hook_ptr = unsafe {
-            hook_timer(
-                self.ptr,
+            hook_command_run(
+                weechat.ptr,
                 command.as_ptr(),
-                Some(c_hook_cb::<T>),
+                Some(c_hook_cb),
                 data_ref as *const _ as *const c_void,
                 ptr::null_mut(),
             )
         };
         let hook_data = unsafe { Box::from_raw(data_ref) };
+
+        if hook_ptr.is_null() {
+            return Err(Error::HookCreation);
+        }
+
         let hook = Hook {
             ptr: hook_ptr,
-            weechat_ptr: self.ptr,
+            weechat_ptr: weechat.ptr,
         };
 
-        CommandRunHook::<T> {
+        Ok(CommandRunHook {
             _hook: hook,
             _hook_data: hook_data,
-        }
+        })
     }
+}
 
+impl SignalHook {
     /// Hook a signal.
     ///
+    /// * `weechat` - The weechat object the hook is created with.
     /// * `signal` - The signal to hook (wildcard `*` is allowed).
-    /// * `callback` - A function that will be called when the signal is received.
-    /// * `callback_data` - Data that will be passed to the callback every time
-    ///     the callback runs. This data will be freed when the hook is unhooked.
-    pub fn hook_signal<T>(
-        &self,
+    /// * `callback` - A closure that will be called when the signal is received.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn new<C, R>(
+        weechat: &Weechat,
         signal: &str,
-        callback: fn(
-            data: &T,
-            weechat: &Weechat,
-            signal_value: SignalHookValue,
-        ) -> ReturnCode,
-        callback_data: Option<T>,
-    ) -> SignalHook<T>
+        mut callback: C,
+    ) -> Result<SignalHook, Error>
     where
-        T: Default,
+        C: FnMut(&Weechat, SignalHookValue) -> R + 'static,
+        R: CallbackResult,
     {
-        unsafe extern "C" fn c_hook_cb<T>(
+        unsafe extern "C" fn c_hook_cb(
             pointer: *const c_void,
             _data: *mut c_void,
             _signal: *const c_char,
             data_type: *const c_char,
             signal_data: *mut c_void,
         ) -> c_int {
-            let hook_data: &mut SignalHookData<T> =
-                { &mut *(pointer as *mut SignalHookData<T>) };
-            let callback = hook_data.callback;
-            let callback_data = &hook_data.callback_data;
+            let hook_data: &mut SignalHookData =
+                { &mut *(pointer as *mut SignalHookData) };
 
             // this cannot contain invalid utf
             let data_type =
@@ -494,8 +567,7 @@ impl Weechat {
             if let Some(value) =
                 SignalHookValue::from_raw_with_type(data_type, signal_data)
             {
-                callback(
-                    callback_data,
+                (hook_data.callback)(
                     &Weechat::from_ptr(hook_data.weechat_ptr),
                     value,
                 ) as i32
@@ -505,34 +577,218 @@ impl Weechat {
         }
 
         let data = Box::new(SignalHookData {
-            callback,
-            callback_data: callback_data.unwrap_or_default(),
-            weechat_ptr: self.ptr,
+            callback: Box::new(move |weechat, value| {
+                callback(weechat, value).into_return_code(weechat)
+            }),
+            weechat_ptr: weechat.ptr,
         });
 
         let data_ref = Box::leak(data);
-        let hook_signal = self.get().hook_signal.unwrap();
+        let hook_signal = weechat.get().hook_signal.unwrap();
 
         let signal = LossyCString::new(signal);
 
         let hook_ptr = unsafe {
             hook_signal(
-                self.ptr,
+                weechat.ptr,
                 signal.as_ptr(),
-                Some(c_hook_cb::<T>),
+                Some(c_hook_cb),
                 data_ref as *const _ as *const c_void,
                 ptr::null_mut(),
             )
         };
         let hook_data = unsafe { Box::from_raw(data_ref) };
+
+        if hook_ptr.is_null() {
+            return Err(Error::HookCreation);
+        }
+
         let hook = Hook {
             ptr: hook_ptr,
-            weechat_ptr: self.ptr,
+            weechat_ptr: weechat.ptr,
         };
 
-        SignalHook::<T> {
+        Ok(SignalHook {
             _hook: hook,
             _hook_data: hook_data,
-        }
+        })
+    }
+}
+
+impl Weechat {
+    /// Create a new weechat command. Returns the hook of the command. The
+    /// command is unhooked if the hook is dropped.
+    ///
+    /// See `CommandHook::new()` to create a command with a closure.
+    /// * `command_info` - The description of the command.
+    /// * `callback` - A function that will be called when the command is run.
+    /// * `callback_data` - Data that will be passed to the callback every time
+    ///     the callback runs. This data will be freed when the hook is
+    ///     unhooked.
+    pub fn hook_command<T: 'static>(
+        &self,
+        command_info: CommandDescription,
+        callback: fn(data: &T, buffer: Buffer, args: ArgsWeechat),
+        callback_data: T,
+    ) -> Result<CommandHook, Error> {
+        CommandHook::new(self, command_info, move |_, buffer, args| {
+            callback(&callback_data, buffer, args)
+        })
+    }
+
+    /// Hook an object that can be turned into a raw file descriptor.
+    /// Returns the hook object.
+    ///
+    /// See `FdHook::new()` to create the hook with a closure.
+    /// * `fd_object` - An object for wich the file descriptor will be watched
+    ///     and the callback called when read or write operations can happen
+    ///     on it.
+    /// * `mode` - Configure the hook to watch for writes, reads or both on the
+    ///     file descriptor.
+    /// * `callback` - A function that will be called if a watched event on the
+    ///     file descriptor happends.
+    /// * `callback_data` - Data that will be passed to the callback every time
+    ///     the callback runs. This data will be freed when the hook is
+    ///     unhooked.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn hook_fd<T: 'static, F: AsRawFd + 'static>(
+        &self,
+        fd_object: F,
+        mode: FdHookMode,
+        callback: fn(data: &T, fd_object: &mut F),
+        callback_data: T,
+    ) -> Result<FdHook<F>, Error> {
+        FdHook::new(self, fd_object, mode, move |_, fd_object| {
+            callback(&callback_data, fd_object)
+        })
+    }
+
+    /// Create a timer that will repeatedly fire.
+    ///
+    /// See `TimerHook::new()` to create the timer with a closure.
+    /// * `interval` - The delay between calls in milliseconds.
+    /// * `align_second` - The alignment on a second. For example, if current time is 09:00, if
+    ///     interval = 60000 (60 seconds), and align_second = 60, then timer is called each minute when
+    ///     second is 0.
+    /// * `max_calls` - The number of calls to timer (if 0, then timer has no end)
+    /// * `callback` - A function that will be called when the timer fires, the `remaining` argument
+    ///     will be -1 if the timer has no end.
+    /// * `callback_data` - Data that will be passed to the callback every time
+    ///     the callback runs. This data will be freed when the hook is unhooked.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn hook_timer<T: 'static>(
+        &self,
+        interval: Duration,
+        align_second: i32,
+        max_calls: i32,
+        callback: fn(data: &T, weechat: &Weechat, remaining: i32),
+        callback_data: T,
+    ) -> Result<TimerHook, Error> {
+        TimerHook::new(
+            self,
+            interval,
+            align_second,
+            max_calls,
+            move |weechat, remaining| {
+                callback(&callback_data, weechat, remaining)
+            },
+        )
+    }
+
+    /// Hook a command when Weechat runs it.
+    ///
+    /// See `CommandRunHook::new()` to create the hook with a closure.
+    /// * `command` - The command to hook (wildcard `*` is allowed).
+    /// * `callback` - A function that will be called when the command is run.
+    /// * `callback_data` - Data that will be passed to the callback every time
+    ///     the callback runs. This data will be freed when the hook is unhooked.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn hook_command_run<T: 'static>(
+        &self,
+        command: &str,
+        callback: fn(data: &T, buffer: Buffer, command: Cow<str>) -> ReturnCode,
+        callback_data: T,
+    ) -> Result<CommandRunHook, Error> {
+        CommandRunHook::new(self, command, move |_, buffer, command| {
+            callback(&callback_data, buffer, command)
+        })
+    }
+
+    /// Hook a signal.
+    ///
+    /// See `SignalHook::new()` to create the hook with a closure.
+    /// * `signal` - The signal to hook (wildcard `*` is allowed).
+    /// * `callback` - A function that will be called when the signal is received.
+    /// * `callback_data` - Data that will be passed to the callback every time
+    ///     the callback runs. This data will be freed when the hook is unhooked.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn hook_signal<T: 'static>(
+        &self,
+        signal: &str,
+        callback: fn(
+            data: &T,
+            weechat: &Weechat,
+            signal_value: SignalHookValue,
+        ) -> ReturnCode,
+        callback_data: T,
+    ) -> Result<SignalHook, Error> {
+        SignalHook::new(self, signal, move |weechat, value| {
+            callback(&callback_data, weechat, value)
+        })
+    }
+
+    /// Send a signal.
+    ///
+    /// * `signal` - The name of the signal to send.
+    /// * `value` - The data that will be sent with the signal.
+    ///
+    /// Returns the return code of the last callback that handled the signal,
+    /// `ReturnCode::OkEat` means that the signal was eaten by a callback and
+    /// wasn't sent to the other callbacks.
+    pub fn signal_send(
+        &self,
+        signal: &str,
+        value: SignalHookValue,
+    ) -> ReturnCode {
+        let hook_signal_send = self.get().hook_signal_send.unwrap();
+
+        let signal = LossyCString::new(signal);
+        let data_type = LossyCString::new(value.data_type());
+
+        let ret = match value {
+            SignalHookValue::String(string) => {
+                let string = LossyCString::new(string);
+                unsafe {
+                    hook_signal_send(
+                        signal.as_ptr(),
+                        data_type.as_ptr(),
+                        string.as_ptr() as *mut c_void,
+                    )
+                }
+            }
+            SignalHookValue::Integer(mut integer) => unsafe {
+                hook_signal_send(
+                    signal.as_ptr(),
+                    data_type.as_ptr(),
+                    &mut integer as *mut i32 as *mut c_void,
+                )
+            },
+            SignalHookValue::Pointer(pointer) => unsafe {
+                hook_signal_send(signal.as_ptr(), data_type.as_ptr(), pointer)
+            },
+            SignalHookValue::Buffer(buffer) => unsafe {
+                hook_signal_send(
+                    signal.as_ptr(),
+                    data_type.as_ptr(),
+                    buffer.ptr as *mut c_void,
+                )
+            },
+        };
+
+        ReturnCode::from_int(ret)
     }
 }
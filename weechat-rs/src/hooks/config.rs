@@ -0,0 +1,142 @@
+//! Hooks for changes of weechat configuration options.
+
+use libc::{c_char, c_int};
+use std::borrow::Cow;
+use std::ffi::CStr;
+use std::os::raw::c_void;
+use std::ptr;
+
+use weechat_sys::t_weechat_plugin;
+
+use super::Hook;
+use crate::{CallbackResult, Error, LossyCString, ReturnCode, Weechat};
+
+/// Hook for configuration option changes, the hook is removed when the object
+/// is dropped.
+pub struct ConfigHook {
+    _hook: Hook,
+    _hook_data: Box<ConfigHookData>,
+}
+
+type ConfigHookCbT =
+    Box<dyn FnMut(&Weechat, Cow<str>, Option<Cow<str>>) -> ReturnCode>;
+
+struct ConfigHookData {
+    callback: ConfigHookCbT,
+    weechat_ptr: *mut t_weechat_plugin,
+}
+
+impl ConfigHook {
+    /// Hook changes of configuration options.
+    ///
+    /// * `weechat` - The weechat object the hook is created with.
+    /// * `option` - The full name of the option, for example
+    ///     "weechat.look.buffer_time_format" (wildcard `*` is allowed, e.g.
+    ///     "weechat.look.*").
+    /// * `callback` - A closure that will be called when a matching option
+    ///     is changed. The callback receives the full name of the option and
+    ///     the new value, the value is None if the option was removed.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn new<C, R>(
+        weechat: &Weechat,
+        option: &str,
+        mut callback: C,
+    ) -> Result<ConfigHook, Error>
+    where
+        C: FnMut(&Weechat, Cow<str>, Option<Cow<str>>) -> R + 'static,
+        R: CallbackResult,
+    {
+        unsafe extern "C" fn c_hook_cb(
+            pointer: *const c_void,
+            _data: *mut c_void,
+            option: *const c_char,
+            value: *const c_char,
+        ) -> c_int {
+            let hook_data: &mut ConfigHookData =
+                { &mut *(pointer as *mut ConfigHookData) };
+
+            let option = CStr::from_ptr(option).to_string_lossy();
+            let value = if value.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(value).to_string_lossy())
+            };
+
+            (hook_data.callback)(
+                &Weechat::from_ptr(hook_data.weechat_ptr),
+                option,
+                value,
+            ) as i32
+        }
+
+        let data = Box::new(ConfigHookData {
+            callback: Box::new(move |weechat, option, value| {
+                callback(weechat, option, value).into_return_code(weechat)
+            }),
+            weechat_ptr: weechat.ptr,
+        });
+
+        let data_ref = Box::leak(data);
+        let hook_config = weechat.get().hook_config.unwrap();
+
+        let option = LossyCString::new(option);
+
+        let hook_ptr = unsafe {
+            hook_config(
+                weechat.ptr,
+                option.as_ptr(),
+                Some(c_hook_cb),
+                data_ref as *const _ as *const c_void,
+                ptr::null_mut(),
+            )
+        };
+        let hook_data = unsafe { Box::from_raw(data_ref) };
+
+        if hook_ptr.is_null() {
+            return Err(Error::HookCreation);
+        }
+
+        let hook = Hook {
+            ptr: hook_ptr,
+            weechat_ptr: weechat.ptr,
+        };
+
+        Ok(ConfigHook {
+            _hook: hook,
+            _hook_data: hook_data,
+        })
+    }
+}
+
+impl Weechat {
+    /// Hook changes of configuration options.
+    ///
+    /// See `ConfigHook::new()` to create the hook with a closure.
+    /// * `option` - The full name of the option, for example
+    ///     "weechat.look.buffer_time_format" (wildcard `*` is allowed, e.g.
+    ///     "weechat.look.*").
+    /// * `callback` - A function that will be called when a matching option
+    ///     is changed. The callback receives the full name of the option and
+    ///     the new value, the value is None if the option was removed.
+    /// * `callback_data` - Data that will be passed to the callback every time
+    ///     the callback runs. This data will be freed when the hook is
+    ///     unhooked.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn hook_config<T: 'static>(
+        &self,
+        option: &str,
+        callback: fn(
+            data: &T,
+            weechat: &Weechat,
+            option: Cow<str>,
+            value: Option<Cow<str>>,
+        ) -> ReturnCode,
+        callback_data: T,
+    ) -> Result<ConfigHook, Error> {
+        ConfigHook::new(self, option, move |weechat, option, value| {
+            callback(&callback_data, weechat, option, value)
+        })
+    }
+}
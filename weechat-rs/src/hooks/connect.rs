@@ -0,0 +1,324 @@
+//! Hooks for non-blocking TCP connections.
+
+use libc::{c_char, c_int};
+use std::borrow::Cow;
+use std::ffi::CStr;
+use std::net::TcpStream;
+use std::os::raw::c_void;
+use std::os::unix::io::FromRawFd;
+use std::ptr;
+
+use weechat_sys::{
+    t_weechat_plugin, WEECHAT_HOOK_CONNECT_ADDRESS_NOT_FOUND,
+    WEECHAT_HOOK_CONNECT_CONNECTION_REFUSED,
+    WEECHAT_HOOK_CONNECT_GNUTLS_HANDSHAKE_ERROR,
+    WEECHAT_HOOK_CONNECT_GNUTLS_INIT_ERROR,
+    WEECHAT_HOOK_CONNECT_IP_ADDRESS_NOT_FOUND,
+    WEECHAT_HOOK_CONNECT_LOCAL_HOSTNAME_ERROR,
+    WEECHAT_HOOK_CONNECT_MEMORY_ERROR, WEECHAT_HOOK_CONNECT_OK,
+    WEECHAT_HOOK_CONNECT_PROXY_ERROR, WEECHAT_HOOK_CONNECT_SOCKET_ERROR,
+    WEECHAT_HOOK_CONNECT_TIMEOUT,
+};
+
+use super::Hook;
+use crate::{CallbackResult, Error, LossyCString, ReturnCode, Weechat};
+
+/// Hook for a connection, the connection attempt is aborted if the object is
+/// dropped before the connection callback runs.
+pub struct ConnectHook {
+    hook: Option<Hook>,
+    hook_data: Box<ConnectHookData>,
+}
+
+type ConnectHookCbT = Box<dyn FnMut(&Weechat, ConnectResult) -> ReturnCode>;
+
+struct ConnectHookData {
+    callback: ConnectHookCbT,
+    weechat_ptr: *mut t_weechat_plugin,
+    finished: bool,
+}
+
+/// Status of a connection attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectStatus {
+    /// The connection was successfully established.
+    Ok,
+    /// The address wasn't found.
+    AddressNotFound,
+    /// The IP address wasn't found.
+    IpAddressNotFound,
+    /// The connection was refused.
+    ConnectionRefused,
+    /// An error occurred with the proxy.
+    ProxyError,
+    /// An error occurred with the local hostname.
+    LocalHostnameError,
+    /// The TLS initialization failed, contains the GnuTLS return code.
+    GnutlsInitError(i32),
+    /// The TLS handshake failed, contains the GnuTLS return code.
+    GnutlsHandshakeError(i32),
+    /// Insufficient memory.
+    MemoryError,
+    /// The connection attempt timed out.
+    Timeout,
+    /// The socket couldn't be created.
+    SocketError,
+    /// A status that isn't known to this crate, for example one that was
+    /// added in a newer Weechat version. Contains the raw status code.
+    Unknown(i32),
+}
+
+impl ConnectStatus {
+    pub(crate) fn from_int(status: c_int, gnutls_rc: c_int) -> ConnectStatus {
+        match status {
+            WEECHAT_HOOK_CONNECT_OK => ConnectStatus::Ok,
+            WEECHAT_HOOK_CONNECT_ADDRESS_NOT_FOUND => {
+                ConnectStatus::AddressNotFound
+            }
+            WEECHAT_HOOK_CONNECT_IP_ADDRESS_NOT_FOUND => {
+                ConnectStatus::IpAddressNotFound
+            }
+            WEECHAT_HOOK_CONNECT_CONNECTION_REFUSED => {
+                ConnectStatus::ConnectionRefused
+            }
+            WEECHAT_HOOK_CONNECT_PROXY_ERROR => ConnectStatus::ProxyError,
+            WEECHAT_HOOK_CONNECT_LOCAL_HOSTNAME_ERROR => {
+                ConnectStatus::LocalHostnameError
+            }
+            WEECHAT_HOOK_CONNECT_GNUTLS_INIT_ERROR => {
+                ConnectStatus::GnutlsInitError(gnutls_rc)
+            }
+            WEECHAT_HOOK_CONNECT_GNUTLS_HANDSHAKE_ERROR => {
+                ConnectStatus::GnutlsHandshakeError(gnutls_rc)
+            }
+            WEECHAT_HOOK_CONNECT_MEMORY_ERROR => ConnectStatus::MemoryError,
+            WEECHAT_HOOK_CONNECT_TIMEOUT => ConnectStatus::Timeout,
+            WEECHAT_HOOK_CONNECT_SOCKET_ERROR => ConnectStatus::SocketError,
+            status => ConnectStatus::Unknown(status),
+        }
+    }
+}
+
+/// The result of a connection attempt that is passed to the connect
+/// callback.
+pub struct ConnectResult<'a> {
+    /// The status of the connection attempt.
+    pub status: ConnectStatus,
+    /// The connected socket, only set if the status is `ConnectStatus::Ok`.
+    /// The socket can be watched for incoming data with `Weechat::hook_fd()`.
+    pub stream: Option<TcpStream>,
+    /// The IP address that was found for the address.
+    pub ip_address: Option<Cow<'a, str>>,
+    /// An error message if the connection attempt failed.
+    pub error: Option<Cow<'a, str>>,
+}
+
+/// GnuTLS parameters for a connection.
+///
+/// The pointers are passed as is to weechat, see the weechat API
+/// documentation of `hook_connect()` for their meaning.
+pub struct GnutlsOptions<'a> {
+    /// Pointer to a GnuTLS session (`gnutls_session_t`).
+    pub session: *mut c_void,
+    /// Pointer to a GnuTLS callback that is used to verify or set
+    /// certificates.
+    pub callback: *mut c_void,
+    /// Size of the key used during the Diffie-Hellman key exchange.
+    pub dhkey_size: i32,
+    /// GnuTLS priorities.
+    pub priorities: &'a str,
+}
+
+#[derive(Default)]
+/// Settings for a connection that should be created.
+/// The fields of this struct accept the same values that are described in the
+/// weechat API documentation of `hook_connect()`.
+pub struct ConnectSettings<'a> {
+    /// Name of the weechat proxy to use for the connection.
+    pub proxy: Option<&'a str>,
+    /// Name or IP address to connect to.
+    pub address: &'a str,
+    /// Port number to connect to.
+    pub port: u16,
+    /// Should IPv6 be used with a fallback to IPv4.
+    pub ipv6: bool,
+    /// Retry count, used to fallback to IPv4 hosts if IPv6 hosts connect but
+    /// then fail to accept the client.
+    pub retry: i32,
+    /// Local hostname to use for the connection.
+    pub local_hostname: Option<&'a str>,
+    /// GnuTLS parameters, if set the connection is established using TLS.
+    pub gnutls: Option<GnutlsOptions<'a>>,
+}
+
+impl ConnectHook {
+    /// Has the connection attempt finished.
+    pub fn finished(&self) -> bool {
+        self.hook_data.finished
+    }
+}
+
+impl Drop for ConnectHook {
+    fn drop(&mut self) {
+        // Weechat removes the hook by itself after the callback was called,
+        // unhooking it again would be a double free.
+        if self.hook_data.finished {
+            if let Some(hook) = self.hook.take() {
+                std::mem::forget(hook);
+            }
+        }
+    }
+}
+
+impl ConnectHook {
+    /// Connect to a remote host in the background.
+    ///
+    /// * `weechat` - The weechat object the hook is created with.
+    /// * `settings` - The settings for the connection, see `ConnectSettings`.
+    /// * `callback` - A closure that will be called once the connection is
+    ///     established or the connection attempt failed.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn new<C, R>(
+        weechat: &Weechat,
+        settings: ConnectSettings,
+        mut callback: C,
+    ) -> Result<ConnectHook, Error>
+    where
+        C: FnMut(&Weechat, ConnectResult) -> R + 'static,
+        R: CallbackResult,
+    {
+        unsafe extern "C" fn c_hook_cb(
+            pointer: *const c_void,
+            _data: *mut c_void,
+            status: c_int,
+            gnutls_rc: c_int,
+            sock: c_int,
+            error: *const c_char,
+            ip_address: *const c_char,
+        ) -> c_int {
+            let hook_data: &mut ConnectHookData =
+                { &mut *(pointer as *mut ConnectHookData) };
+
+            hook_data.finished = true;
+
+            let status = ConnectStatus::from_int(status, gnutls_rc);
+
+            let stream = if status == ConnectStatus::Ok && sock >= 0 {
+                Some(TcpStream::from_raw_fd(sock))
+            } else {
+                None
+            };
+
+            let to_cow = |s: *const c_char| {
+                if s.is_null() {
+                    None
+                } else {
+                    Some(CStr::from_ptr(s).to_string_lossy())
+                }
+            };
+
+            let result = ConnectResult {
+                status,
+                stream,
+                ip_address: to_cow(ip_address),
+                error: to_cow(error),
+            };
+
+            (hook_data.callback)(
+                &Weechat::from_ptr(hook_data.weechat_ptr),
+                result,
+            ) as i32
+        }
+
+        let data = Box::new(ConnectHookData {
+            callback: Box::new(move |weechat, result| {
+                callback(weechat, result).into_return_code(weechat)
+            }),
+            weechat_ptr: weechat.ptr,
+            finished: false,
+        });
+
+        let data_ref = Box::leak(data);
+        let hook_connect = weechat.get().hook_connect.unwrap();
+
+        let proxy = settings.proxy.map(LossyCString::new);
+        let address = LossyCString::new(settings.address);
+        let local_hostname = settings.local_hostname.map(LossyCString::new);
+
+        let (gnutls_session, gnutls_cb, gnutls_dhkey_size, gnutls_priorities) =
+            match settings.gnutls {
+                Some(gnutls) => (
+                    gnutls.session,
+                    gnutls.callback,
+                    gnutls.dhkey_size,
+                    Some(LossyCString::new(gnutls.priorities)),
+                ),
+                None => (ptr::null_mut(), ptr::null_mut(), 0, None),
+            };
+
+        let hook_ptr = unsafe {
+            hook_connect(
+                weechat.ptr,
+                proxy.as_ref().map_or(ptr::null(), |p| p.as_ptr()),
+                address.as_ptr(),
+                settings.port as i32,
+                settings.ipv6 as i32,
+                settings.retry,
+                gnutls_session,
+                gnutls_cb,
+                gnutls_dhkey_size,
+                gnutls_priorities
+                    .as_ref()
+                    .map_or(ptr::null(), |p| p.as_ptr()),
+                local_hostname.as_ref().map_or(ptr::null(), |h| h.as_ptr()),
+                Some(c_hook_cb),
+                data_ref as *const _ as *const c_void,
+                ptr::null_mut(),
+            )
+        };
+
+        let hook_data = unsafe { Box::from_raw(data_ref) };
+
+        if hook_ptr.is_null() {
+            return Err(Error::HookCreation);
+        }
+
+        let hook = Hook {
+            ptr: hook_ptr,
+            weechat_ptr: weechat.ptr,
+        };
+
+        Ok(ConnectHook {
+            hook: Some(hook),
+            hook_data,
+        })
+    }
+}
+
+impl Weechat {
+    /// Connect to a remote host in the background.
+    ///
+    /// See `ConnectHook::new()` to create the hook with a closure.
+    /// * `settings` - The settings for the connection, see `ConnectSettings`.
+    /// * `callback` - A function that will be called once the connection is
+    ///     established or the connection attempt failed.
+    /// * `callback_data` - Data that will be passed to the callback. This
+    ///     data will be freed when the hook is dropped.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn hook_connect<T: 'static>(
+        &self,
+        settings: ConnectSettings,
+        callback: fn(
+            data: &T,
+            weechat: &Weechat,
+            result: ConnectResult,
+        ) -> ReturnCode,
+        callback_data: T,
+    ) -> Result<ConnectHook, Error> {
+        ConnectHook::new(self, settings, move |weechat, result| {
+            callback(&callback_data, weechat, result)
+        })
+    }
+}
@@ -0,0 +1,219 @@
+//! Futures that wrap hooks, the futures are meant to be run by the executor
+//! of `Weechat::spawn()`.
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::net::TcpStream;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+use super::{
+    ConnectHook, ConnectSettings, ConnectStatus, ProcessHook, ProcessOptions,
+    ProcessReturnCode, TimerHook,
+};
+use crate::{Error, ReturnCode, Weechat};
+
+struct HookState<T> {
+    result: Option<T>,
+    waker: Option<Waker>,
+}
+
+impl<T> HookState<T> {
+    fn new() -> Rc<RefCell<HookState<T>>> {
+        Rc::new(RefCell::new(HookState {
+            result: None,
+            waker: None,
+        }))
+    }
+
+    fn complete(&mut self, result: T) {
+        self.result = Some(result);
+
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+
+    fn poll(&mut self, cx: &mut Context) -> Poll<T> {
+        match self.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                self.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// A future that completes after a duration has passed, see
+/// `Weechat::sleep()`.
+pub struct Sleep {
+    state: Rc<RefCell<HookState<()>>>,
+    hook: Option<TimerHook>,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        let ret = self.state.borrow_mut().poll(cx);
+
+        if ret.is_ready() {
+            // The timer repeats, remove it once it fired.
+            self.hook = None;
+        }
+
+        ret
+    }
+}
+
+/// The collected output of a process that has finished running.
+pub struct ProcessResult {
+    /// The return code of the process.
+    pub return_code: ProcessReturnCode,
+    /// The standard output of the process.
+    pub stdout: String,
+    /// The standard error of the process.
+    pub stderr: String,
+}
+
+/// A future that completes once a process finished running, see
+/// `Weechat::run_process()`.
+pub struct ProcessFuture {
+    state: Rc<RefCell<HookState<ProcessResult>>>,
+    _hook: ProcessHook,
+}
+
+impl Future for ProcessFuture {
+    type Output = ProcessResult;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<ProcessResult> {
+        self.state.borrow_mut().poll(cx)
+    }
+}
+
+/// A future that completes once a connection attempt finished, see
+/// `Weechat::connect()`.
+pub struct ConnectFuture {
+    state: Rc<RefCell<HookState<Result<TcpStream, ConnectStatus>>>>,
+    _hook: ConnectHook,
+}
+
+impl Future for ConnectFuture {
+    type Output = Result<TcpStream, ConnectStatus>;
+
+    fn poll(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Result<TcpStream, ConnectStatus>> {
+        self.state.borrow_mut().poll(cx)
+    }
+}
+
+impl Weechat {
+    /// Create a future that completes after the given duration.
+    /// * `duration` - The duration after which the future completes, the
+    ///     duration has a resolution of milliseconds.
+    ///
+    /// Returns an error if Weechat failed to create the timer.
+    pub fn sleep(&self, duration: Duration) -> Result<Sleep, Error> {
+        let state = HookState::new();
+        let timer_state = state.clone();
+
+        // Weechat doesn't accept a zero interval.
+        let interval = duration.max(Duration::from_millis(1));
+
+        let hook = TimerHook::new(self, interval, 0, 0, move |_, _| {
+            timer_state.borrow_mut().complete(())
+        })?;
+
+        Ok(Sleep {
+            state,
+            hook: Some(hook),
+        })
+    }
+
+    /// Run a command in a background process and collect its output.
+    ///
+    /// Returns a future that completes once the process finished running.
+    /// * `command` - The command to run.
+    /// * `options` - Options for the process, see `ProcessOptions`.
+    /// * `timeout` - Timeout for the command, the process is killed after
+    ///     the timeout. A timeout of 0 means no timeout, timeouts longer
+    ///     than `i32::MAX` milliseconds (about 24 days) are clamped.
+    ///
+    /// Returns an error if Weechat failed to start the process.
+    pub fn run_process(
+        &self,
+        command: &str,
+        options: ProcessOptions,
+        timeout: Duration,
+    ) -> Result<ProcessFuture, Error> {
+        let state = HookState::new();
+        let hook_state = state.clone();
+
+        let mut stdout = String::new();
+        let mut stderr = String::new();
+
+        let hook = ProcessHook::new(
+            self,
+            command,
+            options,
+            timeout,
+            move |_, output| {
+                if let Some(out) = output.stdout {
+                    stdout.push_str(&out);
+                }
+
+                if let Some(err) = output.stderr {
+                    stderr.push_str(&err);
+                }
+
+                match output.return_code {
+                    ProcessReturnCode::Running | ProcessReturnCode::Child => (),
+                    return_code => {
+                        hook_state.borrow_mut().complete(ProcessResult {
+                            return_code,
+                            stdout: std::mem::take(&mut stdout),
+                            stderr: std::mem::take(&mut stderr),
+                        })
+                    }
+                }
+
+                ReturnCode::Ok
+            },
+        )?;
+
+        Ok(ProcessFuture { state, _hook: hook })
+    }
+
+    /// Connect to a remote host in the background.
+    ///
+    /// Returns a future that completes with the connected socket, or with
+    /// the status of the connection attempt if the connection failed.
+    /// * `settings` - The settings for the connection, see `ConnectSettings`.
+    ///
+    /// Returns an error if Weechat failed to start the connection attempt.
+    pub fn connect(
+        &self,
+        settings: ConnectSettings,
+    ) -> Result<ConnectFuture, Error> {
+        let state = HookState::new();
+        let hook_state = state.clone();
+
+        let hook = ConnectHook::new(self, settings, move |_, result| {
+            let result = match result.stream {
+                Some(stream) => Ok(stream),
+                None => Err(result.status),
+            };
+
+            hook_state.borrow_mut().complete(result);
+
+            ReturnCode::Ok
+        })?;
+
+        Ok(ConnectFuture { state, _hook: hook })
+    }
+}
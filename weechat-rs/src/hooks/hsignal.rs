@@ -0,0 +1,164 @@
+//! Hooks for signals carrying a hashtable.
+
+use libc::{c_char, c_int};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::os::raw::c_void;
+use std::ptr;
+
+use weechat_sys::{t_hashtable, t_weechat_plugin};
+
+use super::Hook;
+use crate::{
+    CallbackResult, Error, Hashtable, LossyCString, ReturnCode, Weechat,
+};
+
+/// Hook for a hsignal, the hook is removed when the object is dropped.
+pub struct HsignalHook {
+    _hook: Hook,
+    _hook_data: Box<HsignalHookData>,
+}
+
+type HsignalHookCbT =
+    Box<dyn FnMut(&Weechat, Cow<str>, Option<&Hashtable>) -> ReturnCode>;
+
+struct HsignalHookData {
+    callback: HsignalHookCbT,
+    weechat_ptr: *mut t_weechat_plugin,
+}
+
+impl HsignalHook {
+    /// Hook a hsignal, a signal carrying a hashtable.
+    ///
+    /// * `weechat` - The weechat object the hook is created with.
+    /// * `signal` - The hsignal to hook (wildcard `*` is allowed).
+    /// * `callback` - A closure that will be called when the hsignal is
+    ///     received. The callback receives the name of the signal and the
+    ///     hashtable that was sent with it, if there is one. The hashtable is
+    ///     only valid while the callback runs.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn new<C, R>(
+        weechat: &Weechat,
+        signal: &str,
+        mut callback: C,
+    ) -> Result<HsignalHook, Error>
+    where
+        C: FnMut(&Weechat, Cow<str>, Option<&Hashtable>) -> R + 'static,
+        R: CallbackResult,
+    {
+        unsafe extern "C" fn c_hook_cb(
+            pointer: *const c_void,
+            _data: *mut c_void,
+            signal: *const c_char,
+            hashtable: *mut t_hashtable,
+        ) -> c_int {
+            let hook_data: &mut HsignalHookData =
+                { &mut *(pointer as *mut HsignalHookData) };
+
+            let weechat = Weechat::from_ptr(hook_data.weechat_ptr);
+            let signal = CStr::from_ptr(signal).to_string_lossy();
+            let hashtable = if hashtable.is_null() {
+                None
+            } else {
+                Some(Hashtable::from_ptr(hook_data.weechat_ptr, hashtable))
+            };
+
+            (hook_data.callback)(&weechat, signal, hashtable.as_ref()) as i32
+        }
+
+        let data = Box::new(HsignalHookData {
+            callback: Box::new(move |weechat, signal, hashtable| {
+                callback(weechat, signal, hashtable).into_return_code(weechat)
+            }),
+            weechat_ptr: weechat.ptr,
+        });
+
+        let data_ref = Box::leak(data);
+        let hook_hsignal = weechat.get().hook_hsignal.unwrap();
+
+        let signal = LossyCString::new(signal);
+
+        let hook_ptr = unsafe {
+            hook_hsignal(
+                weechat.ptr,
+                signal.as_ptr(),
+                Some(c_hook_cb),
+                data_ref as *const _ as *const c_void,
+                ptr::null_mut(),
+            )
+        };
+        let hook_data = unsafe { Box::from_raw(data_ref) };
+
+        if hook_ptr.is_null() {
+            return Err(Error::HookCreation);
+        }
+
+        let hook = Hook {
+            ptr: hook_ptr,
+            weechat_ptr: weechat.ptr,
+        };
+
+        Ok(HsignalHook {
+            _hook: hook,
+            _hook_data: hook_data,
+        })
+    }
+}
+
+impl Weechat {
+    /// Hook a hsignal, a signal carrying a hashtable.
+    ///
+    /// See `HsignalHook::new()` to create the hook with a closure.
+    /// * `signal` - The hsignal to hook (wildcard `*` is allowed).
+    /// * `callback` - A function that will be called when the hsignal is
+    ///     received. The callback receives the name of the signal and the
+    ///     hashtable that was sent with it, if there is one. The hashtable is
+    ///     only valid while the callback runs.
+    /// * `callback_data` - Data that will be passed to the callback every time
+    ///     the callback runs. This data will be freed when the hook is
+    ///     unhooked.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn hook_hsignal<T: 'static>(
+        &self,
+        signal: &str,
+        callback: fn(
+            data: &T,
+            weechat: &Weechat,
+            signal: Cow<str>,
+            hashtable: Option<&Hashtable>,
+        ) -> ReturnCode,
+        callback_data: T,
+    ) -> Result<HsignalHook, Error> {
+        HsignalHook::new(self, signal, move |weechat, signal, hashtable| {
+            callback(&callback_data, weechat, signal, hashtable)
+        })
+    }
+
+    /// Send a hsignal, a signal carrying a hashtable.
+    ///
+    /// * `signal` - The name of the hsignal to send.
+    /// * `hashtable` - The data that will be sent with the hsignal.
+    ///
+    /// Returns the return code of the last callback that handled the hsignal,
+    /// `ReturnCode::Error` if the hashtable couldn't be created.
+    pub fn hsignal_send(
+        &self,
+        signal: &str,
+        hashtable: &HashMap<String, String>,
+    ) -> ReturnCode {
+        let hook_hsignal_send = self.get().hook_hsignal_send.unwrap();
+
+        let signal = LossyCString::new(signal);
+        let hashtable = match self.hashtable_from_map(hashtable) {
+            Ok(hashtable) => hashtable,
+            Err(_) => return ReturnCode::Error,
+        };
+
+        let ret = unsafe { hook_hsignal_send(signal.as_ptr(), hashtable.ptr) };
+
+        ReturnCode::from_int(ret)
+    }
+}
@@ -0,0 +1,331 @@
+//! Hooks for providing infos to weechat and other plugins.
+
+use libc::c_char;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::os::raw::c_void;
+use std::ptr;
+
+use weechat_sys::{t_hashtable, t_weechat_plugin};
+
+use super::Hook;
+use crate::{Error, Hashtable, LossyCString, Weechat};
+
+/// Hook for an info, the info is removed when the object is dropped.
+pub struct InfoHook {
+    _hook: Hook,
+    _hook_data: Box<InfoHookData>,
+}
+
+type InfoHookCbT =
+    Box<dyn FnMut(&Weechat, Cow<str>, Cow<str>) -> Option<String>>;
+
+struct InfoHookData {
+    callback: InfoHookCbT,
+    weechat_ptr: *mut t_weechat_plugin,
+}
+
+/// Hook for an info returning a hashtable, the info is removed when the
+/// object is dropped.
+pub struct InfoHashtableHook {
+    _hook: Hook,
+    _hook_data: Box<InfoHashtableHookData>,
+}
+
+type InfoHashtableHookCbT = Box<
+    dyn FnMut(
+        &Weechat,
+        Cow<str>,
+        &HashMap<String, String>,
+    ) -> Option<HashMap<String, String>>,
+>;
+
+struct InfoHashtableHookData {
+    callback: InfoHashtableHookCbT,
+    weechat_ptr: *mut t_weechat_plugin,
+}
+
+impl InfoHook {
+    /// Hook an info, the info can be requested by other plugins with
+    /// `info_get()` or in evaluated expressions with `${info:name,arguments}`.
+    ///
+    /// * `weechat` - The weechat object the hook is created with.
+    /// * `name` - The name of the info.
+    /// * `description` - A description of the info.
+    /// * `args_description` - A description of the arguments of the info.
+    /// * `callback` - A closure that will be called when the info is
+    ///     requested. The callback receives the name of the info and the
+    ///     arguments, the returned string is the value of the info.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn new<C>(
+        weechat: &Weechat,
+        name: &str,
+        description: &str,
+        args_description: &str,
+        callback: C,
+    ) -> Result<InfoHook, Error>
+    where
+        C: FnMut(&Weechat, Cow<str>, Cow<str>) -> Option<String> + 'static,
+    {
+        unsafe extern "C" fn c_hook_cb(
+            pointer: *const c_void,
+            _data: *mut c_void,
+            info_name: *const c_char,
+            arguments: *const c_char,
+        ) -> *mut c_char {
+            let hook_data: &mut InfoHookData =
+                { &mut *(pointer as *mut InfoHookData) };
+
+            let to_cow = |s: *const c_char| {
+                if s.is_null() {
+                    Cow::from("")
+                } else {
+                    CStr::from_ptr(s).to_string_lossy()
+                }
+            };
+
+            let ret = (hook_data.callback)(
+                &Weechat::from_ptr(hook_data.weechat_ptr),
+                to_cow(info_name),
+                to_cow(arguments),
+            );
+
+            match ret {
+                // weechat wants malloc'ed string
+                Some(info) => libc::strdup(LossyCString::new(info).as_ptr()),
+                None => ptr::null_mut(),
+            }
+        }
+
+        let data = Box::new(InfoHookData {
+            callback: Box::new(callback),
+            weechat_ptr: weechat.ptr,
+        });
+
+        let data_ref = Box::leak(data);
+        let hook_info = weechat.get().hook_info.unwrap();
+
+        let name = LossyCString::new(name);
+        let description = LossyCString::new(description);
+        let args_description = LossyCString::new(args_description);
+
+        let hook_ptr = unsafe {
+            hook_info(
+                weechat.ptr,
+                name.as_ptr(),
+                description.as_ptr(),
+                args_description.as_ptr(),
+                Some(c_hook_cb),
+                data_ref as *const _ as *const c_void,
+                ptr::null_mut(),
+            )
+        };
+        let hook_data = unsafe { Box::from_raw(data_ref) };
+
+        if hook_ptr.is_null() {
+            return Err(Error::HookCreation);
+        }
+
+        let hook = Hook {
+            ptr: hook_ptr,
+            weechat_ptr: weechat.ptr,
+        };
+
+        Ok(InfoHook {
+            _hook: hook,
+            _hook_data: hook_data,
+        })
+    }
+}
+
+impl InfoHashtableHook {
+    /// Hook an info returning a hashtable, the info can be requested by other
+    /// plugins with `info_get_hashtable()`.
+    ///
+    /// * `weechat` - The weechat object the hook is created with.
+    /// * `name` - The name of the info.
+    /// * `description` - A description of the info.
+    /// * `args_description` - A description of the keys the input hashtable
+    ///     can contain.
+    /// * `output_description` - A description of the keys of the returned
+    ///     hashtable.
+    /// * `callback` - A closure that will be called when the info is
+    ///     requested. The callback receives the name of the info and the
+    ///     contents of the input hashtable, the returned map is converted to
+    ///     the hashtable that is returned to the caller.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn new<C>(
+        weechat: &Weechat,
+        name: &str,
+        description: &str,
+        args_description: &str,
+        output_description: &str,
+        callback: C,
+    ) -> Result<InfoHashtableHook, Error>
+    where
+        C: FnMut(
+                &Weechat,
+                Cow<str>,
+                &HashMap<String, String>,
+            ) -> Option<HashMap<String, String>>
+            + 'static,
+    {
+        unsafe extern "C" fn c_hook_cb(
+            pointer: *const c_void,
+            _data: *mut c_void,
+            info_name: *const c_char,
+            hashtable: *mut t_hashtable,
+        ) -> *mut t_hashtable {
+            let hook_data: &mut InfoHashtableHookData =
+                { &mut *(pointer as *mut InfoHashtableHookData) };
+
+            let weechat = Weechat::from_ptr(hook_data.weechat_ptr);
+            let info_name = CStr::from_ptr(info_name).to_string_lossy();
+            let hashtable = if hashtable.is_null() {
+                HashMap::new()
+            } else {
+                Hashtable::from_ptr(hook_data.weechat_ptr, hashtable)
+                    .to_string_map()
+            };
+
+            match (hook_data.callback)(&weechat, info_name, &hashtable) {
+                // The caller of info_get_hashtable() frees the hashtable.
+                Some(info) => weechat
+                    .hashtable_from_map(&info)
+                    .map(Hashtable::into_raw)
+                    .unwrap_or(ptr::null_mut()),
+                None => ptr::null_mut(),
+            }
+        }
+
+        let data = Box::new(InfoHashtableHookData {
+            callback: Box::new(callback),
+            weechat_ptr: weechat.ptr,
+        });
+
+        let data_ref = Box::leak(data);
+        let hook_info_hashtable = weechat.get().hook_info_hashtable.unwrap();
+
+        let name = LossyCString::new(name);
+        let description = LossyCString::new(description);
+        let args_description = LossyCString::new(args_description);
+        let output_description = LossyCString::new(output_description);
+
+        let hook_ptr = unsafe {
+            hook_info_hashtable(
+                weechat.ptr,
+                name.as_ptr(),
+                description.as_ptr(),
+                args_description.as_ptr(),
+                output_description.as_ptr(),
+                Some(c_hook_cb),
+                data_ref as *const _ as *const c_void,
+                ptr::null_mut(),
+            )
+        };
+        let hook_data = unsafe { Box::from_raw(data_ref) };
+
+        if hook_ptr.is_null() {
+            return Err(Error::HookCreation);
+        }
+
+        let hook = Hook {
+            ptr: hook_ptr,
+            weechat_ptr: weechat.ptr,
+        };
+
+        Ok(InfoHashtableHook {
+            _hook: hook,
+            _hook_data: hook_data,
+        })
+    }
+}
+
+impl Weechat {
+    /// Hook an info, the info can be requested by other plugins with
+    /// `info_get()` or in evaluated expressions with `${info:name,arguments}`.
+    ///
+    /// See `InfoHook::new()` to create the hook with a closure.
+    /// * `name` - The name of the info.
+    /// * `description` - A description of the info.
+    /// * `args_description` - A description of the arguments of the info.
+    /// * `callback` - A function that will be called when the info is
+    ///     requested. The callback receives the name of the info and the
+    ///     arguments, the returned string is the value of the info.
+    /// * `callback_data` - Data that will be passed to the callback every time
+    ///     the callback runs. This data will be freed when the hook is
+    ///     unhooked.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn hook_info<T: 'static>(
+        &self,
+        name: &str,
+        description: &str,
+        args_description: &str,
+        callback: fn(
+            data: &T,
+            weechat: &Weechat,
+            info_name: Cow<str>,
+            arguments: Cow<str>,
+        ) -> Option<String>,
+        callback_data: T,
+    ) -> Result<InfoHook, Error> {
+        InfoHook::new(
+            self,
+            name,
+            description,
+            args_description,
+            move |weechat, info_name, arguments| {
+                callback(&callback_data, weechat, info_name, arguments)
+            },
+        )
+    }
+
+    /// Hook an info returning a hashtable, the info can be requested by other
+    /// plugins with `info_get_hashtable()`.
+    ///
+    /// See `InfoHashtableHook::new()` to create the hook with a closure.
+    /// * `name` - The name of the info.
+    /// * `description` - A description of the info.
+    /// * `args_description` - A description of the keys the input hashtable
+    ///     can contain.
+    /// * `output_description` - A description of the keys of the returned
+    ///     hashtable.
+    /// * `callback` - A function that will be called when the info is
+    ///     requested. The callback receives the name of the info and the
+    ///     contents of the input hashtable, the returned map is converted to
+    ///     the hashtable that is returned to the caller.
+    /// * `callback_data` - Data that will be passed to the callback every time
+    ///     the callback runs. This data will be freed when the hook is
+    ///     unhooked.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn hook_info_hashtable<T: 'static>(
+        &self,
+        name: &str,
+        description: &str,
+        args_description: &str,
+        output_description: &str,
+        callback: fn(
+            data: &T,
+            weechat: &Weechat,
+            info_name: Cow<str>,
+            hashtable: &HashMap<String, String>,
+        ) -> Option<HashMap<String, String>>,
+        callback_data: T,
+    ) -> Result<InfoHashtableHook, Error> {
+        InfoHashtableHook::new(
+            self,
+            name,
+            description,
+            args_description,
+            output_description,
+            move |weechat, info_name, hashtable| {
+                callback(&callback_data, weechat, info_name, hashtable)
+            },
+        )
+    }
+}
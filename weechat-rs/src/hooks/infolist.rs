@@ -0,0 +1,177 @@
+//! Hooks for providing infolists to weechat and other plugins.
+
+use libc::c_char;
+use std::borrow::Cow;
+use std::ffi::CStr;
+use std::os::raw::c_void;
+use std::ptr;
+
+use weechat_sys::{t_infolist, t_weechat_plugin};
+
+use super::Hook;
+use crate::infolist::InfolistBuilder;
+use crate::{Error, LossyCString, Weechat};
+
+/// Hook for an infolist, the infolist is removed when the object is dropped.
+pub struct InfolistHook {
+    _hook: Hook,
+    _hook_data: Box<InfolistHookData>,
+}
+
+type InfolistHookCbT =
+    Box<dyn FnMut(&Weechat, *mut c_void, Cow<str>) -> Option<InfolistBuilder>>;
+
+struct InfolistHookData {
+    callback: InfolistHookCbT,
+    weechat_ptr: *mut t_weechat_plugin,
+}
+
+impl InfolistHook {
+    /// Hook an infolist, the infolist can be requested by other plugins and
+    /// scripts with `infolist_get()`.
+    ///
+    /// * `weechat` - The weechat object the hook is created with.
+    /// * `name` - The name of the infolist.
+    /// * `description` - A description of the infolist.
+    /// * `pointer_description` - A description of the pointer that can be
+    ///     passed when the infolist is requested.
+    /// * `args_description` - A description of the arguments that can be
+    ///     passed when the infolist is requested.
+    /// * `callback` - A closure that will be called when the infolist is
+    ///     requested. The callback receives the pointer and the arguments of
+    ///     the request and should return a new infolist created with
+    ///     `Weechat::infolist_new()`, or None if the infolist can't be
+    ///     created.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn new<C>(
+        weechat: &Weechat,
+        name: &str,
+        description: &str,
+        pointer_description: &str,
+        args_description: &str,
+        callback: C,
+    ) -> Result<InfolistHook, Error>
+    where
+        C: FnMut(&Weechat, *mut c_void, Cow<str>) -> Option<InfolistBuilder>
+            + 'static,
+    {
+        unsafe extern "C" fn c_hook_cb(
+            pointer: *const c_void,
+            _data: *mut c_void,
+            _infolist_name: *const c_char,
+            obj_pointer: *mut c_void,
+            arguments: *const c_char,
+        ) -> *mut t_infolist {
+            let hook_data: &mut InfolistHookData =
+                { &mut *(pointer as *mut InfolistHookData) };
+
+            let arguments = if arguments.is_null() {
+                Cow::from("")
+            } else {
+                CStr::from_ptr(arguments).to_string_lossy()
+            };
+
+            let ret = (hook_data.callback)(
+                &Weechat::from_ptr(hook_data.weechat_ptr),
+                obj_pointer,
+                arguments,
+            );
+
+            match ret {
+                // The caller of infolist_get() frees the infolist.
+                Some(infolist) => infolist.into_raw(),
+                None => ptr::null_mut(),
+            }
+        }
+
+        let data = Box::new(InfolistHookData {
+            callback: Box::new(callback),
+            weechat_ptr: weechat.ptr,
+        });
+
+        let data_ref = Box::leak(data);
+        let hook_infolist = weechat.get().hook_infolist.unwrap();
+
+        let name = LossyCString::new(name);
+        let description = LossyCString::new(description);
+        let pointer_description = LossyCString::new(pointer_description);
+        let args_description = LossyCString::new(args_description);
+
+        let hook_ptr = unsafe {
+            hook_infolist(
+                weechat.ptr,
+                name.as_ptr(),
+                description.as_ptr(),
+                pointer_description.as_ptr(),
+                args_description.as_ptr(),
+                Some(c_hook_cb),
+                data_ref as *const _ as *const c_void,
+                ptr::null_mut(),
+            )
+        };
+        let hook_data = unsafe { Box::from_raw(data_ref) };
+
+        if hook_ptr.is_null() {
+            return Err(Error::HookCreation);
+        }
+
+        let hook = Hook {
+            ptr: hook_ptr,
+            weechat_ptr: weechat.ptr,
+        };
+
+        Ok(InfolistHook {
+            _hook: hook,
+            _hook_data: hook_data,
+        })
+    }
+}
+
+impl Weechat {
+    /// Hook an infolist, the infolist can be requested by other plugins and
+    /// scripts with `infolist_get()`.
+    ///
+    /// See `InfolistHook::new()` to create the hook with a closure.
+    /// * `name` - The name of the infolist.
+    /// * `description` - A description of the infolist.
+    /// * `pointer_description` - A description of the pointer that can be
+    ///     passed when the infolist is requested.
+    /// * `args_description` - A description of the arguments that can be
+    ///     passed when the infolist is requested.
+    /// * `callback` - A function that will be called when the infolist is
+    ///     requested. The callback receives the pointer and the arguments of
+    ///     the request and should return a new infolist created with
+    ///     `Weechat::infolist_new()`, or None if the infolist can't be
+    ///     created.
+    /// * `callback_data` - Data that will be passed to the callback every time
+    ///     the callback runs. This data will be freed when the hook is
+    ///     unhooked.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn hook_infolist<T: 'static>(
+        &self,
+        name: &str,
+        description: &str,
+        pointer_description: &str,
+        args_description: &str,
+        callback: fn(
+            data: &T,
+            weechat: &Weechat,
+            pointer: *mut c_void,
+            arguments: Cow<str>,
+        ) -> Option<InfolistBuilder>,
+        callback_data: T,
+    ) -> Result<InfolistHook, Error> {
+        InfolistHook::new(
+            self,
+            name,
+            description,
+            pointer_description,
+            args_description,
+            move |weechat, pointer, arguments| {
+                callback(&callback_data, weechat, pointer, arguments)
+            },
+        )
+    }
+}
@@ -0,0 +1,174 @@
+//! A channel to send messages from other threads to the Weechat main thread.
+
+use std::collections::VecDeque;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::sync::{Arc, Mutex};
+
+use super::{FdHook, FdHookMode};
+use crate::{Error, Weechat};
+
+/// A closure that is run on the Weechat main thread, see
+/// `MainThreadHook::for_tasks()`.
+pub type MainThreadTask = Box<dyn FnOnce(&Weechat) + Send>;
+
+struct Channel<T> {
+    // The queue is set to None once the hook is removed.
+    queue: Mutex<Option<VecDeque<T>>>,
+    sender: UnixStream,
+}
+
+/// Hook that receives messages sent from other threads on the Weechat main
+/// thread, the hook is removed when the object is dropped.
+///
+/// Messages are sent using a `MainThreadSender`, the hook is woken up using a
+/// socket that is watched by a file descriptor hook.
+pub struct MainThreadHook<T> {
+    channel: Arc<Channel<T>>,
+    _hook: FdHook<UnixStream>,
+}
+
+/// A sender for messages that should be handled on the Weechat main thread.
+///
+/// The sender can be cloned and sent to other threads, see
+/// `MainThreadHook::sender()`.
+pub struct MainThreadSender<T> {
+    channel: Arc<Channel<T>>,
+}
+
+impl<T> Clone for MainThreadSender<T> {
+    fn clone(&self) -> Self {
+        MainThreadSender {
+            channel: self.channel.clone(),
+        }
+    }
+}
+
+impl<T> MainThreadSender<T> {
+    /// Send a message to the Weechat main thread.
+    /// * `message` - The message that will be passed to the callback of the
+    ///     hook on the main thread.
+    /// Returns the message back as an error if the hook was removed.
+    pub fn send(&self, message: T) -> Result<(), T> {
+        match self.channel.queue.lock().unwrap().as_mut() {
+            Some(queue) => queue.push_back(message),
+            None => return Err(message),
+        }
+
+        // The socket is non-blocking, if the socket buffer is full the hook
+        // is going to be woken up anyways.
+        let _ = (&self.channel.sender).write(&[0]);
+
+        Ok(())
+    }
+}
+
+impl MainThreadSender<MainThreadTask> {
+    /// Run a closure on the Weechat main thread.
+    /// * `task` - The closure that should run, the closure receives the
+    ///     Weechat object so it can safely use the Weechat API.
+    /// Returns the closure back as an error if the hook was removed.
+    pub fn run(
+        &self,
+        task: impl FnOnce(&Weechat) + Send + 'static,
+    ) -> Result<(), MainThreadTask> {
+        self.send(Box::new(task))
+    }
+}
+
+impl<T: 'static> MainThreadHook<T> {
+    /// Create a hook that receives messages from other threads.
+    ///
+    /// * `weechat` - The weechat object the hook is created with.
+    /// * `callback` - A closure that will be called on the main thread for
+    ///     every message that is sent using a `MainThreadSender` of the hook.
+    ///
+    /// Returns an error if the socket or the hook that wakes up the main
+    /// thread couldn't be created.
+    pub fn new(
+        weechat: &Weechat,
+        mut callback: impl FnMut(&Weechat, T) + 'static,
+    ) -> Result<MainThreadHook<T>, Error> {
+        let socket_error = |error: std::io::Error| {
+            Error::Custom(format!(
+                "Can't create the socket for the main thread hook: {}",
+                error
+            ))
+        };
+
+        let (sender, receiver) = UnixStream::pair().map_err(socket_error)?;
+
+        sender.set_nonblocking(true).map_err(socket_error)?;
+        receiver.set_nonblocking(true).map_err(socket_error)?;
+
+        let channel = Arc::new(Channel {
+            queue: Mutex::new(Some(VecDeque::new())),
+            sender,
+        });
+        let hook_channel = channel.clone();
+
+        let hook = FdHook::new(
+            weechat,
+            receiver,
+            FdHookMode::Read,
+            move |weechat, receiver| {
+                let mut buf = [0; 64];
+
+                // Drain the socket, the queue contains the messages.
+                while let Ok(n) = receiver.read(&mut buf) {
+                    if n == 0 {
+                        break;
+                    }
+                }
+
+                // The messages are taken out of the queue first so the lock
+                // isn't held while the callback runs.
+                let messages: Vec<T> =
+                    match hook_channel.queue.lock().unwrap().as_mut() {
+                        Some(queue) => queue.drain(..).collect(),
+                        None => return,
+                    };
+
+                for message in messages {
+                    callback(weechat, message);
+                }
+            },
+        )?;
+
+        Ok(MainThreadHook {
+            channel,
+            _hook: hook,
+        })
+    }
+
+    /// Get a sender for the hook, the sender can be sent to other threads.
+    pub fn sender(&self) -> MainThreadSender<T> {
+        MainThreadSender {
+            channel: self.channel.clone(),
+        }
+    }
+}
+
+impl MainThreadHook<MainThreadTask> {
+    /// Create a hook that runs closures sent from other threads on the main
+    /// thread, see `MainThreadSender::run()`.
+    ///
+    /// * `weechat` - The weechat object the hook is created with.
+    ///
+    /// Returns an error if the hook couldn't be created.
+    pub fn for_tasks(
+        weechat: &Weechat,
+    ) -> Result<MainThreadHook<MainThreadTask>, Error> {
+        MainThreadHook::new(weechat, |weechat, task: MainThreadTask| {
+            task(weechat)
+        })
+    }
+}
+
+impl<T> Drop for MainThreadHook<T> {
+    fn drop(&mut self) {
+        // Close the channel, messages that weren't handled yet are dropped
+        // and further sends fail.
+        self.channel.queue.lock().unwrap().take();
+    }
+}
@@ -0,0 +1,205 @@
+//! Hooks for modifying strings with weechat modifiers.
+
+use libc::c_char;
+use std::borrow::Cow;
+use std::ffi::CStr;
+use std::os::raw::c_void;
+use std::ptr;
+
+use weechat_sys::t_weechat_plugin;
+
+use super::Hook;
+use crate::{Error, LossyCString, Weechat};
+
+/// Hook for a modifier, the hook is removed when the object is dropped.
+pub struct ModifierHook {
+    _hook: Hook,
+    _hook_data: Box<ModifierHookData>,
+}
+
+type ModifierHookCbT =
+    Box<dyn FnMut(&Weechat, Cow<str>, Cow<str>, Cow<str>) -> Option<String>>;
+
+struct ModifierHookData {
+    callback: ModifierHookCbT,
+    weechat_ptr: *mut t_weechat_plugin,
+}
+
+impl ModifierHook {
+    /// Hook a modifier.
+    ///
+    /// * `weechat` - The weechat object the hook is created with.
+    /// * `modifier` - The name of the modifier, see the weechat API
+    ///     documentation of `hook_modifier()` for a list of modifiers.
+    /// * `callback` - A closure that will be called when the modifier is
+    ///     used. The callback receives the modifier name, the modifier data
+    ///     and the string that should be modified. If the callback returns
+    ///     a string it replaces the original string, if `None` is returned
+    ///     the string is left unchanged.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn new<C>(
+        weechat: &Weechat,
+        modifier: &str,
+        callback: C,
+    ) -> Result<ModifierHook, Error>
+    where
+        C: FnMut(&Weechat, Cow<str>, Cow<str>, Cow<str>) -> Option<String>
+            + 'static,
+    {
+        unsafe extern "C" fn c_hook_cb(
+            pointer: *const c_void,
+            _data: *mut c_void,
+            modifier: *const c_char,
+            modifier_data: *const c_char,
+            string: *const c_char,
+        ) -> *mut c_char {
+            let hook_data: &mut ModifierHookData =
+                { &mut *(pointer as *mut ModifierHookData) };
+
+            let to_cow = |s: *const c_char| {
+                if s.is_null() {
+                    Cow::from("")
+                } else {
+                    CStr::from_ptr(s).to_string_lossy()
+                }
+            };
+
+            let ret = (hook_data.callback)(
+                &Weechat::from_ptr(hook_data.weechat_ptr),
+                to_cow(modifier),
+                to_cow(modifier_data),
+                to_cow(string),
+            );
+
+            match ret {
+                // weechat wants malloc'ed string
+                Some(string) => {
+                    libc::strdup(LossyCString::new(string).as_ptr())
+                }
+                None => ptr::null_mut(),
+            }
+        }
+
+        let data = Box::new(ModifierHookData {
+            callback: Box::new(callback),
+            weechat_ptr: weechat.ptr,
+        });
+
+        let data_ref = Box::leak(data);
+        let hook_modifier = weechat.get().hook_modifier.unwrap();
+
+        let modifier = LossyCString::new(modifier);
+
+        let hook_ptr = unsafe {
+            hook_modifier(
+                weechat.ptr,
+                modifier.as_ptr(),
+                Some(c_hook_cb),
+                data_ref as *const _ as *const c_void,
+                ptr::null_mut(),
+            )
+        };
+        let hook_data = unsafe { Box::from_raw(data_ref) };
+
+        if hook_ptr.is_null() {
+            return Err(Error::HookCreation);
+        }
+
+        let hook = Hook {
+            ptr: hook_ptr,
+            weechat_ptr: weechat.ptr,
+        };
+
+        Ok(ModifierHook {
+            _hook: hook,
+            _hook_data: hook_data,
+        })
+    }
+}
+
+impl Weechat {
+    /// Hook a modifier.
+    ///
+    /// See `ModifierHook::new()` to create the hook with a closure.
+    /// * `modifier` - The name of the modifier, see the weechat API
+    ///     documentation of `hook_modifier()` for a list of modifiers.
+    /// * `callback` - A function that will be called when the modifier is
+    ///     used. The callback receives the modifier name, the modifier data
+    ///     and the string that should be modified. If the callback returns
+    ///     a string it replaces the original string, if `None` is returned
+    ///     the string is left unchanged.
+    /// * `callback_data` - Data that will be passed to the callback every time
+    ///     the callback runs. This data will be freed when the hook is
+    ///     unhooked.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn hook_modifier<T: 'static>(
+        &self,
+        modifier: &str,
+        callback: fn(
+            data: &T,
+            weechat: &Weechat,
+            modifier: Cow<str>,
+            modifier_data: Cow<str>,
+            string: Cow<str>,
+        ) -> Option<String>,
+        callback_data: T,
+    ) -> Result<ModifierHook, Error> {
+        ModifierHook::new(
+            self,
+            modifier,
+            move |weechat, modifier, modifier_data, string| {
+                callback(
+                    &callback_data,
+                    weechat,
+                    modifier,
+                    modifier_data,
+                    string,
+                )
+            },
+        )
+    }
+
+    /// Execute a modifier.
+    ///
+    /// * `modifier` - The name of the modifier, for example
+    ///     `color_decode_ansi` or `irc_in_privmsg`.
+    /// * `modifier_data` - Data for the modifier, see the weechat API
+    ///     documentation of `hook_modifier()` for the data each modifier
+    ///     expects.
+    /// * `string` - The string that should be modified.
+    ///
+    /// Returns the modified string, or None if the modifier failed.
+    pub fn modifier_exec(
+        &self,
+        modifier: &str,
+        modifier_data: &str,
+        string: &str,
+    ) -> Option<String> {
+        let hook_modifier_exec = self.get().hook_modifier_exec.unwrap();
+
+        let modifier = LossyCString::new(modifier);
+        let modifier_data = LossyCString::new(modifier_data);
+        let string = LossyCString::new(string);
+
+        unsafe {
+            let result = hook_modifier_exec(
+                self.ptr,
+                modifier.as_ptr(),
+                modifier_data.as_ptr(),
+                string.as_ptr(),
+            );
+
+            if result.is_null() {
+                None
+            } else {
+                let modified =
+                    CStr::from_ptr(result).to_string_lossy().into_owned();
+                // The returned string is malloc'ed by weechat.
+                libc::free(result as *mut c_void);
+                Some(modified)
+            }
+        }
+    }
+}
@@ -0,0 +1,351 @@
+//! Hooks for lines that are printed to buffers.
+
+use libc::{c_char, c_int};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::os::raw::c_void;
+use std::ptr;
+
+use weechat_sys::{t_gui_buffer, t_hashtable, t_weechat_plugin};
+
+use super::Hook;
+use crate::{
+    Buffer, CallbackResult, Error, Hashtable, LossyCString, ReturnCode, Weechat,
+};
+
+/// Hook for printed messages, the hook is removed when the object is dropped.
+pub struct PrintHook {
+    _hook: Hook,
+    _hook_data: Box<PrintHookData>,
+}
+
+type PrintHookCbT = Box<dyn FnMut(&Weechat, Buffer, PrintedLine) -> ReturnCode>;
+
+struct PrintHookData {
+    callback: PrintHookCbT,
+    weechat_ptr: *mut t_weechat_plugin,
+}
+
+/// A line that was printed to a buffer, passed to the print hook callback.
+pub struct PrintedLine<'a> {
+    /// The date of the line, as a unix timestamp.
+    pub date: i64,
+    /// The tags of the line.
+    pub tags: Vec<Cow<'a, str>>,
+    /// Is the line displayed or is it filtered.
+    pub displayed: bool,
+    /// Is the line a highlight.
+    pub highlight: bool,
+    /// The prefix of the line.
+    pub prefix: Cow<'a, str>,
+    /// The message of the line.
+    pub message: Cow<'a, str>,
+}
+
+/// Hook for lines added to buffers, the hook is removed when the object is
+/// dropped.
+pub struct LineHook {
+    _hook: Hook,
+    _hook_data: Box<LineHookData>,
+}
+
+type LineHookCbT = Box<
+    dyn FnMut(
+        &Weechat,
+        &HashMap<String, String>,
+    ) -> Option<HashMap<String, String>>,
+>;
+
+struct LineHookData {
+    callback: LineHookCbT,
+    weechat_ptr: *mut t_weechat_plugin,
+}
+
+impl PrintHook {
+    /// Hook a message printed to a buffer.
+    ///
+    /// * `weechat` - The weechat object the hook is created with.
+    /// * `buffer` - The buffer to watch, if no buffer is given messages from
+    ///     all buffers are caught.
+    /// * `tags` - Only catch messages with these tags, comma separated tags
+    ///     must all be present (logical "and") while tags separated by `|`
+    ///     form a logical "or". An empty string catches all messages.
+    /// * `message` - Only catch messages containing this string, an empty
+    ///     string catches all messages.
+    /// * `strip_colors` - Should colors be stripped from the message before
+    ///     it is passed to the callback.
+    /// * `callback` - A closure that will be called when a message is
+    ///     printed.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn new<C, R>(
+        weechat: &Weechat,
+        buffer: Option<&Buffer>,
+        tags: &str,
+        message: &str,
+        strip_colors: bool,
+        mut callback: C,
+    ) -> Result<PrintHook, Error>
+    where
+        C: FnMut(&Weechat, Buffer, PrintedLine) -> R + 'static,
+        R: CallbackResult,
+    {
+        unsafe extern "C" fn c_hook_cb(
+            pointer: *const c_void,
+            _data: *mut c_void,
+            buffer: *mut t_gui_buffer,
+            date: libc::time_t,
+            tags_count: c_int,
+            tags: *mut *const c_char,
+            displayed: c_int,
+            highlight: c_int,
+            prefix: *const c_char,
+            message: *const c_char,
+        ) -> c_int {
+            let hook_data: &mut PrintHookData =
+                { &mut *(pointer as *mut PrintHookData) };
+
+            let weechat = Weechat::from_ptr(hook_data.weechat_ptr);
+            let buffer = Buffer::from_ptr(hook_data.weechat_ptr, buffer);
+
+            let to_cow = |s: *const c_char| {
+                if s.is_null() {
+                    Cow::from("")
+                } else {
+                    CStr::from_ptr(s).to_string_lossy()
+                }
+            };
+
+            let tags = (0..tags_count as isize)
+                .map(|i| to_cow(*tags.offset(i)))
+                .collect();
+
+            let line = PrintedLine {
+                date: i64::from(date),
+                tags,
+                displayed: displayed != 0,
+                highlight: highlight != 0,
+                prefix: to_cow(prefix),
+                message: to_cow(message),
+            };
+
+            (hook_data.callback)(&weechat, buffer, line) as i32
+        }
+
+        let data = Box::new(PrintHookData {
+            callback: Box::new(move |weechat, buffer, line| {
+                callback(weechat, buffer, line).into_return_code(weechat)
+            }),
+            weechat_ptr: weechat.ptr,
+        });
+
+        let data_ref = Box::leak(data);
+        let hook_print = weechat.get().hook_print.unwrap();
+
+        let buffer_ptr = buffer.map_or(ptr::null_mut(), |b| b.ptr);
+        let tags = LossyCString::new(tags);
+        let message = LossyCString::new(message);
+
+        let hook_ptr = unsafe {
+            hook_print(
+                weechat.ptr,
+                buffer_ptr,
+                tags.as_ptr(),
+                message.as_ptr(),
+                strip_colors as i32,
+                Some(c_hook_cb),
+                data_ref as *const _ as *const c_void,
+                ptr::null_mut(),
+            )
+        };
+        let hook_data = unsafe { Box::from_raw(data_ref) };
+
+        if hook_ptr.is_null() {
+            return Err(Error::HookCreation);
+        }
+
+        let hook = Hook {
+            ptr: hook_ptr,
+            weechat_ptr: weechat.ptr,
+        };
+
+        Ok(PrintHook {
+            _hook: hook,
+            _hook_data: hook_data,
+        })
+    }
+}
+
+impl LineHook {
+    /// Hook a line before it is added to a buffer.
+    ///
+    /// * `weechat` - The weechat object the hook is created with.
+    /// * `buffer_type` - Catch lines on this buffer type, valid values are
+    ///     "formatted", "free" or "*" for all buffer types.
+    /// * `buffer_name` - Comma separated list of buffer masks, an empty string
+    ///     or "*" catches lines of all buffers.
+    /// * `tags` - Only catch lines with these tags, see `PrintHook::new()`.
+    /// * `callback` - A closure that will be called when a line is added to
+    ///     a buffer. The callback receives the line as a map, see the weechat
+    ///     API documentation of `hook_line()` for the list of keys. The
+    ///     callback can return a map containing the keys of the line that
+    ///     should be updated.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn new<C>(
+        weechat: &Weechat,
+        buffer_type: &str,
+        buffer_name: &str,
+        tags: &str,
+        callback: C,
+    ) -> Result<LineHook, Error>
+    where
+        C: FnMut(
+                &Weechat,
+                &HashMap<String, String>,
+            ) -> Option<HashMap<String, String>>
+            + 'static,
+    {
+        unsafe extern "C" fn c_hook_cb(
+            pointer: *const c_void,
+            _data: *mut c_void,
+            line: *mut t_hashtable,
+        ) -> *mut t_hashtable {
+            let hook_data: &mut LineHookData =
+                { &mut *(pointer as *mut LineHookData) };
+
+            let weechat = Weechat::from_ptr(hook_data.weechat_ptr);
+            let line = Hashtable::from_ptr(hook_data.weechat_ptr, line)
+                .to_string_map();
+
+            match (hook_data.callback)(&weechat, &line) {
+                // Weechat takes ownership of the returned hashtable and
+                // frees it.
+                Some(update) => weechat
+                    .hashtable_from_map(&update)
+                    .map(Hashtable::into_raw)
+                    .unwrap_or(ptr::null_mut()),
+                None => ptr::null_mut(),
+            }
+        }
+
+        let data = Box::new(LineHookData {
+            callback: Box::new(callback),
+            weechat_ptr: weechat.ptr,
+        });
+
+        let data_ref = Box::leak(data);
+        let hook_line = weechat.get().hook_line.unwrap();
+
+        let buffer_type = LossyCString::new(buffer_type);
+        let buffer_name = LossyCString::new(buffer_name);
+        let tags = LossyCString::new(tags);
+
+        let hook_ptr = unsafe {
+            hook_line(
+                weechat.ptr,
+                buffer_type.as_ptr(),
+                buffer_name.as_ptr(),
+                tags.as_ptr(),
+                Some(c_hook_cb),
+                data_ref as *const _ as *const c_void,
+                ptr::null_mut(),
+            )
+        };
+        let hook_data = unsafe { Box::from_raw(data_ref) };
+
+        if hook_ptr.is_null() {
+            return Err(Error::HookCreation);
+        }
+
+        let hook = Hook {
+            ptr: hook_ptr,
+            weechat_ptr: weechat.ptr,
+        };
+
+        Ok(LineHook {
+            _hook: hook,
+            _hook_data: hook_data,
+        })
+    }
+}
+
+impl Weechat {
+    /// Hook a message printed to a buffer.
+    ///
+    /// See `PrintHook::new()` to create the hook with a closure.
+    /// * `buffer` - The buffer to watch, if no buffer is given messages from
+    ///     all buffers are caught.
+    /// * `tags` - Only catch messages with these tags, comma separated tags
+    ///     must all be present (logical "and") while tags separated by `|`
+    ///     form a logical "or". An empty string catches all messages.
+    /// * `message` - Only catch messages containing this string, an empty
+    ///     string catches all messages.
+    /// * `strip_colors` - Should colors be stripped from the message before
+    ///     it is passed to the callback.
+    /// * `callback` - A function that will be called when a message is
+    ///     printed.
+    /// * `callback_data` - Data that will be passed to the callback every time
+    ///     the callback runs. This data will be freed when the hook is
+    ///     unhooked.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn hook_print<T: 'static>(
+        &self,
+        buffer: Option<&Buffer>,
+        tags: &str,
+        message: &str,
+        strip_colors: bool,
+        callback: fn(data: &T, buffer: Buffer, line: PrintedLine) -> ReturnCode,
+        callback_data: T,
+    ) -> Result<PrintHook, Error> {
+        PrintHook::new(
+            self,
+            buffer,
+            tags,
+            message,
+            strip_colors,
+            move |_, buffer, line| callback(&callback_data, buffer, line),
+        )
+    }
+
+    /// Hook a line before it is added to a buffer.
+    ///
+    /// See `LineHook::new()` to create the hook with a closure.
+    /// * `buffer_type` - Catch lines on this buffer type, valid values are
+    ///     "formatted", "free" or "*" for all buffer types.
+    /// * `buffer_name` - Comma separated list of buffer masks, an empty string
+    ///     or "*" catches lines of all buffers.
+    /// * `tags` - Only catch lines with these tags, see `hook_print()`.
+    /// * `callback` - A function that will be called when a line is added to
+    ///     a buffer. The callback receives the line as a map, see the weechat
+    ///     API documentation of `hook_line()` for the list of keys. The
+    ///     callback can return a map containing the keys of the line that
+    ///     should be updated.
+    /// * `callback_data` - Data that will be passed to the callback every time
+    ///     the callback runs. This data will be freed when the hook is
+    ///     unhooked.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn hook_line<T: 'static>(
+        &self,
+        buffer_type: &str,
+        buffer_name: &str,
+        tags: &str,
+        callback: fn(
+            data: &T,
+            weechat: &Weechat,
+            line: &HashMap<String, String>,
+        ) -> Option<HashMap<String, String>>,
+        callback_data: T,
+    ) -> Result<LineHook, Error> {
+        LineHook::new(
+            self,
+            buffer_type,
+            buffer_name,
+            tags,
+            move |weechat, line| callback(&callback_data, weechat, line),
+        )
+    }
+}
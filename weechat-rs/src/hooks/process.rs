@@ -0,0 +1,404 @@
+//! Hooks for running external processes in the background.
+
+use libc::{c_char, c_int};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::os::raw::c_void;
+use std::ptr;
+use std::time::Duration;
+
+use weechat_sys::{
+    t_weechat_plugin, WEECHAT_HOOK_PROCESS_CHILD, WEECHAT_HOOK_PROCESS_ERROR,
+    WEECHAT_HOOK_PROCESS_RUNNING,
+};
+
+use super::Hook;
+use crate::{CallbackResult, Error, LossyCString, ReturnCode, Weechat};
+
+/// Hook for a background process, the process is killed and the hook removed
+/// when the object is dropped.
+pub struct ProcessHook {
+    hook: Option<Hook>,
+    hook_data: Box<ProcessHookData>,
+}
+
+type ProcessHookCbT = Box<dyn FnMut(&Weechat, ProcessOutput) -> ReturnCode>;
+
+struct ProcessHookData {
+    callback: ProcessHookCbT,
+    weechat_ptr: *mut t_weechat_plugin,
+    finished: bool,
+}
+
+/// The state of a hooked process, as reported to the process callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessReturnCode {
+    /// The process is still running, more output will follow.
+    Running,
+    /// The process couldn't be launched or it was killed because of a
+    /// timeout.
+    Error,
+    /// The process has finished with the given exit code.
+    Finished(i32),
+    /// The callback is running in the forked child process. This only
+    /// happens for commands that start with "func:", the output of the
+    /// callback is sent back to the parent as the output of the process.
+    Child,
+}
+
+impl ProcessReturnCode {
+    pub(crate) fn from_int(code: c_int) -> ProcessReturnCode {
+        match code {
+            WEECHAT_HOOK_PROCESS_RUNNING => ProcessReturnCode::Running,
+            WEECHAT_HOOK_PROCESS_ERROR => ProcessReturnCode::Error,
+            WEECHAT_HOOK_PROCESS_CHILD => ProcessReturnCode::Child,
+            code => ProcessReturnCode::Finished(code),
+        }
+    }
+}
+
+/// Output of a hooked process that is passed to the process callback.
+///
+/// If the output of the process is bigger than the flush size the callback
+/// will be called multiple times, each time with a chunk of the output.
+pub struct ProcessOutput<'a> {
+    /// The command that was run.
+    pub command: Cow<'a, str>,
+    /// The state of the process.
+    pub return_code: ProcessReturnCode,
+    /// A chunk of the standard output of the process, if there is any.
+    pub stdout: Option<Cow<'a, str>>,
+    /// A chunk of the standard error of the process, if there is any.
+    pub stderr: Option<Cow<'a, str>>,
+}
+
+/// Options for a hooked process.
+///
+/// The options map to the options hashtable of the weechat
+/// `hook_process_hashtable()` function.
+#[derive(Default)]
+pub struct ProcessOptions {
+    arguments: Vec<String>,
+    stdin: bool,
+    buffer_flush: Option<usize>,
+    detached: bool,
+}
+
+impl ProcessOptions {
+    /// Create a new set of process options with default values.
+    pub fn new() -> ProcessOptions {
+        ProcessOptions::default()
+    }
+
+    /// Add an argument for the command. If arguments are added, the command
+    /// is executed directly instead of being run by a shell.
+    pub fn argument(mut self, argument: &str) -> ProcessOptions {
+        self.arguments.push(argument.to_owned());
+        self
+    }
+
+    /// Add multiple arguments for the command.
+    pub fn arguments<I, S>(mut self, arguments: I) -> ProcessOptions
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.arguments
+            .extend(arguments.into_iter().map(|a| a.as_ref().to_owned()));
+        self
+    }
+
+    /// Create a pipe for writing data on the standard input of the process.
+    /// See `ProcessHook::write_stdin()`.
+    pub fn stdin(mut self, stdin: bool) -> ProcessOptions {
+        self.stdin = stdin;
+        self
+    }
+
+    /// Set the minimum number of bytes that are buffered before the callback
+    /// is called with the output of the process. Setting this to 1 sends the
+    /// output to the callback as soon as it is available.
+    pub fn buffer_flush(mut self, bytes: usize) -> ProcessOptions {
+        self.buffer_flush = Some(bytes);
+        self
+    }
+
+    /// Run the process in detached mode, the output of the process is
+    /// discarded.
+    ///
+    /// Weechat removes the hook of a detached process as soon as the process
+    /// is started, the callback is never called and dropping the hook
+    /// doesn't kill the process.
+    pub fn detached(mut self, detached: bool) -> ProcessOptions {
+        self.detached = detached;
+        self
+    }
+
+    fn to_map(&self) -> HashMap<String, String> {
+        let mut map: HashMap<String, String> = self
+            .arguments
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| (format!("arg{}", i + 1), arg.clone()))
+            .collect();
+
+        if self.stdin {
+            map.insert("stdin".to_owned(), "1".to_owned());
+        }
+
+        if let Some(bytes) = self.buffer_flush {
+            map.insert("buffer_flush".to_owned(), bytes.to_string());
+        }
+
+        if self.detached {
+            map.insert("detached".to_owned(), "1".to_owned());
+        }
+
+        map
+    }
+}
+
+/// Convert the timeout to milliseconds, weechat takes the timeout as an int
+/// so longer timeouts are clamped to the maximum weechat supports.
+fn timeout_to_millis(timeout: Duration) -> c_int {
+    timeout.as_millis().min(c_int::MAX as u128) as c_int
+}
+
+impl ProcessHook {
+    fn set(&self, property: &str, value: &str) {
+        if self.hook_data.finished {
+            return;
+        }
+
+        if let Some(hook) = &self.hook {
+            let weechat = Weechat::from_ptr(hook.weechat_ptr);
+            let hook_set = weechat.get().hook_set.unwrap();
+
+            let property = LossyCString::new(property);
+            let value = LossyCString::new(value);
+
+            unsafe { hook_set(hook.ptr, property.as_ptr(), value.as_ptr()) };
+        }
+    }
+
+    /// Write data to the standard input of the process. The process needs to
+    /// be created with the `stdin` option enabled.
+    pub fn write_stdin(&self, data: &str) {
+        self.set("stdin", data);
+    }
+
+    /// Close the pipe used to send data to the standard input of the process.
+    pub fn close_stdin(&self) {
+        self.set("stdin_close", "");
+    }
+
+    /// Has the process finished running.
+    pub fn finished(&self) -> bool {
+        self.hook_data.finished
+    }
+}
+
+impl Drop for ProcessHook {
+    fn drop(&mut self) {
+        // Weechat removes the hook by itself once the process has finished
+        // or right away if the process is detached, unhooking it again would
+        // be a double free.
+        if self.hook_data.finished {
+            if let Some(hook) = self.hook.take() {
+                std::mem::forget(hook);
+            }
+        }
+    }
+}
+
+impl ProcessHook {
+    /// Run a command in a background process.
+    ///
+    /// * `weechat` - The weechat object the hook is created with.
+    /// * `command` - The command to run, the command is run by a shell unless
+    ///     arguments are given in the options.
+    /// * `options` - Options for the process, see `ProcessOptions`.
+    /// * `timeout` - Timeout for the command, the process is killed after
+    ///     the timeout. A timeout of 0 means no timeout, timeouts longer
+    ///     than `i32::MAX` milliseconds (about 24 days) are clamped.
+    /// * `callback` - A closure that will be called when data from the
+    ///     process is available or when the process ends.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    ///
+    /// If the process is detached Weechat removes the hook right away, the
+    /// returned hook is marked as finished.
+    pub fn new<C, R>(
+        weechat: &Weechat,
+        command: &str,
+        options: ProcessOptions,
+        timeout: Duration,
+        mut callback: C,
+    ) -> Result<ProcessHook, Error>
+    where
+        C: FnMut(&Weechat, ProcessOutput) -> R + 'static,
+        R: CallbackResult,
+    {
+        unsafe extern "C" fn c_hook_cb(
+            pointer: *const c_void,
+            _data: *mut c_void,
+            command: *const c_char,
+            return_code: c_int,
+            out: *const c_char,
+            err: *const c_char,
+        ) -> c_int {
+            let hook_data: &mut ProcessHookData =
+                { &mut *(pointer as *mut ProcessHookData) };
+
+            let return_code = ProcessReturnCode::from_int(return_code);
+
+            match return_code {
+                ProcessReturnCode::Running | ProcessReturnCode::Child => (),
+                _ => hook_data.finished = true,
+            }
+
+            let to_cow = |s: *const c_char| {
+                if s.is_null() {
+                    None
+                } else {
+                    Some(CStr::from_ptr(s).to_string_lossy())
+                }
+            };
+
+            let output = ProcessOutput {
+                command: CStr::from_ptr(command).to_string_lossy(),
+                return_code,
+                stdout: to_cow(out),
+                stderr: to_cow(err),
+            };
+
+            (hook_data.callback)(
+                &Weechat::from_ptr(hook_data.weechat_ptr),
+                output,
+            ) as i32
+        }
+
+        // Weechat copies the options, our hashtable is freed once it goes out
+        // of scope.
+        let hashtable = weechat.hashtable_from_map(&options.to_map())?;
+
+        let data = Box::new(ProcessHookData {
+            callback: Box::new(move |weechat, output| {
+                callback(weechat, output).into_return_code(weechat)
+            }),
+            weechat_ptr: weechat.ptr,
+            finished: false,
+        });
+
+        let data_ref = Box::leak(data);
+
+        let hook_process_hashtable =
+            weechat.get().hook_process_hashtable.unwrap();
+
+        let command = LossyCString::new(command);
+        let timeout = timeout_to_millis(timeout);
+
+        let hook_ptr = unsafe {
+            hook_process_hashtable(
+                weechat.ptr,
+                command.as_ptr(),
+                hashtable.ptr,
+                timeout,
+                Some(c_hook_cb),
+                data_ref as *const _ as *const c_void,
+                ptr::null_mut(),
+            )
+        };
+
+        let mut hook_data = unsafe { Box::from_raw(data_ref) };
+
+        if hook_ptr.is_null() {
+            return Err(Error::HookCreation);
+        }
+
+        hook_data.finished = options.detached;
+
+        let hook = Hook {
+            ptr: hook_ptr,
+            weechat_ptr: weechat.ptr,
+        };
+
+        Ok(ProcessHook {
+            hook: Some(hook),
+            hook_data,
+        })
+    }
+}
+
+impl Weechat {
+    /// Run a command in a background process.
+    ///
+    /// See `ProcessHook::new()` to create the hook with a closure.
+    /// * `command` - The command to run, the command is run by a shell.
+    /// * `timeout` - Timeout for the command, the process is killed after
+    ///     the timeout. A timeout of 0 means no timeout, timeouts longer
+    ///     than `i32::MAX` milliseconds (about 24 days) are clamped.
+    /// * `callback` - A function that will be called when data from the
+    ///     process is available or when the process ends.
+    /// * `callback_data` - Data that will be passed to the callback every time
+    ///     the callback runs. This data will be freed when the hook is
+    ///     unhooked.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn hook_process<T: 'static>(
+        &self,
+        command: &str,
+        timeout: Duration,
+        callback: fn(
+            data: &T,
+            weechat: &Weechat,
+            output: ProcessOutput,
+        ) -> ReturnCode,
+        callback_data: T,
+    ) -> Result<ProcessHook, Error> {
+        self.hook_process_hashtable(
+            command,
+            ProcessOptions::default(),
+            timeout,
+            callback,
+            callback_data,
+        )
+    }
+
+    /// Run a command in a background process with additional options.
+    ///
+    /// See `ProcessHook::new()` to create the hook with a closure.
+    /// * `command` - The command to run.
+    /// * `options` - Options for the process, see `ProcessOptions`.
+    /// * `timeout` - Timeout for the command, the process is killed after
+    ///     the timeout. A timeout of 0 means no timeout, timeouts longer
+    ///     than `i32::MAX` milliseconds (about 24 days) are clamped.
+    /// * `callback` - A function that will be called when data from the
+    ///     process is available or when the process ends.
+    /// * `callback_data` - Data that will be passed to the callback every time
+    ///     the callback runs. This data will be freed when the hook is
+    ///     unhooked.
+    ///
+    /// Returns an error if Weechat failed to create the hook.
+    pub fn hook_process_hashtable<T: 'static>(
+        &self,
+        command: &str,
+        options: ProcessOptions,
+        timeout: Duration,
+        callback: fn(
+            data: &T,
+            weechat: &Weechat,
+            output: ProcessOutput,
+        ) -> ReturnCode,
+        callback_data: T,
+    ) -> Result<ProcessHook, Error> {
+        ProcessHook::new(
+            self,
+            command,
+            options,
+            timeout,
+            move |weechat, out| callback(&callback_data, weechat, out),
+        )
+    }
+}
@@ -1,12 +1,16 @@
 //! Weechat Infolist module.
 
+use libc::{c_int, time_t};
+use std::collections::{hash_map, HashMap};
 use std::ffi::CStr;
 use std::os::raw::c_void;
-use std::ptr;
+use std::{mem, ptr, slice};
 
-use weechat_sys::{t_gui_buffer, t_infolist, t_weechat_plugin};
+use weechat_sys::{
+    t_gui_buffer, t_infolist, t_infolist_item, t_weechat_plugin,
+};
 
-use crate::{Buffer, LossyCString, Weechat};
+use crate::{Buffer, Error, LossyCString, Weechat};
 use std::borrow::Cow;
 
 /// Weechat Infolist type.
@@ -15,6 +19,89 @@ pub struct Infolist {
     pub(crate) weechat_ptr: *mut t_weechat_plugin,
 }
 
+/// The type of a variable of an infolist item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InfolistVarType {
+    /// An integer variable.
+    Integer,
+    /// A string variable.
+    String,
+    /// A pointer variable.
+    Pointer,
+    /// A variable containing raw data.
+    Buffer,
+    /// A time variable.
+    Time,
+}
+
+impl InfolistVarType {
+    pub(crate) fn from_str(var_type: &str) -> Option<InfolistVarType> {
+        match var_type {
+            "i" => Some(InfolistVarType::Integer),
+            "s" => Some(InfolistVarType::String),
+            "p" => Some(InfolistVarType::Pointer),
+            "b" => Some(InfolistVarType::Buffer),
+            "t" => Some(InfolistVarType::Time),
+            _ => None,
+        }
+    }
+}
+
+/// The value of a variable of an infolist item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InfolistVariable {
+    /// Integer data.
+    Integer(i32),
+    /// String data.
+    String(String),
+    /// Pointer data.
+    Pointer(*mut c_void),
+    /// Raw data.
+    Buffer(Vec<u8>),
+    /// Time data, a unix timestamp.
+    Time(i64),
+}
+
+/// An owned copy of an infolist item, maps the variable names of the item to
+/// their values.
+#[derive(Debug, Clone)]
+pub struct InfolistItem {
+    variables: HashMap<String, InfolistVariable>,
+}
+
+/// A builder for a new infolist, used to provide infolists to weechat.
+///
+/// The infolist is freed when the builder is dropped, unless it is handed over
+/// to weechat by returning it from an infolist hook callback.
+pub struct InfolistBuilder {
+    ptr: *mut t_infolist,
+    weechat_ptr: *mut t_weechat_plugin,
+}
+
+/// A builder for a single item of a new infolist, see
+/// `InfolistBuilder::new_item()`.
+pub struct InfolistItemBuilder<'a> {
+    ptr: *mut t_infolist_item,
+    builder: &'a InfolistBuilder,
+}
+
+/// An iterator over the items of an infolist, see `Infolist::iter()`.
+pub struct InfolistIter<'a> {
+    infolist: &'a Infolist,
+}
+
+impl<'a> Iterator for InfolistIter<'a> {
+    type Item = InfolistItem;
+
+    fn next(&mut self) -> Option<InfolistItem> {
+        if self.infolist.next() {
+            Some(self.infolist.current_item())
+        } else {
+            None
+        }
+    }
+}
+
 impl Drop for Infolist {
     fn drop(&mut self) {
         let weechat = Weechat::from_ptr(self.weechat_ptr);
@@ -23,7 +110,33 @@ impl Drop for Infolist {
     }
 }
 
+impl Drop for InfolistBuilder {
+    fn drop(&mut self) {
+        let weechat = Weechat::from_ptr(self.weechat_ptr);
+        let free = weechat.get().infolist_free.unwrap();
+        unsafe { free(self.ptr) }
+    }
+}
+
 impl Weechat {
+    /// Create a new empty infolist.
+    /// Returns a builder that can be used to add items to the infolist, or
+    /// an error if weechat couldn't create the infolist.
+    pub fn infolist_new(&self) -> Result<InfolistBuilder, Error> {
+        let infolist_new = self.get().infolist_new.unwrap();
+
+        let ptr = unsafe { infolist_new(self.ptr) };
+
+        if ptr.is_null() {
+            return Err(Error::InfolistCreation);
+        }
+
+        Ok(InfolistBuilder {
+            ptr,
+            weechat_ptr: self.ptr,
+        })
+    }
+
     /// Get an infolist.
     /// * `name` - The name of the infolist.
     /// * `arguments` - Optional arguments for the infolist. See the weechat
@@ -76,26 +189,109 @@ impl Infolist {
         ret == 1
     }
 
+    /// Reset the "cursor" of the infolist, the next call to `next()` moves
+    /// the cursor to the first item and a call to `prev()` to the last item.
+    pub fn reset_item_cursor(&self) {
+        let weechat = Weechat::from_ptr(self.weechat_ptr);
+        let infolist_reset_item_cursor =
+            weechat.get().infolist_reset_item_cursor.unwrap();
+        unsafe { infolist_reset_item_cursor(self.ptr) };
+    }
+
     /// Get the list of fields for current infolist item.
-    /// Returns a string with the comma separated list of type/name tuple
-    /// separated by a colon.
-    /// The types are: "i" (integer), "s" (string), "p" (pointer), "b" (buffer),
-    /// "t" (time).
-    /// Example: "i:my_integer,s:my_string"
-    pub fn fields(&self) -> Option<Cow<str>> {
+    /// Returns a list of variable names and their types, the list is empty
+    /// if the cursor doesn't point to an item.
+    pub fn fields(&self) -> Vec<(String, InfolistVarType)> {
         let weechat = Weechat::from_ptr(self.weechat_ptr);
         let infolist_fields = weechat.get().infolist_fields.unwrap();
-        unsafe {
+
+        let fields = unsafe {
             let ret = infolist_fields(self.ptr);
             if ret.is_null() {
-                None
-            } else {
-                Some(CStr::from_ptr(ret).to_string_lossy())
+                return Vec::new();
             }
-        }
+            CStr::from_ptr(ret).to_string_lossy()
+        };
+
+        // The fields are a comma separated list of type/name tuples
+        // separated by a colon, e.g. "i:my_integer,s:my_string".
+        fields
+            .split(',')
+            .filter_map(|field| {
+                let mut parts = field.splitn(2, ':');
+                let var_type = InfolistVarType::from_str(parts.next()?)?;
+                let name = parts.next()?;
+                Some((name.to_owned(), var_type))
+            })
+            .collect()
+    }
+
+    /// Get an iterator over the items of the infolist.
+    ///
+    /// The cursor is reset before the iteration starts and moved while
+    /// iterating, the iterator yields owned copies of the items.
+    pub fn iter(&self) -> InfolistIter {
+        self.reset_item_cursor();
+        InfolistIter { infolist: self }
+    }
+
+    fn current_item(&self) -> InfolistItem {
+        let variables = self
+            .fields()
+            .into_iter()
+            .map(|(name, var_type)| {
+                let value = match var_type {
+                    InfolistVarType::Integer => {
+                        InfolistVariable::Integer(self.get_integer(&name))
+                    }
+                    InfolistVarType::String => InfolistVariable::String(
+                        self.get_string(&name)
+                            .map(Cow::into_owned)
+                            .unwrap_or_default(),
+                    ),
+                    InfolistVarType::Pointer => {
+                        InfolistVariable::Pointer(self.get_pointer(&name))
+                    }
+                    InfolistVarType::Buffer => InfolistVariable::Buffer(
+                        self.get_raw_buffer(&name).unwrap_or_default(),
+                    ),
+                    InfolistVarType::Time => {
+                        InfolistVariable::Time(self.get_time(&name))
+                    }
+                };
+                (name, value)
+            })
+            .collect();
+
+        InfolistItem { variables }
+    }
+
+    /// Get the value of an integer variable in the current infolist item.
+    /// * `name` - The variable name of the infolist item.
+    pub fn get_integer(&self, name: &str) -> i32 {
+        let weechat = Weechat::from_ptr(self.weechat_ptr);
+        let infolist_integer = weechat.get().infolist_integer.unwrap();
+
+        let name = LossyCString::new(name);
+
+        unsafe { infolist_integer(self.ptr, name.as_ptr()) }
+    }
+
+    /// Get the value of a time variable in the current infolist item as a
+    /// unix timestamp.
+    /// * `name` - The variable name of the infolist item.
+    pub fn get_time(&self, name: &str) -> i64 {
+        let weechat = Weechat::from_ptr(self.weechat_ptr);
+        let infolist_time = weechat.get().infolist_time.unwrap();
+
+        let name = LossyCString::new(name);
+
+        unsafe { infolist_time(self.ptr, name.as_ptr()) as i64 }
     }
 
-    fn get_pointer(&self, name: &str) -> *mut c_void {
+    /// Get the value of a pointer variable in the current infolist item.
+    /// * `name` - The variable name of the infolist item.
+    pub fn get_pointer(&self, name: &str) -> *mut c_void {
         let weechat = Weechat::from_ptr(self.weechat_ptr);
         let infolist_pointer = weechat.get().infolist_pointer.unwrap();
 
@@ -104,6 +300,29 @@ impl Infolist {
         unsafe { infolist_pointer(self.ptr, name.as_ptr()) }
     }
 
+    /// Get a copy of the data of a buffer variable in the current infolist
+    /// item.
+    /// * `name` - The variable name of the infolist item.
+    pub fn get_raw_buffer(&self, name: &str) -> Option<Vec<u8>> {
+        let weechat = Weechat::from_ptr(self.weechat_ptr);
+        let infolist_buffer = weechat.get().infolist_buffer.unwrap();
+
+        let name = LossyCString::new(name);
+        let mut size = 0;
+
+        unsafe {
+            let data = infolist_buffer(self.ptr, name.as_ptr(), &mut size);
+            if data.is_null() || size < 0 {
+                None
+            } else {
+                Some(
+                    slice::from_raw_parts(data as *const u8, size as usize)
+                        .to_vec(),
+                )
+            }
+        }
+    }
+
     /// Get the buffer of the current infolist item.
     /// If the infolist item doesn't have a buffer None is returned.
     pub fn get_buffer(&self) -> Option<Buffer> {
@@ -134,3 +353,220 @@ impl Infolist {
         }
     }
 }
+
+impl<'a> IntoIterator for &'a Infolist {
+    type Item = InfolistItem;
+    type IntoIter = InfolistIter<'a>;
+
+    fn into_iter(self) -> InfolistIter<'a> {
+        self.iter()
+    }
+}
+
+impl InfolistItem {
+    /// Get the value of a variable.
+    /// * `name` - The name of the variable.
+    pub fn get(&self, name: &str) -> Option<&InfolistVariable> {
+        self.variables.get(name)
+    }
+
+    /// Get the value of an integer variable.
+    /// * `name` - The name of the variable.
+    pub fn get_integer(&self, name: &str) -> Option<i32> {
+        match self.get(name)? {
+            InfolistVariable::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Get the value of a string variable.
+    /// * `name` - The name of the variable.
+    pub fn get_string(&self, name: &str) -> Option<&str> {
+        match self.get(name)? {
+            InfolistVariable::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get the value of a pointer variable.
+    /// * `name` - The name of the variable.
+    pub fn get_pointer(&self, name: &str) -> Option<*mut c_void> {
+        match self.get(name)? {
+            InfolistVariable::Pointer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Get the value of a time variable as a unix timestamp.
+    /// * `name` - The name of the variable.
+    pub fn get_time(&self, name: &str) -> Option<i64> {
+        match self.get(name)? {
+            InfolistVariable::Time(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Get the number of variables of the item.
+    pub fn len(&self) -> usize {
+        self.variables.len()
+    }
+
+    /// Does the item have no variables.
+    pub fn is_empty(&self) -> bool {
+        self.variables.is_empty()
+    }
+
+    /// Get an iterator over the variable names and values of the item.
+    pub fn iter(&self) -> hash_map::Iter<String, InfolistVariable> {
+        self.variables.iter()
+    }
+
+    /// Convert the item into a map of variable names and values.
+    pub fn into_map(self) -> HashMap<String, InfolistVariable> {
+        self.variables
+    }
+}
+
+impl InfolistBuilder {
+    /// Give up ownership of the infolist and return the raw pointer, used to
+    /// hand the infolist over to weechat.
+    pub(crate) fn into_raw(self) -> *mut t_infolist {
+        let ptr = self.ptr;
+        mem::forget(self);
+        ptr
+    }
+
+    /// Add a new item to the infolist.
+    /// Returns a builder that can be used to add variables to the item.
+    pub fn new_item(&mut self) -> InfolistItemBuilder {
+        let weechat = Weechat::from_ptr(self.weechat_ptr);
+        let infolist_new_item = weechat.get().infolist_new_item.unwrap();
+
+        let ptr = unsafe { infolist_new_item(self.ptr) };
+
+        InfolistItemBuilder { ptr, builder: self }
+    }
+}
+
+impl<'a> InfolistItemBuilder<'a> {
+    fn get_weechat(&self) -> Weechat {
+        Weechat::from_ptr(self.builder.weechat_ptr)
+    }
+
+    /// Add an integer variable to the item.
+    /// * `name` - The name of the variable.
+    /// * `value` - The value of the variable.
+    pub fn new_var_integer(
+        self,
+        name: &str,
+        value: i32,
+    ) -> InfolistItemBuilder<'a> {
+        let weechat = self.get_weechat();
+        let infolist_new_var_integer =
+            weechat.get().infolist_new_var_integer.unwrap();
+
+        let name = LossyCString::new(name);
+
+        unsafe { infolist_new_var_integer(self.ptr, name.as_ptr(), value) };
+        self
+    }
+
+    /// Add a string variable to the item.
+    /// * `name` - The name of the variable.
+    /// * `value` - The value of the variable.
+    pub fn new_var_string(
+        self,
+        name: &str,
+        value: &str,
+    ) -> InfolistItemBuilder<'a> {
+        let weechat = self.get_weechat();
+        let infolist_new_var_string =
+            weechat.get().infolist_new_var_string.unwrap();
+
+        let name = LossyCString::new(name);
+        let value = LossyCString::new(value);
+
+        unsafe {
+            infolist_new_var_string(self.ptr, name.as_ptr(), value.as_ptr())
+        };
+        self
+    }
+
+    /// Add a pointer variable to the item.
+    /// * `name` - The name of the variable.
+    /// * `pointer` - The value of the variable.
+    ///
+    /// # Safety
+    /// Consumers of the infolist will dereference the pointer, it needs to be
+    /// valid for the lifetime of the infolist.
+    pub unsafe fn new_var_pointer(
+        self,
+        name: &str,
+        pointer: *mut c_void,
+    ) -> InfolistItemBuilder<'a> {
+        let weechat = self.get_weechat();
+        let infolist_new_var_pointer =
+            weechat.get().infolist_new_var_pointer.unwrap();
+
+        let name = LossyCString::new(name);
+
+        infolist_new_var_pointer(self.ptr, name.as_ptr(), pointer);
+        self
+    }
+
+    /// Add a buffer pointer variable to the item.
+    /// * `name` - The name of the variable, usually "buffer".
+    /// * `buffer` - The buffer that should be stored in the variable.
+    pub fn new_var_buffer_pointer(
+        self,
+        name: &str,
+        buffer: &Buffer,
+    ) -> InfolistItemBuilder<'a> {
+        unsafe { self.new_var_pointer(name, buffer.ptr as *mut c_void) }
+    }
+
+    /// Add a variable containing raw data to the item, the data is copied.
+    /// * `name` - The name of the variable.
+    /// * `data` - The value of the variable.
+    pub fn new_var_buffer(
+        self,
+        name: &str,
+        data: &[u8],
+    ) -> InfolistItemBuilder<'a> {
+        let weechat = self.get_weechat();
+        let infolist_new_var_buffer =
+            weechat.get().infolist_new_var_buffer.unwrap();
+
+        let name = LossyCString::new(name);
+
+        unsafe {
+            infolist_new_var_buffer(
+                self.ptr,
+                name.as_ptr(),
+                data.as_ptr() as *mut c_void,
+                data.len() as c_int,
+            )
+        };
+        self
+    }
+
+    /// Add a time variable to the item.
+    /// * `name` - The name of the variable.
+    /// * `time` - The value of the variable as a unix timestamp.
+    pub fn new_var_time(
+        self,
+        name: &str,
+        time: i64,
+    ) -> InfolistItemBuilder<'a> {
+        let weechat = self.get_weechat();
+        let infolist_new_var_time =
+            weechat.get().infolist_new_var_time.unwrap();
+
+        let name = LossyCString::new(name);
+
+        unsafe {
+            infolist_new_var_time(self.ptr, name.as_ptr(), time as time_t)
+        };
+        self
+    }
+}
@@ -5,41 +5,86 @@ pub mod buffer;
 pub mod completion;
 pub mod config;
 pub mod config_options;
+pub mod error;
+pub mod eval;
+#[cfg(feature = "async")]
+pub mod executor;
+pub mod hashtable;
+pub mod hdata;
 pub mod hooks;
 pub mod infolist;
 pub mod plugin;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod weechat;
+pub mod window;
 
 pub use weechat_macro::weechat_plugin;
 
+pub use error::{CallbackResult, Error};
 pub use plugin::{WeechatPlugin, WeechatResult};
-pub use weechat::{ArgsWeechat, OptionChanged, Weechat};
+pub use weechat::{ArgsWeechat, OptionChanged, OptionUnset, Weechat};
+pub use window::Window;
 
-pub use buffer::{Buffer, Nick, NickArgs};
+pub use buffer::{
+    Buffer, BufferBuilder, BufferLine, BufferLines, Nick, NickArgs,
+};
 
 pub use config::{Config, ConfigSection, ConfigSectionInfo};
 pub use config_options::{
-    BooleanOption, ColorOption, ConfigOption, IntegerOption, StringOption,
+    AnyOption, BooleanOption, ColorOption, ConfigOption, IntegerOption,
+    StringOption,
 };
 
+pub use eval::EvalBuilder;
+
+pub use hashtable::{Hashtable, HashtableType, HashtableValue};
+
+pub use hdata::{Hdata, HdataIter, HdataPointer, HdataType};
+
 pub use hooks::{
-    CommandDescription, CommandHook, CommandRunHook, FdHook, FdHookMode,
-    SignalHook, SignalHookValue, TimerHook,
+    CommandDescription, CommandHook, CommandRunHook, ConfigHook, ConnectHook,
+    ConnectResult, ConnectSettings, ConnectStatus, FdHook, FdHookMode,
+    GnutlsOptions, HsignalHook, InfoHashtableHook, InfoHook, InfolistHook,
+    LineHook, MainThreadHook, MainThreadSender, MainThreadTask, ModifierHook,
+    PrintHook, PrintedLine, ProcessHook, ProcessOptions, ProcessOutput,
+    ProcessReturnCode, SignalHook, SignalHookValue, TimerHook,
 };
 
+#[cfg(feature = "async")]
+pub use hooks::{ConnectFuture, ProcessFuture, ProcessResult, Sleep};
+
+#[cfg(feature = "async")]
+pub use executor::JoinHandle;
+
 pub use completion::{Completion, CompletionHook, CompletionPosition};
 
-pub use infolist::Infolist;
+pub use infolist::{
+    Infolist, InfolistBuilder, InfolistItem, InfolistItemBuilder, InfolistIter,
+    InfolistVarType, InfolistVariable,
+};
 
+use libc::c_int;
 use std::ffi::CString;
 
 /// Status values for weechat callbacks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ReturnCode {
     Ok = weechat_sys::WEECHAT_RC_OK as isize,
     OkEat = weechat_sys::WEECHAT_RC_OK_EAT as isize,
     Error = weechat_sys::WEECHAT_RC_ERROR as isize,
 }
 
+impl ReturnCode {
+    pub(crate) fn from_int(v: c_int) -> ReturnCode {
+        match v {
+            weechat_sys::WEECHAT_RC_OK => ReturnCode::Ok,
+            weechat_sys::WEECHAT_RC_OK_EAT => ReturnCode::OkEat,
+            _ => ReturnCode::Error,
+        }
+    }
+}
+
 pub(crate) struct LossyCString;
 
 impl LossyCString {
@@ -1,9 +1,66 @@
-use crate::{ArgsWeechat, Weechat};
-use libc::c_int;
+use crate::{ArgsWeechat, Error, Weechat};
+use std::any::Any;
+use std::cell::RefCell;
+
+thread_local! {
+    static PLUGIN: RefCell<Option<Box<dyn Any>>> = RefCell::new(None);
+}
 
 pub trait WeechatPlugin: Sized {
     fn init(weechat: Weechat, args: ArgsWeechat) -> WeechatResult<Self>;
 }
 
-pub struct Error(c_int);
 pub type WeechatResult<T> = Result<T, Error>;
+
+pub(crate) fn free_plugin() {
+    // The plugin is taken out before it's dropped, so the drop
+    // implementation of the plugin doesn't run while the plugin is borrowed.
+    let plugin = PLUGIN.with(|plugin| plugin.borrow_mut().take());
+    drop(plugin);
+}
+
+impl Weechat {
+    /// Store the plugin struct so it can be accessed with
+    /// `Weechat::with_plugin()`.
+    ///
+    /// This is called by the `weechat_plugin!` macro once the plugin is
+    /// initialized and should not be called otherwise.
+    #[doc(hidden)]
+    pub fn set_plugin<P: WeechatPlugin + 'static>(plugin: P) {
+        PLUGIN.with(|cell| *cell.borrow_mut() = Some(Box::new(plugin)));
+    }
+
+    /// Run a closure with a reference to the plugin struct.
+    ///
+    /// Returns None if called outside of the Weechat main thread, while the
+    /// plugin is being initialized, if the plugin is currently borrowed
+    /// mutably or if `P` isn't the type of the plugin.
+    /// * `f` - The closure that receives the plugin struct.
+    pub fn with_plugin<P, R>(f: impl FnOnce(&P) -> R) -> Option<R>
+    where
+        P: WeechatPlugin + 'static,
+    {
+        PLUGIN.with(|cell| {
+            let plugin = cell.try_borrow().ok()?;
+            let plugin = plugin.as_ref()?.downcast_ref::<P>()?;
+            Some(f(plugin))
+        })
+    }
+
+    /// Run a closure with a mutable reference to the plugin struct.
+    ///
+    /// Returns None if called outside of the Weechat main thread, while the
+    /// plugin is being initialized, if the plugin is currently borrowed or
+    /// if `P` isn't the type of the plugin.
+    /// * `f` - The closure that receives the plugin struct.
+    pub fn with_plugin_mut<P, R>(f: impl FnOnce(&mut P) -> R) -> Option<R>
+    where
+        P: WeechatPlugin + 'static,
+    {
+        PLUGIN.with(|cell| {
+            let mut plugin = cell.try_borrow_mut().ok()?;
+            let plugin = plugin.as_mut()?.downcast_mut::<P>()?;
+            Some(f(plugin))
+        })
+    }
+}
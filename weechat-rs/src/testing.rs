@@ -0,0 +1,1191 @@
+//! A fake Weechat plugin API to test plugins without a running Weechat.
+//!
+//! `TestWeechat` creates a `t_weechat_plugin` whose API functions are
+//! implemented in-process. Printed lines are recorded per buffer, commands,
+//! signals, timers and file descriptor events can be fired into the hooks a
+//! plugin registered and configuration options are kept in memory.
+//!
+//! The following parts of the API are emulated:
+//! * printing and logging, colors and prefixes
+//! * buffers, their properties, local variables and nicklists
+//! * command, command run, signal, timer and file descriptor hooks
+//! * configuration files, sections and options, plugin options
+//! * hashtables
+//! * bars and bar items
+//!
+//! The other API functions that the crate uses do nothing and return null or
+//! zero. The API functions are called through C function pointers and can't
+//! panic, calling an API function that isn't emulated or using the API
+//! without a `TestWeechat` is recorded as a failure instead. The next call to
+//! a `TestWeechat` method, or dropping it, panics with the recorded failures.
+//!
+//! ```
+//! use weechat::testing::TestWeechat;
+//!
+//! let test = TestWeechat::new();
+//! let weechat = test.weechat();
+//!
+//! weechat.print("Hello");
+//! assert_eq!(test.printed_lines(&test.core_buffer()), vec!["Hello"]);
+//! ```
+
+use libc::{c_char, c_int, c_short, time_t};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::{CStr, CString};
+use std::os::raw::c_void;
+use std::os::unix::io::RawFd;
+use std::time::Duration;
+use std::{ptr, slice, thread};
+
+use weechat_sys::{
+    t_config_file, t_config_option, t_gui_bar_item, t_gui_buffer, t_gui_window,
+    t_hashtable, t_weechat_plugin, WEECHAT_RC_OK_EAT,
+};
+
+use crate::{
+    ArgsWeechat, Buffer, LossyCString, ReturnCode, SignalHookValue, Weechat,
+    WeechatPlugin, WeechatResult,
+};
+
+mod api;
+#[cfg(test)]
+mod tests;
+
+thread_local! {
+    static STATE: RefCell<Option<State>> = const { RefCell::new(None) };
+    static PLUGIN: Cell<*mut t_weechat_plugin> =
+        const { Cell::new(ptr::null_mut()) };
+    static FAILURES: RefCell<Vec<String>> =
+        const { RefCell::new(Vec::new()) };
+}
+
+type InputCbT = unsafe extern "C" fn(
+    *const c_void,
+    *mut c_void,
+    *mut t_gui_buffer,
+    *const c_char,
+) -> c_int;
+type CloseCbT = unsafe extern "C" fn(
+    *const c_void,
+    *mut c_void,
+    *mut t_gui_buffer,
+) -> c_int;
+type CommandCbT = unsafe extern "C" fn(
+    *const c_void,
+    *mut c_void,
+    *mut t_gui_buffer,
+    c_int,
+    *mut *mut c_char,
+    *mut *mut c_char,
+) -> c_int;
+type CommandRunCbT = unsafe extern "C" fn(
+    *const c_void,
+    *mut c_void,
+    *mut t_gui_buffer,
+    *const c_char,
+) -> c_int;
+type SignalCbT = unsafe extern "C" fn(
+    *const c_void,
+    *mut c_void,
+    *const c_char,
+    *const c_char,
+    *mut c_void,
+) -> c_int;
+type TimerCbT =
+    unsafe extern "C" fn(*const c_void, *mut c_void, c_int) -> c_int;
+type FdCbT = unsafe extern "C" fn(*const c_void, *mut c_void, c_int) -> c_int;
+type ProcessCbT = unsafe extern "C" fn(
+    *const c_void,
+    *mut c_void,
+    *const c_char,
+    c_int,
+    *const c_char,
+    *const c_char,
+) -> c_int;
+type ReloadCbT = unsafe extern "C" fn(
+    *const c_void,
+    *mut c_void,
+    *mut t_config_file,
+) -> c_int;
+type CheckCbT = unsafe extern "C" fn(
+    *const c_void,
+    *mut c_void,
+    *mut t_config_option,
+    *const c_char,
+) -> c_int;
+type ChangeCbT =
+    unsafe extern "C" fn(*const c_void, *mut c_void, *mut t_config_option);
+type BarItemCbT = unsafe extern "C" fn(
+    *const c_void,
+    *mut c_void,
+    *mut t_gui_bar_item,
+    *mut t_gui_window,
+    *mut t_gui_buffer,
+    *mut t_hashtable,
+) -> *mut c_char;
+
+/// A C callback together with the pointer and data it receives.
+#[derive(Clone, Copy)]
+struct Callback<F> {
+    function: F,
+    pointer: *const c_void,
+    data: *mut c_void,
+}
+
+impl<F> Callback<F> {
+    fn new(
+        function: Option<F>,
+        pointer: *const c_void,
+        data: *mut c_void,
+    ) -> Option<Callback<F>> {
+        function.map(|function| Callback {
+            function,
+            pointer,
+            data,
+        })
+    }
+}
+
+struct FakeBuffer {
+    properties: HashMap<String, CString>,
+    lines: Vec<String>,
+    input_callback: Option<Callback<InputCbT>>,
+    close_callback: Option<Callback<CloseCbT>>,
+}
+
+impl FakeBuffer {
+    fn new(plugin: &str, name: &str) -> FakeBuffer {
+        let mut buffer = FakeBuffer {
+            properties: HashMap::new(),
+            lines: Vec::new(),
+            input_callback: None,
+            close_callback: None,
+        };
+
+        buffer.set("plugin", plugin);
+        buffer.set("name", name);
+        buffer.set("short_name", name);
+        buffer.set("title", "");
+        buffer.set("input", "");
+
+        buffer
+    }
+
+    fn get(&self, property: &str) -> Option<&CString> {
+        self.properties.get(property)
+    }
+
+    fn get_str(&self, property: &str) -> &str {
+        self.get(property)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("")
+    }
+
+    fn set(&mut self, property: &str, value: &str) {
+        self.properties
+            .insert(property.to_owned(), LossyCString::new(value));
+
+        if property == "name" || property == "plugin" {
+            let full_name =
+                format!("{}.{}", self.get_str("plugin"), self.get_str("name"));
+            self.properties
+                .insert("full_name".to_owned(), LossyCString::new(full_name));
+        }
+    }
+}
+
+struct FakeGroup {
+    buffer: usize,
+    name: String,
+}
+
+struct FakeNick {
+    buffer: usize,
+    group: usize,
+    properties: HashMap<&'static str, CString>,
+}
+
+struct Timer {
+    interval: Duration,
+    next_call: Duration,
+    // None if the timer runs forever.
+    remaining_calls: Option<c_int>,
+    callback: Callback<TimerCbT>,
+}
+
+enum FakeHook {
+    Command {
+        name: String,
+        callback: Callback<CommandCbT>,
+    },
+    CommandRun {
+        pattern: String,
+        callback: Callback<CommandRunCbT>,
+    },
+    Signal {
+        pattern: String,
+        callback: Callback<SignalCbT>,
+    },
+    Timer(Timer),
+    Fd {
+        fd: c_int,
+        events: c_short,
+        callback: Callback<FdCbT>,
+    },
+    // The process isn't run, the hook only keeps its callback.
+    Process {
+        _callback: Callback<ProcessCbT>,
+    },
+}
+
+struct FakeConfig {
+    name: String,
+    _reload_callback: Option<Callback<ReloadCbT>>,
+}
+
+struct FakeSection {
+    config: usize,
+    name: String,
+}
+
+struct FakeOption {
+    section: usize,
+    full_name: String,
+    option_type: String,
+    // Strings returned by config_option_get_string().
+    properties: HashMap<&'static str, CString>,
+    string_values: Vec<String>,
+    min: c_int,
+    max: c_int,
+    default_value: Option<String>,
+    value: Option<CString>,
+    null_allowed: bool,
+    check_callback: Option<Callback<CheckCbT>>,
+    change_callback: Option<Callback<ChangeCbT>>,
+}
+
+impl FakeOption {
+    fn value(&self) -> Option<&str> {
+        self.value.as_ref().and_then(|value| value.to_str().ok())
+    }
+
+    /// Convert a value that should be set to the normalized value of the
+    /// option, returns None if the value isn't valid for the option.
+    fn parse_value(&self, value: &str) -> Option<String> {
+        match self.option_type.as_ref() {
+            "boolean" => match value.to_lowercase().as_ref() {
+                "on" | "yes" | "y" | "true" | "t" | "1" => {
+                    Some("on".to_owned())
+                }
+                "off" | "no" | "n" | "false" | "f" | "0" => {
+                    Some("off".to_owned())
+                }
+                "toggle" => match self.value() {
+                    Some("on") => Some("off".to_owned()),
+                    _ => Some("on".to_owned()),
+                },
+                _ => None,
+            },
+            "integer" if !self.string_values.is_empty() => self
+                .string_values
+                .iter()
+                .find(|string_value| *string_value == value)
+                .cloned(),
+            "integer" => {
+                let current = self.integer();
+
+                let value = if let Some(value) = value.strip_prefix("++") {
+                    current.checked_add(value.parse().ok()?)?
+                } else if let Some(value) = value.strip_prefix("--") {
+                    current.checked_sub(value.parse().ok()?)?
+                } else {
+                    value.parse().ok()?
+                };
+
+                if value < self.min || value > self.max {
+                    None
+                } else {
+                    Some(value.to_string())
+                }
+            }
+            _ => Some(value.to_owned()),
+        }
+    }
+
+    fn integer(&self) -> c_int {
+        let value = self.value().unwrap_or("");
+
+        if self.string_values.is_empty() {
+            value.parse().unwrap_or(0)
+        } else {
+            self.string_values
+                .iter()
+                .position(|string_value| string_value == value)
+                .unwrap_or(0) as c_int
+        }
+    }
+}
+
+struct FakeBarItem {
+    name: String,
+    callback: Callback<BarItemCbT>,
+}
+
+struct FakeBar {
+    // Strings returned by hdata_string().
+    properties: HashMap<String, CString>,
+}
+
+impl FakeBar {
+    fn name(&self) -> &str {
+        self.properties
+            .get("name")
+            .and_then(|name| name.to_str().ok())
+            .unwrap_or("")
+    }
+}
+
+/// A key or value of a hashtable. The data is boxed so the pointers that are
+/// handed out stay valid while other items are added.
+#[derive(PartialEq)]
+enum FakeValue {
+    Integer(Box<c_int>),
+    String(CString),
+    Pointer(usize),
+    Buffer(Box<[u8]>),
+    Time(Box<time_t>),
+}
+
+impl FakeValue {
+    /// Copy a key or value out of the pointer the plugin passed, returns
+    /// None if the pointer is null or the type is unknown.
+    unsafe fn from_raw(
+        value_type: &str,
+        data: *const c_void,
+        size: c_int,
+    ) -> Option<FakeValue> {
+        if data.is_null() && value_type != "pointer" {
+            return None;
+        }
+
+        let value = match value_type {
+            "integer" => FakeValue::Integer(Box::new(*(data as *const c_int))),
+            "string" => FakeValue::String(
+                CStr::from_ptr(data as *const c_char).to_owned(),
+            ),
+            "pointer" => FakeValue::Pointer(data as usize),
+            "buffer" => FakeValue::Buffer(
+                slice::from_raw_parts(data as *const u8, size.max(0) as usize)
+                    .into(),
+            ),
+            "time" => FakeValue::Time(Box::new(*(data as *const time_t))),
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    fn as_ptr(&self) -> *mut c_void {
+        match self {
+            FakeValue::Integer(integer) => {
+                &**integer as *const c_int as *mut c_void
+            }
+            FakeValue::String(string) => string.as_ptr() as *mut c_void,
+            FakeValue::Pointer(pointer) => *pointer as *mut c_void,
+            FakeValue::Buffer(data) => data.as_ptr() as *mut c_void,
+            FakeValue::Time(time) => &**time as *const time_t as *mut c_void,
+        }
+    }
+
+    /// Convert the value to a string the way Weechat does it for scripts.
+    fn display_string(&self) -> String {
+        match self {
+            FakeValue::Integer(integer) => integer.to_string(),
+            FakeValue::String(string) => string.to_string_lossy().into_owned(),
+            FakeValue::Pointer(pointer) => format!("{:#x}", pointer),
+            FakeValue::Buffer(data) => format!("{:#x}", data.as_ptr() as usize),
+            FakeValue::Time(time) => time.to_string(),
+        }
+    }
+}
+
+struct FakeHashtable {
+    size: c_int,
+    key_type: CString,
+    value_type: CString,
+    // Kept in insertion order, hashtables are small in tests.
+    items: Vec<(FakeValue, FakeValue)>,
+}
+
+impl FakeHashtable {
+    fn key_type(&self) -> &str {
+        self.key_type.to_str().unwrap_or("")
+    }
+
+    fn value_type(&self) -> &str {
+        self.value_type.to_str().unwrap_or("")
+    }
+
+    fn position(&self, key: &FakeValue) -> Option<usize> {
+        self.items.iter().position(|(item_key, _)| item_key == key)
+    }
+}
+
+struct State {
+    next_id: usize,
+    now: Duration,
+    core_buffer: usize,
+    current_buffer: usize,
+    buffers: BTreeMap<usize, FakeBuffer>,
+    groups: BTreeMap<usize, FakeGroup>,
+    nicks: BTreeMap<usize, FakeNick>,
+    hooks: BTreeMap<usize, FakeHook>,
+    configs: BTreeMap<usize, FakeConfig>,
+    sections: BTreeMap<usize, FakeSection>,
+    options: BTreeMap<usize, FakeOption>,
+    plugin_options: HashMap<String, CString>,
+    bar_items: BTreeMap<usize, FakeBarItem>,
+    bars: BTreeMap<usize, FakeBar>,
+    // Only the hdata of bars is emulated.
+    bar_hdata: usize,
+    hashtables: BTreeMap<usize, FakeHashtable>,
+    log: Vec<String>,
+}
+
+impl State {
+    fn new() -> State {
+        let mut state = State {
+            next_id: 0,
+            now: Duration::from_secs(0),
+            core_buffer: 0,
+            current_buffer: 0,
+            buffers: BTreeMap::new(),
+            groups: BTreeMap::new(),
+            nicks: BTreeMap::new(),
+            hooks: BTreeMap::new(),
+            configs: BTreeMap::new(),
+            sections: BTreeMap::new(),
+            options: BTreeMap::new(),
+            plugin_options: HashMap::new(),
+            bar_items: BTreeMap::new(),
+            bars: BTreeMap::new(),
+            bar_hdata: 0,
+            hashtables: BTreeMap::new(),
+            log: Vec::new(),
+        };
+
+        let core_buffer = state.new_id();
+        state
+            .buffers
+            .insert(core_buffer, FakeBuffer::new("core", "weechat"));
+        state.core_buffer = core_buffer;
+        state.current_buffer = core_buffer;
+        state.bar_hdata = state.new_id();
+
+        state
+    }
+
+    /// Allocate a new id, the ids are handed out to the plugin as pointers
+    /// to the objects we emulate.
+    fn new_id(&mut self) -> usize {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn find_buffer(
+        &self,
+        predicate: impl Fn(&FakeBuffer) -> bool,
+    ) -> Option<usize> {
+        self.buffers
+            .iter()
+            .find(|(_, buffer)| predicate(buffer))
+            .map(|(id, _)| *id)
+    }
+
+    fn buffer_id(&self, buffer: *mut t_gui_buffer) -> usize {
+        if buffer.is_null() {
+            self.core_buffer
+        } else {
+            to_id(buffer)
+        }
+    }
+}
+
+/// Run a function with the state of the fake instance.
+///
+/// This panics if there is no fake instance, it must only be used outside of
+/// the emulated API functions, see `api::try_with_state()`.
+fn with_state<R>(f: impl FnOnce(&mut State) -> R) -> R {
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        let state = state
+            .as_mut()
+            .expect("The Weechat API was used without a TestWeechat");
+        f(state)
+    })
+}
+
+/// Returns true if the plugin pointer belongs to the fake instance of the
+/// current thread.
+fn is_fake(ptr: *mut t_weechat_plugin) -> bool {
+    !ptr.is_null() && PLUGIN.with(|plugin| plugin.get()) == ptr
+}
+
+/// Print a message if the plugin is the fake instance, the crate calls this
+/// before it calls `printf_date_tags()` or `log_printf()`.
+///
+/// Stable Rust can't define C-variadic functions, so the fake API can't put
+/// its own variadic functions into the plugin struct.
+/// * `buffer` - The buffer the message is printed on, None if the message
+///     is written to the log.
+///
+/// Returns true if the fake API printed the message.
+pub(crate) fn print_variadic(
+    ptr: *mut t_weechat_plugin,
+    buffer: Option<*mut t_gui_buffer>,
+    message: &str,
+) -> bool {
+    if !is_fake(ptr) {
+        return false;
+    }
+
+    match buffer {
+        Some(buffer) => api::printf_date_tags(buffer, message),
+        None => api::log_printf(message),
+    }
+
+    true
+}
+
+/// Record a failure of the emulated API, see `check_failures()`.
+fn record_failure(failure: String) {
+    let _ = FAILURES.try_with(|failures| failures.borrow_mut().push(failure));
+}
+
+fn take_failures() -> Vec<String> {
+    FAILURES.with(|failures| failures.borrow_mut().drain(..).collect())
+}
+
+/// Panic with the failures that the emulated API recorded so far.
+fn check_failures() {
+    let failures = take_failures();
+
+    if !failures.is_empty() {
+        panic!("The fake Weechat API failed:\n{}", failures.join("\n"));
+    }
+}
+
+fn to_ptr<T>(id: usize) -> *mut T {
+    id as *mut T
+}
+
+fn to_id<T>(ptr: *const T) -> usize {
+    ptr as usize
+}
+
+unsafe fn to_string(string: *const c_char) -> String {
+    if string.is_null() {
+        String::new()
+    } else {
+        CStr::from_ptr(string).to_string_lossy().into_owned()
+    }
+}
+
+/// Match a string against a pattern that may contain `*` wildcards, the
+/// match is case insensitive like in Weechat.
+fn matches(pattern: &str, string: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let string = string.to_lowercase();
+
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+
+    let mut rest = match string.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+
+    let parts: Vec<&str> = parts.collect();
+
+    match parts.split_last() {
+        None => rest.is_empty(),
+        Some((last, middle)) => {
+            for part in middle {
+                match rest.find(part) {
+                    Some(position) => rest = &rest[position + part.len()..],
+                    None => return false,
+                }
+            }
+
+            rest.ends_with(last)
+        }
+    }
+}
+
+/// A fake Weechat instance that plugins can be tested with.
+///
+/// Only one `TestWeechat` can exist per thread, `Weechat::instance()`
+/// returns the fake instance while it exists. Every object that was created
+/// using the Weechat API needs to be dropped before the `TestWeechat`.
+pub struct TestWeechat {
+    ptr: *mut t_weechat_plugin,
+    _name: CString,
+}
+
+impl TestWeechat {
+    /// Create a new fake Weechat instance on the current thread.
+    ///
+    /// # Panics
+    ///
+    /// Panics if another `TestWeechat` exists on the current thread.
+    pub fn new() -> TestWeechat {
+        let name = LossyCString::new("test");
+
+        // All the fields of the plugin struct are pointers or integers, the
+        // API functions are filled out by install().
+        let mut plugin: Box<t_weechat_plugin> =
+            Box::new(unsafe { std::mem::zeroed() });
+        plugin.name = name.as_ptr() as *mut c_char;
+        api::install(&mut plugin);
+
+        STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            assert!(
+                state.is_none(),
+                "Only one TestWeechat can exist on a thread"
+            );
+            *state = Some(State::new());
+        });
+
+        // Failures that were left over by a TestWeechat that was dropped
+        // during a panic.
+        take_failures();
+
+        let ptr = Box::into_raw(plugin);
+        PLUGIN.with(|plugin| plugin.set(ptr));
+        unsafe { Weechat::init_from_ptr(ptr) };
+
+        TestWeechat { ptr, _name: name }
+    }
+
+    /// Get the Weechat object of the fake instance.
+    pub fn weechat(&self) -> Weechat {
+        check_failures();
+
+        Weechat::from_ptr(self.ptr)
+    }
+
+    /// Initialize a plugin like Weechat does when it loads the plugin.
+    ///
+    /// The plugin struct is stored and can be accessed with
+    /// `Weechat::with_plugin()`, it's dropped with the `TestWeechat`.
+    /// * `args` - The arguments that are passed to the init function of the
+    ///     plugin.
+    pub fn load_plugin<P: WeechatPlugin + 'static>(
+        &self,
+        args: &[&str],
+    ) -> WeechatResult<()> {
+        check_failures();
+
+        let args: Vec<CString> = args.iter().map(LossyCString::new).collect();
+        let mut argv: Vec<*mut c_char> =
+            args.iter().map(|arg| arg.as_ptr() as *mut c_char).collect();
+
+        let args = ArgsWeechat::new(argv.len() as c_int, argv.as_mut_ptr());
+        let plugin = P::init(self.weechat(), args)?;
+
+        Weechat::set_plugin(plugin);
+
+        Ok(())
+    }
+
+    /// Get the core Weechat buffer.
+    pub fn core_buffer(&self) -> Buffer {
+        check_failures();
+
+        let id = with_state(|state| state.core_buffer);
+        Buffer::from_ptr(self.ptr, to_ptr(id))
+    }
+
+    /// Get the lines that were printed on a buffer, prefixes are separated
+    /// from the message with a tab like they are passed to Weechat.
+    pub fn printed_lines(&self, buffer: &Buffer) -> Vec<String> {
+        check_failures();
+
+        with_state(|state| {
+            let id = state.buffer_id(buffer.ptr);
+            state
+                .buffers
+                .get(&id)
+                .map(|buffer| buffer.lines.clone())
+                .unwrap_or_default()
+        })
+    }
+
+    /// Get the lines that were written to the Weechat log file.
+    pub fn log_lines(&self) -> Vec<String> {
+        check_failures();
+
+        with_state(|state| state.log.clone())
+    }
+
+    /// Run a command on a buffer like Weechat does when the user enters it.
+    ///
+    /// Command run hooks see the command first, if one of them eats the
+    /// command `ReturnCode::OkEat` is returned. Returns `ReturnCode::Error`
+    /// if no hook for the command exists.
+    /// * `buffer` - The buffer the command is run on.
+    /// * `command` - The command including its arguments, e.g.
+    ///     "/mycommand arg1 arg2".
+    pub fn run_command(&self, buffer: &Buffer, command: &str) -> ReturnCode {
+        check_failures();
+
+        let run_hooks: Vec<Callback<CommandRunCbT>> = with_state(|state| {
+            state
+                .hooks
+                .values()
+                .filter_map(|hook| match hook {
+                    FakeHook::CommandRun { pattern, callback }
+                        if matches(pattern, command) =>
+                    {
+                        Some(*callback)
+                    }
+                    _ => None,
+                })
+                .collect()
+        });
+
+        let c_command = LossyCString::new(command);
+
+        for callback in run_hooks {
+            let ret = unsafe {
+                (callback.function)(
+                    callback.pointer,
+                    callback.data,
+                    buffer.ptr,
+                    c_command.as_ptr(),
+                )
+            };
+
+            if ret == WEECHAT_RC_OK_EAT {
+                return ReturnCode::OkEat;
+            }
+        }
+
+        // Split the command into words, remembering where each word starts
+        // so the arguments up to the end of line can be created.
+        let mut words = Vec::new();
+        let mut start = None;
+
+        for (i, c) in command.char_indices() {
+            if c == ' ' {
+                if let Some(start) = start.take() {
+                    words.push((start, &command[start..i]));
+                }
+            } else if start.is_none() {
+                start = Some(i);
+            }
+        }
+
+        if let Some(start) = start {
+            words.push((start, &command[start..]));
+        }
+
+        let name = match words.first() {
+            Some((_, word)) if word.starts_with('/') => word[1..].to_owned(),
+            _ => return ReturnCode::Error,
+        };
+
+        let callback = with_state(|state| {
+            state.hooks.values().find_map(|hook| match hook {
+                FakeHook::Command {
+                    name: hook_name,
+                    callback,
+                } if *hook_name == name => Some(*callback),
+                _ => None,
+            })
+        });
+
+        let callback = match callback {
+            Some(callback) => callback,
+            None => return ReturnCode::Error,
+        };
+
+        let argv: Vec<CString> = words
+            .iter()
+            .map(|(_, word)| LossyCString::new(word))
+            .collect();
+        let argv_eol: Vec<CString> = words
+            .iter()
+            .map(|(start, _)| LossyCString::new(&command[*start..]))
+            .collect();
+
+        let mut argv_ptrs: Vec<*mut c_char> = argv
+            .iter()
+            .map(|arg| arg.as_ptr() as *mut c_char)
+            .chain(Some(ptr::null_mut()))
+            .collect();
+        let mut argv_eol_ptrs: Vec<*mut c_char> = argv_eol
+            .iter()
+            .map(|arg| arg.as_ptr() as *mut c_char)
+            .chain(Some(ptr::null_mut()))
+            .collect();
+
+        let ret = unsafe {
+            (callback.function)(
+                callback.pointer,
+                callback.data,
+                buffer.ptr,
+                argv.len() as c_int,
+                argv_ptrs.as_mut_ptr(),
+                argv_eol_ptrs.as_mut_ptr(),
+            )
+        };
+
+        ReturnCode::from_int(ret)
+    }
+
+    /// Enter text into the input of a buffer like the user does.
+    ///
+    /// Text that starts with a `/` is run as a command, see
+    /// `TestWeechat::run_command()`, otherwise the text is passed to the
+    /// input callback of the buffer.
+    /// * `buffer` - The buffer the text is entered on.
+    /// * `input` - The text that is entered.
+    pub fn input(&self, buffer: &Buffer, input: &str) -> ReturnCode {
+        check_failures();
+
+        if input.starts_with('/') && !input.starts_with("//") {
+            return self.run_command(buffer, input);
+        }
+
+        let callback = with_state(|state| {
+            let id = state.buffer_id(buffer.ptr);
+            state.buffers.get(&id).and_then(|b| b.input_callback)
+        });
+
+        match callback {
+            Some(callback) => {
+                let input = LossyCString::new(input);
+
+                let ret = unsafe {
+                    (callback.function)(
+                        callback.pointer,
+                        callback.data,
+                        buffer.ptr,
+                        input.as_ptr(),
+                    )
+                };
+
+                ReturnCode::from_int(ret)
+            }
+            None => ReturnCode::Ok,
+        }
+    }
+
+    /// Close a buffer like the user does, the close callback of the buffer
+    /// is called before the buffer is removed. The core buffer can't be
+    /// closed.
+    /// * `buffer` - The buffer that should be closed.
+    pub fn close_buffer(&self, buffer: Buffer) {
+        check_failures();
+
+        let id = with_state(|state| state.buffer_id(buffer.ptr));
+        self.close(id);
+    }
+
+    fn close(&self, id: usize) {
+        let callback = with_state(|state| {
+            if id == state.core_buffer {
+                return None;
+            }
+
+            state.buffers.get(&id).map(|b| b.close_callback)
+        });
+
+        let callback = match callback {
+            Some(callback) => callback,
+            None => return,
+        };
+
+        if let Some(callback) = callback {
+            unsafe {
+                (callback.function)(callback.pointer, callback.data, to_ptr(id))
+            };
+        }
+
+        with_state(|state| {
+            state.buffers.remove(&id);
+            state.groups.retain(|_, group| group.buffer != id);
+            state.nicks.retain(|_, nick| nick.buffer != id);
+
+            if state.current_buffer == id {
+                state.current_buffer = state.core_buffer;
+            }
+        });
+    }
+
+    /// Send a signal to the signal hooks, see `Weechat::signal_send()`.
+    /// * `signal` - The name of the signal.
+    /// * `value` - The data that is sent with the signal.
+    pub fn send_signal(
+        &self,
+        signal: &str,
+        value: SignalHookValue,
+    ) -> ReturnCode {
+        check_failures();
+
+        self.weechat().signal_send(signal, value)
+    }
+
+    /// Advance the fake clock of the timer hooks, every timer that is due
+    /// until then is called in order.
+    /// * `duration` - The duration the clock advances.
+    pub fn advance_time(&self, duration: Duration) {
+        check_failures();
+
+        let end = with_state(|state| state.now + duration);
+
+        loop {
+            let next = with_state(|state| {
+                let timer = state
+                    .hooks
+                    .values_mut()
+                    .filter_map(|hook| match hook {
+                        FakeHook::Timer(timer)
+                            if timer.remaining_calls != Some(0)
+                                && timer.next_call <= end =>
+                        {
+                            Some(timer)
+                        }
+                        _ => None,
+                    })
+                    .min_by_key(|timer| timer.next_call)?;
+
+                state.now = timer.next_call;
+                timer.next_call += timer.interval;
+
+                let remaining_calls = match timer.remaining_calls.as_mut() {
+                    Some(remaining_calls) => {
+                        *remaining_calls -= 1;
+                        *remaining_calls
+                    }
+                    None => -1,
+                };
+
+                Some((timer.callback, remaining_calls))
+            });
+
+            match next {
+                Some((callback, remaining_calls)) => unsafe {
+                    (callback.function)(
+                        callback.pointer,
+                        callback.data,
+                        remaining_calls,
+                    );
+                },
+                None => break,
+            }
+        }
+
+        with_state(|state| state.now = end);
+    }
+
+    /// Call the hooks of a file descriptor, whether the file descriptor is
+    /// ready or not.
+    ///
+    /// Returns the return code of the last hook that was called.
+    /// * `fd` - The file descriptor whose hooks should be called.
+    pub fn fire_fd(&self, fd: RawFd) -> ReturnCode {
+        check_failures();
+
+        let hooks: Vec<usize> = with_state(|state| {
+            state
+                .hooks
+                .iter()
+                .filter_map(|(id, hook)| match hook {
+                    FakeHook::Fd { fd: hook_fd, .. } if *hook_fd == fd => {
+                        Some(*id)
+                    }
+                    _ => None,
+                })
+                .collect()
+        });
+
+        let mut ret = ReturnCode::Ok;
+
+        for id in hooks {
+            if let Some(rc) = self.fire_fd_hook(id) {
+                ret = ReturnCode::from_int(rc);
+            }
+        }
+
+        ret
+    }
+
+    /// Call the hooks of all the file descriptors that are ready, like the
+    /// Weechat main loop does.
+    ///
+    /// Returns the number of hooks that were called. Tasks spawned with
+    /// `Weechat::spawn()` and messages of a `MainThreadHook` are handled
+    /// this way, call this method until it returns 0 to run them until they
+    /// are idle.
+    pub fn run_ready_fds(&self) -> usize {
+        check_failures();
+
+        let (ids, mut fds): (Vec<usize>, Vec<libc::pollfd>) =
+            with_state(|state| {
+                state
+                    .hooks
+                    .iter()
+                    .filter_map(|(id, hook)| match hook {
+                        FakeHook::Fd { fd, events, .. } => Some((
+                            *id,
+                            libc::pollfd {
+                                fd: *fd,
+                                events: *events,
+                                revents: 0,
+                            },
+                        )),
+                        _ => None,
+                    })
+                    .unzip()
+            });
+
+        if fds.is_empty() {
+            return 0;
+        }
+
+        let ret = unsafe {
+            libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, 0)
+        };
+
+        if ret <= 0 {
+            return 0;
+        }
+
+        ids.into_iter()
+            .zip(fds)
+            .filter(|(_, fd)| fd.revents != 0)
+            .filter_map(|(id, _)| self.fire_fd_hook(id))
+            .count()
+    }
+
+    fn fire_fd_hook(&self, id: usize) -> Option<c_int> {
+        let (fd, callback) = with_state(|state| match state.hooks.get(&id) {
+            Some(FakeHook::Fd { fd, callback, .. }) => Some((*fd, *callback)),
+            _ => None,
+        })?;
+
+        Some(unsafe {
+            (callback.function)(callback.pointer, callback.data, fd)
+        })
+    }
+
+    /// Get the value of a configuration option.
+    ///
+    /// Returns None if the option doesn't exist or if its value is null.
+    /// * `option_name` - The full name of the option, e.g.
+    ///     "myplugin.section.option".
+    pub fn option_value(&self, option_name: &str) -> Option<String> {
+        check_failures();
+
+        with_state(|state| {
+            state
+                .options
+                .values()
+                .find(|option| option.full_name == option_name)?
+                .value()
+                .map(str::to_owned)
+        })
+    }
+
+    /// Get a property of a bar, e.g. its "position" or "items".
+    ///
+    /// Returns None if no bar with the given name exists.
+    /// * `name` - The name of the bar.
+    /// * `property` - The name of the property, see `BarBuilder` for the
+    ///     properties of a bar.
+    pub fn bar_property(&self, name: &str, property: &str) -> Option<String> {
+        check_failures();
+
+        with_state(|state| {
+            state
+                .bars
+                .values()
+                .find(|bar| bar.name() == name)?
+                .properties
+                .get(property)
+                .map(|value| value.to_string_lossy().into_owned())
+        })
+    }
+
+    /// Build the content of a bar item by calling its callback, the current
+    /// buffer is passed to the callback.
+    ///
+    /// Returns None if no bar item with the given name exists.
+    /// * `name` - The name of the bar item.
+    pub fn bar_item_content(&self, name: &str) -> Option<String> {
+        check_failures();
+
+        let (id, callback, buffer) = with_state(|state| {
+            let (id, item) =
+                state.bar_items.iter().find(|(_, item)| item.name == name)?;
+            Some((*id, item.callback, state.current_buffer))
+        })?;
+
+        unsafe {
+            let content = (callback.function)(
+                callback.pointer,
+                callback.data,
+                to_ptr(id),
+                ptr::null_mut(),
+                to_ptr(buffer),
+                ptr::null_mut(),
+            );
+
+            if content.is_null() {
+                return Some(String::new());
+            }
+
+            let string = CStr::from_ptr(content).to_string_lossy().into_owned();
+            libc::free(content as *mut c_void);
+
+            Some(string)
+        }
+    }
+}
+
+impl Default for TestWeechat {
+    fn default() -> Self {
+        TestWeechat::new()
+    }
+}
+
+impl Drop for TestWeechat {
+    fn drop(&mut self) {
+        crate::plugin::free_plugin();
+
+        // Weechat closes the buffers of a plugin once the plugin is
+        // unloaded, this frees the data of the buffer callbacks. The close
+        // callbacks may still use the Weechat instance, so it's freed last.
+        let buffers: Vec<usize> =
+            with_state(|state| state.buffers.keys().copied().collect());
+
+        for id in buffers {
+            self.close(id);
+        }
+
+        Weechat::free();
+
+        STATE.with(|state| state.borrow_mut().take());
+        PLUGIN.with(|plugin| plugin.set(ptr::null_mut()));
+
+        unsafe { drop(Box::from_raw(self.ptr)) };
+
+        if !thread::panicking() {
+            check_failures();
+        }
+    }
+}
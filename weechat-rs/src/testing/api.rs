@@ -0,0 +1,1873 @@
+//! The emulated functions of the Weechat plugin API.
+
+use libc::{c_char, c_int, c_long, c_short, c_ulonglong};
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::os::raw::c_void;
+use std::ptr;
+use std::time::Duration;
+
+use weechat_sys::{
+    t_config_file, t_config_option, t_config_section, t_gui_bar,
+    t_gui_bar_item, t_gui_buffer, t_gui_nick, t_gui_nick_group, t_hashtable,
+    t_hashtable_item, t_hdata, t_hook, t_weechat_plugin,
+    WEECHAT_CONFIG_OPTION_SET_ERROR, WEECHAT_CONFIG_OPTION_SET_OK_CHANGED,
+    WEECHAT_CONFIG_OPTION_SET_OK_SAME_VALUE,
+    WEECHAT_CONFIG_OPTION_SET_OPTION_NOT_FOUND,
+    WEECHAT_CONFIG_OPTION_UNSET_ERROR, WEECHAT_CONFIG_OPTION_UNSET_OK_NO_RESET,
+    WEECHAT_CONFIG_OPTION_UNSET_OK_RESET, WEECHAT_RC_OK, WEECHAT_RC_OK_EAT,
+};
+
+use super::{
+    matches, record_failure, to_id, to_ptr, to_string, BarItemCbT, Callback,
+    ChangeCbT, CheckCbT, CloseCbT, CommandCbT, CommandRunCbT, FakeBar,
+    FakeBarItem, FakeBuffer, FakeConfig, FakeGroup, FakeHashtable, FakeHook,
+    FakeNick, FakeOption, FakeSection, FakeValue, FdCbT, InputCbT, ProcessCbT,
+    ReloadCbT, SignalCbT, State, Timer, TimerCbT, STATE,
+};
+use crate::LossyCString;
+
+type HashKeyCbT =
+    unsafe extern "C" fn(*mut t_hashtable, *const c_void) -> c_ulonglong;
+type KeyCmpCbT = unsafe extern "C" fn(
+    *mut t_hashtable,
+    *const c_void,
+    *const c_void,
+) -> c_int;
+type MapCbT = unsafe extern "C" fn(
+    *mut c_void,
+    *mut t_hashtable,
+    *const c_void,
+    *const c_void,
+);
+type MapStringCbT = unsafe extern "C" fn(
+    *mut c_void,
+    *mut t_hashtable,
+    *const c_char,
+    *const c_char,
+);
+
+/// The value an API function returns if it fails.
+trait Fallback {
+    fn fallback() -> Self;
+}
+
+macro_rules! zero_fallback {
+    ($($type:ty),*) => {
+        $(
+            impl Fallback for $type {
+                fn fallback() -> Self {
+                    0
+                }
+            }
+        )*
+    };
+}
+
+zero_fallback!(i8, u8, i32, u32, i64, u64, usize);
+
+impl Fallback for () {
+    fn fallback() -> Self {}
+}
+
+impl Fallback for bool {
+    fn fallback() -> Self {
+        false
+    }
+}
+
+impl<T> Fallback for *mut T {
+    fn fallback() -> Self {
+        ptr::null_mut()
+    }
+}
+
+// Constant pointers are only returned for strings, an empty string is
+// returned so the string doesn't need to be checked for null.
+impl<T> Fallback for *const T {
+    fn fallback() -> Self {
+        b"\0".as_ptr() as *const T
+    }
+}
+
+impl<T> Fallback for Option<T> {
+    fn fallback() -> Self {
+        None
+    }
+}
+
+impl<T> Fallback for Vec<T> {
+    fn fallback() -> Self {
+        Vec::new()
+    }
+}
+
+impl<T, E: Fallback> Fallback for Result<T, E> {
+    fn fallback() -> Self {
+        Err(E::fallback())
+    }
+}
+
+impl Fallback for Duration {
+    fn fallback() -> Self {
+        Duration::from_secs(0)
+    }
+}
+
+/// Run a function with the state of the fake instance.
+///
+/// The API functions can't panic, if there is no fake instance or its state
+/// is already borrowed the failure is recorded and the fallback value is
+/// returned.
+fn try_with_state<R: Fallback>(f: impl FnOnce(&mut State) -> R) -> R {
+    let result = STATE.with(|state| match state.try_borrow_mut() {
+        Ok(mut state) => match state.as_mut() {
+            Some(state) => Ok(f(state)),
+            None => Err("The Weechat API was used without a TestWeechat"),
+        },
+        Err(_) => Err("The Weechat API was used while its state is borrowed"),
+    });
+
+    result.unwrap_or_else(|failure| {
+        record_failure(failure.to_owned());
+        R::fallback()
+    })
+}
+
+/// The name of an API function that isn't emulated.
+trait FunctionName {
+    const NAME: &'static str;
+}
+
+/// API functions that aren't emulated are filled out with a stub that
+/// records a failure and returns the fallback value.
+trait Unemulated {
+    fn stub<N: FunctionName>() -> Self;
+}
+
+macro_rules! unemulated_stub {
+    ($($arg:ident),*) => {
+        impl<Ret: Fallback, $($arg),*> Unemulated
+            for unsafe extern "C" fn($($arg),*) -> Ret
+        {
+            fn stub<Func: FunctionName>() -> Self {
+                unsafe extern "C" fn c_stub<
+                    Func: FunctionName,
+                    Ret: Fallback,
+                    $($arg),*
+                >(
+                    $(_: $arg),*
+                ) -> Ret {
+                    record_failure(format!(
+                        "The Weechat API function {} isn't emulated",
+                        Func::NAME
+                    ));
+                    Ret::fallback()
+                }
+
+                c_stub::<Func, Ret, $($arg),*>
+            }
+        }
+    };
+}
+
+unemulated_stub!();
+unemulated_stub!(A);
+unemulated_stub!(A, B);
+unemulated_stub!(A, B, C);
+unemulated_stub!(A, B, C, D);
+unemulated_stub!(A, B, C, D, E);
+unemulated_stub!(A, B, C, D, E, F);
+unemulated_stub!(A, B, C, D, E, F, G);
+unemulated_stub!(A, B, C, D, E, F, G, H);
+unemulated_stub!(A, B, C, D, E, F, G, H, I);
+unemulated_stub!(A, B, C, D, E, F, G, H, I, J);
+unemulated_stub!(A, B, C, D, E, F, G, H, I, J, K);
+unemulated_stub!(A, B, C, D, E, F, G, H, I, J, K, L);
+unemulated_stub!(A, B, C, D, E, F, G, H, I, J, K, L, M);
+unemulated_stub!(A, B, C, D, E, F, G, H, I, J, K, L, M, N);
+unemulated_stub!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O);
+unemulated_stub!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
+unemulated_stub!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q);
+
+macro_rules! unemulated {
+    ($plugin:ident, $($function:ident),* $(,)?) => {
+        $({
+            #[allow(non_camel_case_types)]
+            struct $function;
+
+            impl FunctionName for $function {
+                const NAME: &'static str = stringify!($function);
+            }
+
+            $plugin.$function = Some(Unemulated::stub::<$function>());
+        })*
+    };
+}
+
+/// Fill out the API functions of the plugin struct with the emulated ones.
+///
+/// The variadic functions can't be emulated, the crate calls
+/// `printf_date_tags()` and `log_printf()` of this module through
+/// `print_variadic()` instead.
+pub(super) fn install(plugin: &mut t_weechat_plugin) {
+    plugin.prefix = Some(prefix);
+    plugin.color = Some(color);
+
+    plugin.buffer_new = Some(buffer_new);
+    plugin.buffer_search = Some(buffer_search);
+    plugin.buffer_get_string = Some(buffer_get_string);
+    plugin.buffer_set = Some(buffer_set);
+    plugin.buffer_clear = Some(buffer_clear);
+
+    plugin.nicklist_add_group = Some(nicklist_add_group);
+    plugin.nicklist_search_group = Some(nicklist_search_group);
+    plugin.nicklist_add_nick = Some(nicklist_add_nick);
+    plugin.nicklist_search_nick = Some(nicklist_search_nick);
+    plugin.nicklist_nick_get_string = Some(nicklist_nick_get_string);
+    plugin.nicklist_remove_nick = Some(nicklist_remove_nick);
+
+    plugin.hook_command = Some(hook_command);
+    plugin.hook_command_run = Some(hook_command_run);
+    plugin.hook_signal = Some(hook_signal);
+    plugin.hook_signal_send = Some(hook_signal_send);
+    plugin.hook_timer = Some(hook_timer);
+    plugin.hook_fd = Some(hook_fd);
+    plugin.hook_process_hashtable = Some(hook_process_hashtable);
+    plugin.hook_set = Some(hook_set);
+    plugin.unhook = Some(unhook);
+
+    plugin.config_new = Some(config_new);
+    plugin.config_new_section = Some(config_new_section);
+    plugin.config_new_option = Some(config_new_option);
+    plugin.config_free = Some(config_free);
+    plugin.config_section_free = Some(config_section_free);
+    plugin.config_section_free_options = Some(config_section_free_options);
+    plugin.config_get = Some(config_get);
+    plugin.config_string = Some(config_string);
+    plugin.config_boolean = Some(config_boolean);
+    plugin.config_integer = Some(config_integer);
+    plugin.config_color = Some(config_string);
+    plugin.config_option_get_string = Some(config_option_get_string);
+    plugin.config_option_set = Some(config_option_set);
+    plugin.config_option_reset = Some(config_option_reset);
+    plugin.config_option_set_null = Some(config_option_set_null);
+    plugin.config_option_unset = Some(config_option_unset);
+    plugin.config_option_is_null = Some(config_option_is_null);
+    plugin.config_option_default_is_null = Some(config_option_default_is_null);
+    plugin.config_get_plugin = Some(config_get_plugin);
+    plugin.config_set_plugin = Some(config_set_plugin);
+
+    plugin.bar_item_search = Some(bar_item_search);
+    plugin.bar_item_new = Some(bar_item_new);
+    plugin.bar_item_update = Some(bar_item_update);
+    plugin.bar_item_remove = Some(bar_item_remove);
+
+    plugin.bar_search = Some(bar_search);
+    plugin.bar_new = Some(bar_new);
+    plugin.bar_set = Some(bar_set);
+    plugin.bar_update = Some(bar_update);
+    plugin.bar_remove = Some(bar_remove);
+
+    plugin.hdata_get = Some(hdata_get);
+    plugin.hdata_get_list = Some(hdata_get_list);
+    plugin.hdata_check_pointer = Some(hdata_check_pointer);
+    plugin.hdata_string = Some(hdata_string);
+
+    plugin.hashtable_new = Some(hashtable_new);
+    plugin.hashtable_set_with_size = Some(hashtable_set_with_size);
+    plugin.hashtable_set = Some(hashtable_set);
+    plugin.hashtable_get = Some(hashtable_get);
+    plugin.hashtable_has_key = Some(hashtable_has_key);
+    plugin.hashtable_map = Some(hashtable_map);
+    plugin.hashtable_map_string = Some(hashtable_map_string);
+    plugin.hashtable_get_integer = Some(hashtable_get_integer);
+    plugin.hashtable_get_string = Some(hashtable_get_string);
+    plugin.hashtable_remove = Some(hashtable_remove);
+    plugin.hashtable_remove_all = Some(hashtable_remove_all);
+    plugin.hashtable_free = Some(hashtable_free);
+
+    unemulated!(
+        plugin,
+        string_eval_expression,
+        hook_print,
+        hook_line,
+        hook_modifier,
+        hook_modifier_exec,
+        hook_hsignal,
+        hook_hsignal_send,
+        hook_config,
+        hook_completion,
+        hook_completion_list_add,
+        hook_connect,
+        hook_info,
+        hook_info_hashtable,
+        hook_infolist,
+        info_get,
+        infolist_new,
+        infolist_new_item,
+        infolist_new_var_integer,
+        infolist_new_var_string,
+        infolist_new_var_pointer,
+        infolist_new_var_buffer,
+        infolist_new_var_time,
+        infolist_get,
+        infolist_next,
+        infolist_prev,
+        infolist_reset_item_cursor,
+        infolist_fields,
+        infolist_integer,
+        infolist_string,
+        infolist_pointer,
+        infolist_buffer,
+        infolist_time,
+        infolist_free,
+        hdata_get_var_type,
+        hdata_get_var_array_size,
+        hdata_get_var_hdata,
+        hdata_move,
+        hdata_search,
+        hdata_char,
+        hdata_integer,
+        hdata_long,
+        hdata_pointer,
+        hdata_time,
+        hdata_hashtable,
+        hdata_update,
+        hdata_get_string,
+        window_search_with_buffer,
+        window_get_integer,
+        window_get_string,
+        window_get_pointer,
+        window_set_title,
+    );
+}
+
+/// Print a message on a buffer, emulates `printf_date_tags()`. The date and
+/// tags of the message aren't recorded.
+pub(super) fn printf_date_tags(buffer: *mut t_gui_buffer, message: &str) {
+    try_with_state(|state| {
+        let id = state.buffer_id(buffer);
+
+        if let Some(buffer) = state.buffers.get_mut(&id) {
+            buffer.lines.push(message.to_owned());
+        }
+    })
+}
+
+/// Write a message to the log, emulates `log_printf()`.
+pub(super) fn log_printf(message: &str) {
+    try_with_state(|state| state.log.push(message.to_owned()))
+}
+
+unsafe extern "C" fn prefix(prefix: *const c_char) -> *const c_char {
+    let prefix: &'static [u8] = match to_string(prefix).as_ref() {
+        "error" => b"=!=\t\0",
+        "network" => b"--\t\0",
+        "action" => b" *\t\0",
+        "join" => b"-->\t\0",
+        "quit" => b"<--\t\0",
+        _ => b"\0",
+    };
+
+    prefix.as_ptr() as *const c_char
+}
+
+unsafe extern "C" fn color(_color_name: *const c_char) -> *const c_char {
+    // Colors aren't rendered, so they don't show up in the printed lines.
+    b"\0".as_ptr() as *const c_char
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn buffer_new(
+    plugin: *mut t_weechat_plugin,
+    name: *const c_char,
+    input_callback: Option<InputCbT>,
+    input_callback_pointer: *const c_void,
+    input_callback_data: *mut c_void,
+    close_callback: Option<CloseCbT>,
+    close_callback_pointer: *const c_void,
+    close_callback_data: *mut c_void,
+) -> *mut t_gui_buffer {
+    let plugin_name = to_string((*plugin).name);
+    let name = to_string(name);
+
+    try_with_state(|state| {
+        let full_name = format!("{}.{}", plugin_name, name);
+
+        if name.is_empty()
+            || state
+                .find_buffer(|buffer| buffer.get_str("full_name") == full_name)
+                .is_some()
+        {
+            return ptr::null_mut();
+        }
+
+        let mut buffer = FakeBuffer::new(&plugin_name, &name);
+        buffer.input_callback = Callback::new(
+            input_callback,
+            input_callback_pointer,
+            input_callback_data,
+        );
+        buffer.close_callback = Callback::new(
+            close_callback,
+            close_callback_pointer,
+            close_callback_data,
+        );
+
+        let id = state.new_id();
+        state.buffers.insert(id, buffer);
+
+        to_ptr(id)
+    })
+}
+
+unsafe extern "C" fn buffer_search(
+    plugin: *const c_char,
+    name: *const c_char,
+) -> *mut t_gui_buffer {
+    let plugin = to_string(plugin);
+    let name = to_string(name);
+
+    try_with_state(|state| {
+        let id = if name.is_empty() {
+            Some(state.current_buffer)
+        } else if plugin == "==" {
+            state.find_buffer(|buffer| buffer.get_str("full_name") == name)
+        } else {
+            state.find_buffer(|buffer| {
+                buffer.get_str("name") == name
+                    && (plugin.is_empty() || buffer.get_str("plugin") == plugin)
+            })
+        };
+
+        id.map_or(ptr::null_mut(), to_ptr)
+    })
+}
+
+unsafe extern "C" fn buffer_get_string(
+    buffer: *mut t_gui_buffer,
+    property: *const c_char,
+) -> *const c_char {
+    let property = to_string(property);
+
+    try_with_state(|state| {
+        let id = state.buffer_id(buffer);
+
+        state
+            .buffers
+            .get(&id)
+            .and_then(|buffer| buffer.get(&property))
+            .map_or(ptr::null(), |value| value.as_ptr())
+    })
+}
+
+unsafe extern "C" fn buffer_set(
+    buffer: *mut t_gui_buffer,
+    property: *const c_char,
+    value: *const c_char,
+) {
+    let property = to_string(property);
+    let value = to_string(value);
+
+    try_with_state(|state| {
+        let id = state.buffer_id(buffer);
+
+        if property == "display" {
+            if state.buffers.contains_key(&id) {
+                state.current_buffer = id;
+            }
+            return;
+        }
+
+        let buffer = match state.buffers.get_mut(&id) {
+            Some(buffer) => buffer,
+            None => return,
+        };
+
+        if let Some(name) = property.strip_prefix("localvar_set_") {
+            buffer.set(&format!("localvar_{}", name), &value);
+        } else if let Some(name) = property.strip_prefix("localvar_del_") {
+            buffer.properties.remove(&format!("localvar_{}", name));
+        } else {
+            buffer.set(&property, &value);
+        }
+    })
+}
+
+unsafe extern "C" fn buffer_clear(buffer: *mut t_gui_buffer) {
+    try_with_state(|state| {
+        let id = state.buffer_id(buffer);
+
+        if let Some(buffer) = state.buffers.get_mut(&id) {
+            buffer.lines.clear();
+        }
+    })
+}
+
+unsafe extern "C" fn nicklist_add_group(
+    buffer: *mut t_gui_buffer,
+    _parent_group: *mut t_gui_nick_group,
+    name: *const c_char,
+    _color: *const c_char,
+    _visible: c_int,
+) -> *mut t_gui_nick_group {
+    let name = to_string(name);
+
+    try_with_state(|state| {
+        let buffer = state.buffer_id(buffer);
+
+        if !state.buffers.contains_key(&buffer)
+            || state
+                .groups
+                .values()
+                .any(|group| group.buffer == buffer && group.name == name)
+        {
+            return ptr::null_mut();
+        }
+
+        let id = state.new_id();
+        state.groups.insert(id, FakeGroup { buffer, name });
+
+        to_ptr(id)
+    })
+}
+
+unsafe extern "C" fn nicklist_search_group(
+    buffer: *mut t_gui_buffer,
+    _from_group: *mut t_gui_nick_group,
+    name: *const c_char,
+) -> *mut t_gui_nick_group {
+    let name = to_string(name);
+
+    try_with_state(|state| {
+        let buffer = state.buffer_id(buffer);
+
+        state
+            .groups
+            .iter()
+            .find(|(_, group)| group.buffer == buffer && group.name == name)
+            .map_or(ptr::null_mut(), |(id, _)| to_ptr(*id))
+    })
+}
+
+unsafe extern "C" fn nicklist_add_nick(
+    buffer: *mut t_gui_buffer,
+    group: *mut t_gui_nick_group,
+    name: *const c_char,
+    color: *const c_char,
+    prefix: *const c_char,
+    prefix_color: *const c_char,
+    _visible: c_int,
+) -> *mut t_gui_nick {
+    let name = to_string(name);
+
+    let mut properties = HashMap::new();
+    properties.insert("name", LossyCString::new(&name));
+    properties.insert("color", LossyCString::new(to_string(color)));
+    properties.insert("prefix", LossyCString::new(to_string(prefix)));
+    properties
+        .insert("prefix_color", LossyCString::new(to_string(prefix_color)));
+
+    try_with_state(|state| {
+        let buffer = state.buffer_id(buffer);
+
+        if !state.buffers.contains_key(&buffer)
+            || state.nicks.values().any(|nick| {
+                nick.buffer == buffer
+                    && nick.properties["name"].to_str() == Ok(&name)
+            })
+        {
+            return ptr::null_mut();
+        }
+
+        let id = state.new_id();
+        state.nicks.insert(
+            id,
+            FakeNick {
+                buffer,
+                group: to_id(group),
+                properties,
+            },
+        );
+
+        to_ptr(id)
+    })
+}
+
+unsafe extern "C" fn nicklist_search_nick(
+    buffer: *mut t_gui_buffer,
+    from_group: *mut t_gui_nick_group,
+    name: *const c_char,
+) -> *mut t_gui_nick {
+    let name = to_string(name);
+    let from_group = to_id(from_group);
+
+    try_with_state(|state| {
+        let buffer = state.buffer_id(buffer);
+
+        state
+            .nicks
+            .iter()
+            .find(|(_, nick)| {
+                nick.buffer == buffer
+                    && (from_group == 0 || nick.group == from_group)
+                    && nick.properties["name"].to_str() == Ok(&name)
+            })
+            .map_or(ptr::null_mut(), |(id, _)| to_ptr(*id))
+    })
+}
+
+unsafe extern "C" fn nicklist_nick_get_string(
+    _buffer: *mut t_gui_buffer,
+    nick: *mut t_gui_nick,
+    property: *const c_char,
+) -> *const c_char {
+    let property = to_string(property);
+
+    try_with_state(|state| {
+        state
+            .nicks
+            .get(&to_id(nick))
+            .and_then(|nick| nick.properties.get(property.as_str()))
+            .map_or(ptr::null(), |value| value.as_ptr())
+    })
+}
+
+unsafe extern "C" fn nicklist_remove_nick(
+    _buffer: *mut t_gui_buffer,
+    nick: *mut t_gui_nick,
+) {
+    try_with_state(|state| state.nicks.remove(&to_id(nick)));
+}
+
+fn add_hook(hook: FakeHook) -> *mut t_hook {
+    try_with_state(|state| {
+        let id = state.new_id();
+        state.hooks.insert(id, hook);
+        to_ptr(id)
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn hook_command(
+    _plugin: *mut t_weechat_plugin,
+    command: *const c_char,
+    _description: *const c_char,
+    _args: *const c_char,
+    _args_description: *const c_char,
+    _completion: *const c_char,
+    callback: Option<CommandCbT>,
+    callback_pointer: *const c_void,
+    callback_data: *mut c_void,
+) -> *mut t_hook {
+    let name = to_string(command);
+
+    match Callback::new(callback, callback_pointer, callback_data) {
+        Some(callback) if !name.is_empty() => {
+            add_hook(FakeHook::Command { name, callback })
+        }
+        _ => ptr::null_mut(),
+    }
+}
+
+unsafe extern "C" fn hook_command_run(
+    _plugin: *mut t_weechat_plugin,
+    command: *const c_char,
+    callback: Option<CommandRunCbT>,
+    callback_pointer: *const c_void,
+    callback_data: *mut c_void,
+) -> *mut t_hook {
+    let pattern = to_string(command);
+
+    match Callback::new(callback, callback_pointer, callback_data) {
+        Some(callback) => add_hook(FakeHook::CommandRun { pattern, callback }),
+        None => ptr::null_mut(),
+    }
+}
+
+unsafe extern "C" fn hook_signal(
+    _plugin: *mut t_weechat_plugin,
+    signal: *const c_char,
+    callback: Option<SignalCbT>,
+    callback_pointer: *const c_void,
+    callback_data: *mut c_void,
+) -> *mut t_hook {
+    let pattern = to_string(signal);
+
+    match Callback::new(callback, callback_pointer, callback_data) {
+        Some(callback) => add_hook(FakeHook::Signal { pattern, callback }),
+        None => ptr::null_mut(),
+    }
+}
+
+unsafe extern "C" fn hook_signal_send(
+    signal: *const c_char,
+    type_data: *const c_char,
+    signal_data: *mut c_void,
+) -> c_int {
+    let signal_name = to_string(signal);
+
+    let callbacks: Vec<Callback<SignalCbT>> = try_with_state(|state| {
+        state
+            .hooks
+            .values()
+            .filter_map(|hook| match hook {
+                FakeHook::Signal { pattern, callback }
+                    if matches(pattern, &signal_name) =>
+                {
+                    Some(*callback)
+                }
+                _ => None,
+            })
+            .collect()
+    });
+
+    let mut ret = WEECHAT_RC_OK;
+
+    for callback in callbacks {
+        ret = (callback.function)(
+            callback.pointer,
+            callback.data,
+            signal,
+            type_data,
+            signal_data,
+        );
+
+        if ret == WEECHAT_RC_OK_EAT {
+            break;
+        }
+    }
+
+    ret
+}
+
+unsafe extern "C" fn hook_timer(
+    _plugin: *mut t_weechat_plugin,
+    interval: c_long,
+    _align_second: c_int,
+    max_calls: c_int,
+    callback: Option<TimerCbT>,
+    callback_pointer: *const c_void,
+    callback_data: *mut c_void,
+) -> *mut t_hook {
+    let callback =
+        match Callback::new(callback, callback_pointer, callback_data) {
+            Some(callback) if interval > 0 => callback,
+            _ => return ptr::null_mut(),
+        };
+
+    let interval = Duration::from_millis(interval as u64);
+    let next_call = try_with_state(|state| state.now + interval);
+
+    add_hook(FakeHook::Timer(Timer {
+        interval,
+        next_call,
+        remaining_calls: if max_calls > 0 { Some(max_calls) } else { None },
+        callback,
+    }))
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn hook_fd(
+    _plugin: *mut t_weechat_plugin,
+    fd: c_int,
+    flag_read: c_int,
+    flag_write: c_int,
+    flag_exception: c_int,
+    callback: Option<FdCbT>,
+    callback_pointer: *const c_void,
+    callback_data: *mut c_void,
+) -> *mut t_hook {
+    let callback =
+        match Callback::new(callback, callback_pointer, callback_data) {
+            Some(callback) if fd >= 0 => callback,
+            _ => return ptr::null_mut(),
+        };
+
+    let mut events: c_short = 0;
+
+    if flag_read != 0 {
+        events |= libc::POLLIN;
+    }
+    if flag_write != 0 {
+        events |= libc::POLLOUT;
+    }
+    if flag_exception != 0 {
+        events |= libc::POLLPRI;
+    }
+
+    add_hook(FakeHook::Fd {
+        fd,
+        events,
+        callback,
+    })
+}
+
+unsafe extern "C" fn hook_process_hashtable(
+    _plugin: *mut t_weechat_plugin,
+    _command: *const c_char,
+    options: *mut t_hashtable,
+    _timeout: c_int,
+    callback: Option<ProcessCbT>,
+    callback_pointer: *const c_void,
+    callback_data: *mut c_void,
+) -> *mut t_hook {
+    let callback =
+        match Callback::new(callback, callback_pointer, callback_data) {
+            Some(callback) => callback,
+            None => return ptr::null_mut(),
+        };
+
+    let detached = LossyCString::new("detached");
+
+    // Weechat removes the hook of a detached process as soon as the process
+    // is started.
+    if !hashtable_get(options, detached.as_ptr() as *const c_void).is_null() {
+        return try_with_state(|state| to_ptr(state.new_id()));
+    }
+
+    add_hook(FakeHook::Process {
+        _callback: callback,
+    })
+}
+
+unsafe extern "C" fn hook_set(
+    _hook: *mut t_hook,
+    _property: *const c_char,
+    _value: *const c_char,
+) {
+}
+
+unsafe extern "C" fn unhook(hook: *mut t_hook) {
+    try_with_state(|state| {
+        if state.hooks.remove(&to_id(hook)).is_none() {
+            record_failure("unhook was called on a removed hook".to_owned());
+        }
+    })
+}
+
+/// Set the value of an option, mirrors `config_file_option_set()` of
+/// Weechat. The value needs to be already normalized.
+fn set_option(id: usize, value: Option<String>, run_callback: bool) -> c_int {
+    let check_callback = match try_with_state(|state| {
+        state.options.get(&id).map(|o| o.check_callback)
+    }) {
+        Some(callback) => callback,
+        None => return WEECHAT_CONFIG_OPTION_SET_OPTION_NOT_FOUND,
+    };
+
+    if let (Some(callback), Some(value)) = (check_callback, &value) {
+        let c_value = LossyCString::new(value);
+
+        let valid = unsafe {
+            (callback.function)(
+                callback.pointer,
+                callback.data,
+                to_ptr(id),
+                c_value.as_ptr(),
+            )
+        };
+
+        if valid == 0 {
+            return WEECHAT_CONFIG_OPTION_SET_ERROR;
+        }
+    }
+
+    let change_callback = try_with_state(|state| {
+        let option = state.options.get_mut(&id)?;
+        let value = value.map(LossyCString::new);
+
+        if option.value == value {
+            None
+        } else {
+            option.value = value;
+            Some(option.change_callback)
+        }
+    });
+
+    match change_callback {
+        Some(callback) => {
+            if let (true, Some(callback)) = (run_callback, callback) {
+                unsafe {
+                    (callback.function)(
+                        callback.pointer,
+                        callback.data,
+                        to_ptr(id),
+                    )
+                };
+            }
+
+            WEECHAT_CONFIG_OPTION_SET_OK_CHANGED
+        }
+        None => WEECHAT_CONFIG_OPTION_SET_OK_SAME_VALUE,
+    }
+}
+
+unsafe extern "C" fn config_new(
+    _plugin: *mut t_weechat_plugin,
+    name: *const c_char,
+    callback_reload: Option<ReloadCbT>,
+    callback_reload_pointer: *const c_void,
+    callback_reload_data: *mut c_void,
+) -> *mut t_config_file {
+    let name = to_string(name);
+
+    try_with_state(|state| {
+        if name.is_empty()
+            || state.configs.values().any(|config| config.name == name)
+        {
+            return ptr::null_mut();
+        }
+
+        let id = state.new_id();
+        state.configs.insert(
+            id,
+            FakeConfig {
+                name,
+                _reload_callback: Callback::new(
+                    callback_reload,
+                    callback_reload_pointer,
+                    callback_reload_data,
+                ),
+            },
+        );
+
+        to_ptr(id)
+    })
+}
+
+// The callbacks of sections aren't supported, the config files are never
+// read or written.
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn config_new_section(
+    config_file: *mut t_config_file,
+    name: *const c_char,
+    _user_can_add_options: c_int,
+    _user_can_delete_options: c_int,
+    _callback_read: Option<
+        unsafe extern "C" fn(
+            *const c_void,
+            *mut c_void,
+            *mut t_config_file,
+            *mut t_config_section,
+            *const c_char,
+            *const c_char,
+        ) -> c_int,
+    >,
+    _callback_read_pointer: *const c_void,
+    _callback_read_data: *mut c_void,
+    _callback_write: Option<
+        unsafe extern "C" fn(
+            *const c_void,
+            *mut c_void,
+            *mut t_config_file,
+            *const c_char,
+        ) -> c_int,
+    >,
+    _callback_write_pointer: *const c_void,
+    _callback_write_data: *mut c_void,
+    _callback_write_default: Option<
+        unsafe extern "C" fn(
+            *const c_void,
+            *mut c_void,
+            *mut t_config_file,
+            *const c_char,
+        ) -> c_int,
+    >,
+    _callback_write_default_pointer: *const c_void,
+    _callback_write_default_data: *mut c_void,
+    _callback_create_option: Option<
+        unsafe extern "C" fn(
+            *const c_void,
+            *mut c_void,
+            *mut t_config_file,
+            *mut t_config_section,
+            *const c_char,
+            *const c_char,
+        ) -> c_int,
+    >,
+    _callback_create_option_pointer: *const c_void,
+    _callback_create_option_data: *mut c_void,
+    _callback_delete_option: Option<
+        unsafe extern "C" fn(
+            *const c_void,
+            *mut c_void,
+            *mut t_config_file,
+            *mut t_config_section,
+            *mut t_config_option,
+        ) -> c_int,
+    >,
+    _callback_delete_option_pointer: *const c_void,
+    _callback_delete_option_data: *mut c_void,
+) -> *mut t_config_section {
+    let name = to_string(name);
+    let config = to_id(config_file);
+
+    try_with_state(|state| {
+        if !state.configs.contains_key(&config)
+            || state
+                .sections
+                .values()
+                .any(|section| section.config == config && section.name == name)
+        {
+            return ptr::null_mut();
+        }
+
+        let id = state.new_id();
+        state.sections.insert(id, FakeSection { config, name });
+
+        to_ptr(id)
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn config_new_option(
+    config_file: *mut t_config_file,
+    section: *mut t_config_section,
+    name: *const c_char,
+    type_: *const c_char,
+    description: *const c_char,
+    string_values: *const c_char,
+    min: c_int,
+    max: c_int,
+    default_value: *const c_char,
+    value: *const c_char,
+    null_value_allowed: c_int,
+    callback_check_value: Option<CheckCbT>,
+    callback_check_value_pointer: *const c_void,
+    callback_check_value_data: *mut c_void,
+    callback_change: Option<ChangeCbT>,
+    callback_change_pointer: *const c_void,
+    callback_change_data: *mut c_void,
+    _callback_delete: Option<ChangeCbT>,
+    _callback_delete_pointer: *const c_void,
+    _callback_delete_data: *mut c_void,
+) -> *mut t_config_option {
+    let name = to_string(name);
+    let option_type = to_string(type_);
+    let description = to_string(description);
+    let string_values = to_string(string_values);
+    let default_value = Some(default_value)
+        .filter(|v| !v.is_null())
+        .map(|v| to_string(v));
+    let value = Some(value).filter(|v| !v.is_null()).map(|v| to_string(v));
+
+    let section_id = to_id(section);
+    let config_id = to_id(config_file);
+
+    try_with_state(|state| {
+        let section = match state.sections.get(&section_id) {
+            Some(section) if section.config == config_id => section,
+            _ => return ptr::null_mut(),
+        };
+        let config = &state.configs[&config_id];
+
+        let full_name = format!("{}.{}.{}", config.name, section.name, name);
+
+        if name.is_empty()
+            || state
+                .options
+                .values()
+                .any(|option| option.full_name == full_name)
+        {
+            return ptr::null_mut();
+        }
+
+        let mut properties = HashMap::new();
+        properties.insert("config_name", LossyCString::new(&config.name));
+        properties.insert("section_name", LossyCString::new(&section.name));
+        properties.insert("name", LossyCString::new(&name));
+        properties.insert("type", LossyCString::new(&option_type));
+        properties.insert("description", LossyCString::new(&description));
+
+        let mut option = FakeOption {
+            section: section_id,
+            full_name,
+            option_type,
+            properties,
+            string_values: string_values
+                .split('|')
+                .filter(|value| !value.is_empty())
+                .map(str::to_owned)
+                .collect(),
+            min,
+            max,
+            default_value: None,
+            value: None,
+            null_allowed: null_value_allowed != 0,
+            check_callback: Callback::new(
+                callback_check_value,
+                callback_check_value_pointer,
+                callback_check_value_data,
+            ),
+            change_callback: Callback::new(
+                callback_change,
+                callback_change_pointer,
+                callback_change_data,
+            ),
+        };
+
+        let parse = |option: &FakeOption, value: Option<String>| match value {
+            Some(value) => option.parse_value(&value).map(Some),
+            None if option.null_allowed => Some(None),
+            None => None,
+        };
+
+        option.default_value = match parse(&option, default_value) {
+            Some(default_value) => default_value,
+            None => return ptr::null_mut(),
+        };
+        option.value = match parse(&option, value) {
+            Some(value) => value.map(LossyCString::new),
+            None => return ptr::null_mut(),
+        };
+
+        let id = state.new_id();
+        state.options.insert(id, option);
+
+        to_ptr(id)
+    })
+}
+
+unsafe extern "C" fn config_free(config_file: *mut t_config_file) {
+    let config = to_id(config_file);
+
+    try_with_state(|state| {
+        let sections: Vec<usize> = state
+            .sections
+            .iter()
+            .filter(|(_, section)| section.config == config)
+            .map(|(id, _)| *id)
+            .collect();
+
+        for section in sections {
+            state.options.retain(|_, option| option.section != section);
+            state.sections.remove(&section);
+        }
+
+        state.configs.remove(&config);
+    })
+}
+
+unsafe extern "C" fn config_section_free(section: *mut t_config_section) {
+    let section = to_id(section);
+
+    try_with_state(|state| {
+        state.options.retain(|_, option| option.section != section);
+        state.sections.remove(&section);
+    })
+}
+
+unsafe extern "C" fn config_section_free_options(
+    section: *mut t_config_section,
+) {
+    let section = to_id(section);
+    try_with_state(|state| {
+        state.options.retain(|_, option| option.section != section)
+    })
+}
+
+unsafe extern "C" fn config_get(
+    option_name: *const c_char,
+) -> *mut t_config_option {
+    let option_name = to_string(option_name);
+
+    try_with_state(|state| {
+        state
+            .options
+            .iter()
+            .find(|(_, option)| option.full_name == option_name)
+            .map_or(ptr::null_mut(), |(id, _)| to_ptr(*id))
+    })
+}
+
+unsafe extern "C" fn config_string(
+    option: *mut t_config_option,
+) -> *const c_char {
+    try_with_state(|state| {
+        state
+            .options
+            .get(&to_id(option))
+            .and_then(|option| option.value.as_ref())
+            .map_or(ptr::null(), |value| value.as_ptr())
+    })
+}
+
+unsafe extern "C" fn config_boolean(option: *mut t_config_option) -> c_int {
+    try_with_state(|state| {
+        let value = state.options.get(&to_id(option)).and_then(|o| o.value());
+        (value == Some("on")) as c_int
+    })
+}
+
+unsafe extern "C" fn config_integer(option: *mut t_config_option) -> c_int {
+    try_with_state(|state| {
+        state
+            .options
+            .get(&to_id(option))
+            .map_or(0, |option| option.integer())
+    })
+}
+
+unsafe extern "C" fn config_option_get_string(
+    option: *mut t_config_option,
+    property: *const c_char,
+) -> *const c_char {
+    let property = to_string(property);
+
+    try_with_state(|state| {
+        state
+            .options
+            .get(&to_id(option))
+            .and_then(|option| option.properties.get(property.as_str()))
+            .map_or(ptr::null(), |value| value.as_ptr())
+    })
+}
+
+unsafe extern "C" fn config_option_set(
+    option: *mut t_config_option,
+    value: *const c_char,
+    run_callback: c_int,
+) -> c_int {
+    let id = to_id(option);
+    let value = Some(value).filter(|v| !v.is_null()).map(|v| to_string(v));
+
+    let value = try_with_state(|state| {
+        let option = match state.options.get(&id) {
+            Some(option) => option,
+            None => return Err(WEECHAT_CONFIG_OPTION_SET_OPTION_NOT_FOUND),
+        };
+
+        match value {
+            Some(value) => option
+                .parse_value(&value)
+                .map(Some)
+                .ok_or(WEECHAT_CONFIG_OPTION_SET_ERROR),
+            None if option.null_allowed => Ok(None),
+            None => Err(WEECHAT_CONFIG_OPTION_SET_ERROR),
+        }
+    });
+
+    match value {
+        Ok(value) => set_option(id, value, run_callback != 0),
+        Err(ret) => ret,
+    }
+}
+
+unsafe extern "C" fn config_option_reset(
+    option: *mut t_config_option,
+    run_callback: c_int,
+) -> c_int {
+    let id = to_id(option);
+
+    match try_with_state(|state| {
+        state.options.get(&id).map(|o| o.default_value.clone())
+    }) {
+        Some(default_value) => set_option(id, default_value, run_callback != 0),
+        None => WEECHAT_CONFIG_OPTION_SET_OPTION_NOT_FOUND,
+    }
+}
+
+unsafe extern "C" fn config_option_set_null(
+    option: *mut t_config_option,
+    run_callback: c_int,
+) -> c_int {
+    let id = to_id(option);
+
+    match try_with_state(|state| state.options.get(&id).map(|o| o.null_allowed))
+    {
+        Some(true) => set_option(id, None, run_callback != 0),
+        Some(false) => WEECHAT_CONFIG_OPTION_SET_ERROR,
+        None => WEECHAT_CONFIG_OPTION_SET_OPTION_NOT_FOUND,
+    }
+}
+
+unsafe extern "C" fn config_option_unset(
+    option: *mut t_config_option,
+) -> c_int {
+    match config_option_reset(option, 1) {
+        WEECHAT_CONFIG_OPTION_SET_OK_CHANGED => {
+            WEECHAT_CONFIG_OPTION_UNSET_OK_RESET
+        }
+        WEECHAT_CONFIG_OPTION_SET_OK_SAME_VALUE => {
+            WEECHAT_CONFIG_OPTION_UNSET_OK_NO_RESET
+        }
+        _ => WEECHAT_CONFIG_OPTION_UNSET_ERROR,
+    }
+}
+
+unsafe extern "C" fn config_option_is_null(
+    option: *mut t_config_option,
+) -> c_int {
+    try_with_state(|state| {
+        state
+            .options
+            .get(&to_id(option))
+            .map_or(1, |option| option.value.is_none() as c_int)
+    })
+}
+
+unsafe extern "C" fn config_option_default_is_null(
+    option: *mut t_config_option,
+) -> c_int {
+    try_with_state(|state| {
+        state
+            .options
+            .get(&to_id(option))
+            .map_or(1, |option| option.default_value.is_none() as c_int)
+    })
+}
+
+unsafe fn plugin_option_name(
+    plugin: *mut t_weechat_plugin,
+    option_name: *const c_char,
+) -> String {
+    format!(
+        "plugins.var.{}.{}",
+        to_string((*plugin).name),
+        to_string(option_name)
+    )
+}
+
+unsafe extern "C" fn config_get_plugin(
+    plugin: *mut t_weechat_plugin,
+    option_name: *const c_char,
+) -> *const c_char {
+    let option_name = plugin_option_name(plugin, option_name);
+
+    try_with_state(|state| {
+        state
+            .plugin_options
+            .get(&option_name)
+            .map_or(ptr::null(), |value| value.as_ptr())
+    })
+}
+
+unsafe extern "C" fn config_set_plugin(
+    plugin: *mut t_weechat_plugin,
+    option_name: *const c_char,
+    value: *const c_char,
+) -> c_int {
+    let option_name = plugin_option_name(plugin, option_name);
+    let value = LossyCString::new(to_string(value));
+
+    try_with_state(|state| {
+        match state.plugin_options.insert(option_name, value.clone()) {
+            Some(old_value) if old_value == value => {
+                WEECHAT_CONFIG_OPTION_SET_OK_SAME_VALUE
+            }
+            _ => WEECHAT_CONFIG_OPTION_SET_OK_CHANGED,
+        }
+    })
+}
+
+unsafe extern "C" fn bar_item_search(
+    name: *const c_char,
+) -> *mut t_gui_bar_item {
+    let name = to_string(name);
+
+    try_with_state(|state| {
+        state
+            .bar_items
+            .iter()
+            .find(|(_, item)| item.name == name)
+            .map_or(ptr::null_mut(), |(id, _)| to_ptr(*id))
+    })
+}
+
+unsafe extern "C" fn bar_item_new(
+    _plugin: *mut t_weechat_plugin,
+    name: *const c_char,
+    build_callback: Option<BarItemCbT>,
+    build_callback_pointer: *const c_void,
+    build_callback_data: *mut c_void,
+) -> *mut t_gui_bar_item {
+    let name = to_string(name);
+    let callback = match Callback::new(
+        build_callback,
+        build_callback_pointer,
+        build_callback_data,
+    ) {
+        Some(callback) => callback,
+        None => return ptr::null_mut(),
+    };
+
+    try_with_state(|state| {
+        if name.is_empty() || state.bar_items.values().any(|i| i.name == name) {
+            return ptr::null_mut();
+        }
+
+        let id = state.new_id();
+        state.bar_items.insert(id, FakeBarItem { name, callback });
+
+        to_ptr(id)
+    })
+}
+
+unsafe extern "C" fn bar_item_update(_name: *const c_char) {
+    // Bar items are only built on demand, see
+    // `TestWeechat::bar_item_content()`.
+}
+
+unsafe extern "C" fn bar_item_remove(item: *mut t_gui_bar_item) {
+    try_with_state(|state| state.bar_items.remove(&to_id(item)));
+}
+
+/// Check if a value is valid for a property of a bar.
+fn valid_bar_property(property: &str, value: &str) -> bool {
+    match property {
+        "name" => !value.is_empty(),
+        "hidden" | "separator" => value == "on" || value == "off",
+        "priority" => value.parse::<i32>().is_ok(),
+        "size" | "size_max" => value.parse::<u32>().is_ok(),
+        "type" => value == "root" || value == "window",
+        "position" => ["top", "bottom", "left", "right"].contains(&value),
+        "filling_top_bottom" | "filling_left_right" => [
+            "horizontal",
+            "vertical",
+            "columns_horizontal",
+            "columns_vertical",
+        ]
+        .contains(&value),
+        "conditions" | "color_fg" | "color_delim" | "color_bg" | "items" => {
+            true
+        }
+        _ => false,
+    }
+}
+
+unsafe extern "C" fn bar_search(name: *const c_char) -> *mut t_gui_bar {
+    let name = to_string(name);
+
+    try_with_state(|state| {
+        state
+            .bars
+            .iter()
+            .find(|(_, bar)| bar.name() == name)
+            .map_or(ptr::null_mut(), |(id, _)| to_ptr(*id))
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn bar_new(
+    name: *const c_char,
+    hidden: *const c_char,
+    priority: *const c_char,
+    type_: *const c_char,
+    condition: *const c_char,
+    position: *const c_char,
+    filling_top_bottom: *const c_char,
+    filling_left_right: *const c_char,
+    size: *const c_char,
+    size_max: *const c_char,
+    color_fg: *const c_char,
+    color_delim: *const c_char,
+    color_bg: *const c_char,
+    separator: *const c_char,
+    items: *const c_char,
+) -> *mut t_gui_bar {
+    let properties: Vec<(&str, String)> = [
+        ("name", name),
+        ("hidden", hidden),
+        ("priority", priority),
+        ("type", type_),
+        ("conditions", condition),
+        ("position", position),
+        ("filling_top_bottom", filling_top_bottom),
+        ("filling_left_right", filling_left_right),
+        ("size", size),
+        ("size_max", size_max),
+        ("color_fg", color_fg),
+        ("color_delim", color_delim),
+        ("color_bg", color_bg),
+        ("separator", separator),
+        ("items", items),
+    ]
+    .iter()
+    .map(|(property, value)| (*property, to_string(*value)))
+    .collect();
+
+    if !properties
+        .iter()
+        .all(|(property, value)| valid_bar_property(property, value))
+    {
+        return ptr::null_mut();
+    }
+
+    let properties = properties
+        .into_iter()
+        .map(|(property, value)| {
+            (property.to_owned(), LossyCString::new(value))
+        })
+        .collect();
+
+    try_with_state(|state| {
+        let bar = FakeBar { properties };
+
+        if state.bars.values().any(|other| other.name() == bar.name()) {
+            return ptr::null_mut();
+        }
+
+        let id = state.new_id();
+        state.bars.insert(id, bar);
+
+        to_ptr(id)
+    })
+}
+
+unsafe extern "C" fn bar_set(
+    bar: *mut t_gui_bar,
+    property: *const c_char,
+    value: *const c_char,
+) -> c_int {
+    let property = to_string(property);
+    let value = to_string(value);
+
+    if !valid_bar_property(&property, &value) {
+        return 0;
+    }
+
+    try_with_state(|state| {
+        let id = to_id(bar);
+
+        if property == "name"
+            && state
+                .bars
+                .iter()
+                .any(|(other, bar)| *other != id && bar.name() == value)
+        {
+            return 0;
+        }
+
+        match state.bars.get_mut(&id) {
+            Some(bar) => {
+                bar.properties.insert(property, LossyCString::new(value));
+                1
+            }
+            None => 0,
+        }
+    })
+}
+
+unsafe extern "C" fn bar_update(_name: *const c_char) {
+    // Bars aren't drawn.
+}
+
+unsafe extern "C" fn bar_remove(bar: *mut t_gui_bar) {
+    try_with_state(|state| state.bars.remove(&to_id(bar)));
+}
+
+unsafe extern "C" fn hdata_get(
+    _plugin: *mut t_weechat_plugin,
+    hdata_name: *const c_char,
+) -> *mut t_hdata {
+    let name = to_string(hdata_name);
+
+    if name != "bar" {
+        record_failure(format!("The {} hdata isn't emulated", name));
+        return ptr::null_mut();
+    }
+
+    try_with_state(|state| to_ptr(state.bar_hdata))
+}
+
+/// Run a function with the state if the hdata is the one of bars, returns
+/// the fallback value otherwise.
+fn with_bar_hdata<R: Fallback>(
+    hdata: *mut t_hdata,
+    f: impl FnOnce(&mut State) -> R,
+) -> R {
+    try_with_state(|state| {
+        if to_id(hdata) == state.bar_hdata {
+            f(state)
+        } else {
+            R::fallback()
+        }
+    })
+}
+
+unsafe extern "C" fn hdata_get_list(
+    hdata: *mut t_hdata,
+    name: *const c_char,
+) -> *mut c_void {
+    let name = to_string(name);
+
+    with_bar_hdata(hdata, |state| match state.bars.keys().next() {
+        Some(id) if name == "gui_bars" => to_ptr(*id),
+        _ => ptr::null_mut(),
+    })
+}
+
+unsafe extern "C" fn hdata_check_pointer(
+    hdata: *mut t_hdata,
+    list: *mut c_void,
+    pointer: *mut c_void,
+) -> c_int {
+    with_bar_hdata(hdata, |state| {
+        (!list.is_null() && state.bars.contains_key(&to_id(pointer))) as c_int
+    })
+}
+
+unsafe extern "C" fn hdata_string(
+    hdata: *mut t_hdata,
+    pointer: *mut c_void,
+    name: *const c_char,
+) -> *const c_char {
+    let name = to_string(name);
+
+    with_bar_hdata(hdata, |state| {
+        state
+            .bars
+            .get(&to_id(pointer))
+            .and_then(|bar| bar.properties.get(&name))
+            .map_or(ptr::null(), |value| value.as_ptr())
+    })
+}
+
+// Custom hash functions aren't supported, Weechat requires them for buffer
+// keys so hashtables with buffer keys can't be created.
+unsafe extern "C" fn hashtable_new(
+    size: c_int,
+    type_keys: *const c_char,
+    type_values: *const c_char,
+    _callback_hash_key: Option<HashKeyCbT>,
+    _callback_keycmp: Option<KeyCmpCbT>,
+) -> *mut t_hashtable {
+    const KEY_TYPES: [&str; 4] = ["integer", "string", "pointer", "time"];
+
+    let key_type = to_string(type_keys);
+    let value_type = to_string(type_values);
+
+    if size <= 0
+        || !KEY_TYPES.contains(&key_type.as_str())
+        || !(KEY_TYPES.contains(&value_type.as_str()) || value_type == "buffer")
+    {
+        return ptr::null_mut();
+    }
+
+    try_with_state(|state| {
+        let id = state.new_id();
+        state.hashtables.insert(
+            id,
+            FakeHashtable {
+                size,
+                key_type: LossyCString::new(key_type),
+                value_type: LossyCString::new(value_type),
+                items: Vec::new(),
+            },
+        );
+
+        to_ptr(id)
+    })
+}
+
+unsafe extern "C" fn hashtable_set_with_size(
+    hashtable: *mut t_hashtable,
+    key: *const c_void,
+    key_size: c_int,
+    value: *const c_void,
+    value_size: c_int,
+) -> *mut t_hashtable_item {
+    try_with_state(|state| {
+        let hashtable = match state.hashtables.get_mut(&to_id(hashtable)) {
+            Some(hashtable) => hashtable,
+            None => return ptr::null_mut(),
+        };
+
+        let key = FakeValue::from_raw(hashtable.key_type(), key, key_size);
+        let value =
+            FakeValue::from_raw(hashtable.value_type(), value, value_size);
+
+        let (key, value) = match (key, value) {
+            (Some(key), Some(value)) => (key, value),
+            _ => return ptr::null_mut(),
+        };
+
+        let position = match hashtable.position(&key) {
+            Some(position) => {
+                hashtable.items[position].1 = value;
+                position
+            }
+            None => {
+                hashtable.items.push((key, value));
+                hashtable.items.len() - 1
+            }
+        };
+
+        // Items are only checked for null, hand out their position.
+        to_ptr(position + 1)
+    })
+}
+
+unsafe extern "C" fn hashtable_set(
+    hashtable: *mut t_hashtable,
+    key: *const c_void,
+    value: *const c_void,
+) -> *mut t_hashtable_item {
+    hashtable_set_with_size(hashtable, key, 0, value, 0)
+}
+
+/// Find the item with the given key and run a function with it, returns the
+/// fallback value if the item doesn't exist.
+unsafe fn with_item<R: Fallback>(
+    hashtable: *mut t_hashtable,
+    key: *const c_void,
+    f: impl FnOnce(&mut FakeHashtable, usize) -> R,
+) -> R {
+    try_with_state(|state| {
+        let hashtable = match state.hashtables.get_mut(&to_id(hashtable)) {
+            Some(hashtable) => hashtable,
+            None => return R::fallback(),
+        };
+
+        let position = FakeValue::from_raw(hashtable.key_type(), key, 0)
+            .and_then(|key| hashtable.position(&key));
+
+        match position {
+            Some(position) => f(hashtable, position),
+            None => R::fallback(),
+        }
+    })
+}
+
+unsafe extern "C" fn hashtable_get(
+    hashtable: *mut t_hashtable,
+    key: *const c_void,
+) -> *mut c_void {
+    with_item(hashtable, key, |hashtable, position| {
+        hashtable.items[position].1.as_ptr()
+    })
+}
+
+unsafe extern "C" fn hashtable_has_key(
+    hashtable: *mut t_hashtable,
+    key: *const c_void,
+) -> c_int {
+    with_item(hashtable, key, |_, _| 1)
+}
+
+unsafe extern "C" fn hashtable_remove(
+    hashtable: *mut t_hashtable,
+    key: *const c_void,
+) {
+    with_item(hashtable, key, |hashtable, position| {
+        hashtable.items.remove(position);
+    })
+}
+
+unsafe extern "C" fn hashtable_remove_all(hashtable: *mut t_hashtable) {
+    try_with_state(|state| {
+        if let Some(hashtable) = state.hashtables.get_mut(&to_id(hashtable)) {
+            hashtable.items.clear();
+        }
+    })
+}
+
+unsafe extern "C" fn hashtable_map(
+    hashtable: *mut t_hashtable,
+    callback_map: Option<MapCbT>,
+    callback_map_data: *mut c_void,
+) {
+    // The pointers stay valid as long as the callback doesn't modify the
+    // hashtable, like in Weechat.
+    let items: Vec<(*mut c_void, *mut c_void)> = try_with_state(|state| {
+        state
+            .hashtables
+            .get(&to_id(hashtable))
+            .map(|hashtable| {
+                hashtable
+                    .items
+                    .iter()
+                    .map(|(key, value)| (key.as_ptr(), value.as_ptr()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    });
+
+    if let Some(callback) = callback_map {
+        for (key, value) in items {
+            callback(callback_map_data, hashtable, key, value);
+        }
+    }
+}
+
+unsafe extern "C" fn hashtable_map_string(
+    hashtable: *mut t_hashtable,
+    callback_map: Option<MapStringCbT>,
+    callback_map_data: *mut c_void,
+) {
+    let items: Vec<(CString, CString)> = try_with_state(|state| {
+        state
+            .hashtables
+            .get(&to_id(hashtable))
+            .map(|hashtable| {
+                hashtable
+                    .items
+                    .iter()
+                    .map(|(key, value)| {
+                        (
+                            LossyCString::new(key.display_string()),
+                            LossyCString::new(value.display_string()),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    });
+
+    if let Some(callback) = callback_map {
+        for (key, value) in items {
+            callback(
+                callback_map_data,
+                hashtable,
+                key.as_ptr(),
+                value.as_ptr(),
+            );
+        }
+    }
+}
+
+unsafe extern "C" fn hashtable_get_integer(
+    hashtable: *mut t_hashtable,
+    property: *const c_char,
+) -> c_int {
+    let property = to_string(property);
+
+    try_with_state(|state| {
+        let hashtable = match state.hashtables.get(&to_id(hashtable)) {
+            Some(hashtable) => hashtable,
+            None => return 0,
+        };
+
+        match property.as_ref() {
+            "size" => hashtable.size,
+            "items_count" => hashtable.items.len() as c_int,
+            _ => 0,
+        }
+    })
+}
+
+unsafe extern "C" fn hashtable_get_string(
+    hashtable: *mut t_hashtable,
+    property: *const c_char,
+) -> *const c_char {
+    let property = to_string(property);
+
+    try_with_state(|state| {
+        let hashtable = match state.hashtables.get(&to_id(hashtable)) {
+            Some(hashtable) => hashtable,
+            None => return ptr::null(),
+        };
+
+        match property.as_ref() {
+            "type_keys" => hashtable.key_type.as_ptr(),
+            "type_values" => hashtable.value_type.as_ptr(),
+            _ => ptr::null(),
+        }
+    })
+}
+
+unsafe extern "C" fn hashtable_free(hashtable: *mut t_hashtable) {
+    try_with_state(|state| state.hashtables.remove(&to_id(hashtable)));
+}
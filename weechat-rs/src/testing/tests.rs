@@ -0,0 +1,373 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::rc::Rc;
+use std::time::Duration;
+
+use super::{with_state, TestWeechat};
+use crate::bar::{BarBuilder, BarPosition};
+use crate::buffer::BufferBuilder;
+use crate::hooks::{
+    CommandDescription, CommandHook, CommandRunHook, FdHook, FdHookMode,
+    ProcessHook, ProcessOptions, SignalHook, SignalHookValue, TimerHook,
+};
+use crate::{HashtableType, HashtableValue, ReturnCode};
+
+#[test]
+fn hashtable_items_can_be_set_and_removed() {
+    let test = TestWeechat::new();
+    let weechat = test.weechat();
+
+    let mut hashtable = weechat
+        .hashtable_new(HashtableType::String, HashtableType::String)
+        .unwrap();
+
+    assert!(hashtable.is_empty());
+    assert!(hashtable.set("key", "value"));
+    assert!(hashtable.set("other", "value"));
+    assert!(hashtable.set("key", "changed"));
+
+    assert_eq!(hashtable.len(), 2);
+    assert_eq!(hashtable.get_string("key"), Some("changed".to_owned()));
+    assert!(hashtable.contains_key("other"));
+    assert_eq!(hashtable.get("missing"), None);
+
+    hashtable.remove("other");
+    assert!(!hashtable.contains_key("other"));
+    assert_eq!(hashtable.len(), 1);
+
+    hashtable.clear();
+    assert!(hashtable.is_empty());
+}
+
+#[test]
+fn hashtable_keeps_its_types() {
+    let test = TestWeechat::new();
+    let weechat = test.weechat();
+
+    let mut hashtable = weechat
+        .hashtable_new(HashtableType::Integer, HashtableType::Time)
+        .unwrap();
+
+    assert_eq!(hashtable.key_type(), HashtableType::Integer);
+    assert_eq!(hashtable.value_type(), HashtableType::Time);
+
+    assert!(!hashtable.set("key", 1));
+    assert!(hashtable.set(1, HashtableValue::Time(10)));
+    assert!(hashtable.set(2, HashtableValue::Time(20)));
+
+    assert_eq!(hashtable.get(2), Some(HashtableValue::Time(20)));
+
+    let mut items: Vec<_> = hashtable.iter().collect();
+    items.sort_by_key(|(key, _)| format!("{:?}", key));
+    assert_eq!(
+        items,
+        vec![
+            (HashtableValue::Integer(1), HashtableValue::Time(10)),
+            (HashtableValue::Integer(2), HashtableValue::Time(20)),
+        ]
+    );
+
+    let mut expected = HashMap::new();
+    expected.insert("1".to_owned(), "10".to_owned());
+    expected.insert("2".to_owned(), "20".to_owned());
+    assert_eq!(hashtable.to_string_map(), expected);
+}
+
+#[test]
+fn hashtable_with_buffer_keys_cant_be_created() {
+    let test = TestWeechat::new();
+
+    assert!(test
+        .weechat()
+        .hashtable_new(HashtableType::Buffer, HashtableType::String)
+        .is_err());
+}
+
+#[test]
+fn buffer_input_and_close_callbacks_are_run() {
+    let test = TestWeechat::new();
+    let weechat = test.weechat();
+
+    let input = Rc::new(RefCell::new(Vec::new()));
+    let closed = Rc::new(Cell::new(false));
+
+    let buffer = {
+        let input = input.clone();
+        let closed = closed.clone();
+
+        BufferBuilder::new("test")
+            .input_callback(move |_, _, text| {
+                input.borrow_mut().push(text.into_owned())
+            })
+            .close_callback(move |_, _| closed.set(true))
+            .build(&weechat)
+            .unwrap()
+    };
+
+    assert!(BufferBuilder::new("test").build(&weechat).is_err());
+    assert_eq!(buffer.get_name(), "test");
+    assert_eq!(weechat.buffer_search("", "test").unwrap(), buffer);
+
+    buffer.print("Hello");
+    assert_eq!(test.printed_lines(&buffer), vec!["Hello"]);
+    assert!(test.printed_lines(&test.core_buffer()).is_empty());
+
+    assert_eq!(test.input(&buffer, "text"), ReturnCode::Ok);
+    assert_eq!(*input.borrow(), vec!["text"]);
+
+    test.close_buffer(buffer);
+    assert!(closed.get());
+    assert!(weechat.buffer_search("", "test").is_none());
+}
+
+#[test]
+fn signal_hooks_are_run_until_one_eats_the_signal() {
+    let test = TestWeechat::new();
+    let weechat = test.weechat();
+
+    let received = Rc::new(RefCell::new(Vec::new()));
+    let other_calls = Rc::new(Cell::new(0));
+
+    let hook = {
+        let received = received.clone();
+        SignalHook::new(&weechat, "test_*", move |_, value| {
+            if let SignalHookValue::String(string) = value {
+                received.borrow_mut().push(string);
+            }
+            ReturnCode::OkEat
+        })
+        .unwrap()
+    };
+    let other_hook = {
+        let other_calls = other_calls.clone();
+        SignalHook::new(&weechat, "test_signal", move |_, _| {
+            other_calls.set(other_calls.get() + 1)
+        })
+        .unwrap()
+    };
+
+    let ret = test.send_signal(
+        "test_signal",
+        SignalHookValue::String("value".to_owned()),
+    );
+    assert_eq!(ret, ReturnCode::OkEat);
+    assert_eq!(*received.borrow(), vec!["value"]);
+    assert_eq!(other_calls.get(), 0);
+
+    drop(hook);
+    drop(other_hook);
+
+    let ret = test.send_signal("test_signal", SignalHookValue::Integer(1));
+    assert_eq!(ret, ReturnCode::Ok);
+    assert_eq!(received.borrow().len(), 1);
+}
+
+#[test]
+fn timer_hooks_run_until_their_calls_run_out() {
+    let test = TestWeechat::new();
+    let weechat = test.weechat();
+
+    let calls = Rc::new(RefCell::new(Vec::new()));
+
+    let _hook = {
+        let calls = calls.clone();
+        TimerHook::new(
+            &weechat,
+            Duration::from_secs(1),
+            0,
+            2,
+            move |_, remaining| calls.borrow_mut().push(remaining),
+        )
+        .unwrap()
+    };
+
+    test.advance_time(Duration::from_millis(500));
+    assert!(calls.borrow().is_empty());
+
+    test.advance_time(Duration::from_secs(5));
+    assert_eq!(*calls.borrow(), vec![1, 0]);
+}
+
+#[test]
+fn fd_hooks_are_run_when_the_fd_is_ready() {
+    let test = TestWeechat::new();
+    let weechat = test.weechat();
+
+    let (mut sender, receiver) = UnixStream::pair().unwrap();
+    receiver.set_nonblocking(true).unwrap();
+
+    let received = Rc::new(RefCell::new(Vec::new()));
+
+    let _hook = {
+        let received = received.clone();
+        FdHook::new(
+            &weechat,
+            receiver,
+            FdHookMode::Read,
+            move |_, receiver: &mut UnixStream| {
+                let mut buffer = [0; 16];
+                let len = receiver.read(&mut buffer).unwrap_or(0);
+                received.borrow_mut().extend_from_slice(&buffer[..len]);
+            },
+        )
+        .unwrap()
+    };
+
+    assert_eq!(test.run_ready_fds(), 0);
+
+    sender.write_all(b"data").unwrap();
+
+    assert_eq!(test.run_ready_fds(), 1);
+    assert_eq!(*received.borrow(), b"data");
+    assert_eq!(test.run_ready_fds(), 0);
+}
+
+#[test]
+fn detached_process_hooks_are_left_to_weechat() {
+    let test = TestWeechat::new();
+    let weechat = test.weechat();
+
+    let hook = ProcessHook::new(
+        &weechat,
+        "true",
+        ProcessOptions::new(),
+        Duration::from_secs(0),
+        |_, _| (),
+    )
+    .unwrap();
+    assert!(!hook.finished());
+    drop(hook);
+
+    // Weechat already removed the hook, dropping it must not unhook it
+    // again.
+    let hook = ProcessHook::new(
+        &weechat,
+        "true",
+        ProcessOptions::new().detached(true),
+        Duration::from_secs(0),
+        |_, _| (),
+    )
+    .unwrap();
+    assert!(hook.finished());
+    drop(hook);
+}
+
+#[test]
+fn command_run_hooks_can_eat_commands() {
+    let test = TestWeechat::new();
+    let weechat = test.weechat();
+    let buffer = test.core_buffer();
+
+    let arguments = Rc::new(RefCell::new(Vec::new()));
+
+    let _command = {
+        let arguments = arguments.clone();
+        CommandHook::new(
+            &weechat,
+            CommandDescription {
+                name: "test",
+                ..Default::default()
+            },
+            move |_, _, args| arguments.borrow_mut().extend(args),
+        )
+        .unwrap()
+    };
+
+    assert_eq!(test.run_command(&buffer, "/test a  b"), ReturnCode::Ok);
+    assert_eq!(*arguments.borrow(), vec!["/test", "a", "b"]);
+    assert_eq!(test.run_command(&buffer, "/missing"), ReturnCode::Error);
+
+    let _run_hook =
+        CommandRunHook::new(&weechat, "/test*", |_, _, _| ReturnCode::OkEat)
+            .unwrap();
+
+    assert_eq!(test.run_command(&buffer, "/test c"), ReturnCode::OkEat);
+    assert_eq!(arguments.borrow().len(), 3);
+}
+
+#[test]
+fn bars_are_built_set_and_removed() {
+    let test = TestWeechat::new();
+    let weechat = test.weechat();
+
+    let bar = BarBuilder::new("test")
+        .position(BarPosition::Left)
+        .items(&["buffer_plugin"])
+        .build(&weechat)
+        .unwrap();
+
+    assert!(BarBuilder::new("test").build(&weechat).is_err());
+    assert_eq!(
+        test.bar_property("test", "position"),
+        Some("left".to_owned())
+    );
+    assert_eq!(
+        test.bar_property("test", "items"),
+        Some("buffer_plugin".to_owned())
+    );
+
+    assert!(bar.set_size(3));
+    assert!(bar.set_items(&["buffer_name", "buffer_plugin"]));
+    assert!(!bar.set("position", "middle"));
+    assert_eq!(test.bar_property("test", "size"), Some("3".to_owned()));
+    assert_eq!(
+        test.bar_property("test", "items"),
+        Some("buffer_name,buffer_plugin".to_owned())
+    );
+
+    assert!(bar.set_name("renamed"));
+    drop(bar);
+
+    assert!(weechat.bar_search("renamed").is_none());
+}
+
+#[test]
+fn bars_renamed_through_other_handles_are_removed() {
+    let test = TestWeechat::new();
+    let weechat = test.weechat();
+
+    let bar = BarBuilder::new("test").build(&weechat).unwrap();
+    let other_handle = weechat.bar_search("test").unwrap();
+
+    assert!(other_handle.set_name("renamed"));
+    assert_eq!(bar.get_name().as_deref(), Some("renamed"));
+
+    drop(bar);
+    assert!(weechat.bar_search("renamed").is_none());
+    assert!(!other_handle.set_hidden(true));
+    assert_eq!(other_handle.get_name(), None);
+}
+
+#[test]
+fn bars_deleted_by_the_user_are_left_alone() {
+    let test = TestWeechat::new();
+    let weechat = test.weechat();
+
+    let bar = BarBuilder::new("test").build(&weechat).unwrap();
+
+    // Like `/bar del test`, another bar takes over the name.
+    with_state(|state| state.bars.clear());
+    assert!(!bar.set_size(1));
+    assert_eq!(bar.get_name(), None);
+    bar.update();
+
+    let other_bar = BarBuilder::new("test").build(&weechat).unwrap();
+
+    drop(bar);
+    assert!(weechat.bar_search("test").is_some());
+
+    drop(other_bar);
+    assert!(weechat.bar_search("test").is_none());
+}
+
+#[test]
+#[should_panic(expected = "info_get isn't emulated")]
+fn unemulated_functions_are_reported() {
+    let test = TestWeechat::new();
+
+    assert!(test.weechat().info_get("version", "").is_none());
+
+    test.core_buffer();
+}
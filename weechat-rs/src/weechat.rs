@@ -1,13 +1,21 @@
 //! Main weechat module
 
-use weechat_sys::t_weechat_plugin;
+use weechat_sys::{t_gui_buffer, t_weechat_plugin};
 
 use crate::LossyCString;
-use libc::{c_char, c_int};
+use libc::{c_char, c_int, time_t};
 use std::borrow::Cow;
+use std::cell::Cell;
 use std::ffi::CStr;
 use std::{ptr, vec};
 
+thread_local! {
+    // The plugin pointer is only set on the main thread, Weechat::instance()
+    // can't be used on other threads.
+    static WEECHAT_PTR: Cell<*mut t_weechat_plugin> =
+        const { Cell::new(ptr::null_mut()) };
+}
+
 /// An iterator over the arguments of a command, yielding a String value for
 /// each argument.
 pub struct ArgsWeechat {
@@ -82,6 +90,30 @@ impl OptionChanged {
     }
 }
 
+/// Status for unsetting options
+pub enum OptionUnset {
+    /// The option had no default value and was left unchanged.
+    NoReset = weechat_sys::WEECHAT_CONFIG_OPTION_UNSET_OK_NO_RESET as isize,
+    /// The option was reset to its default value.
+    Reset = weechat_sys::WEECHAT_CONFIG_OPTION_UNSET_OK_RESET as isize,
+    /// The option was removed.
+    Removed = weechat_sys::WEECHAT_CONFIG_OPTION_UNSET_OK_REMOVED as isize,
+    /// An error occurred unsetting the option.
+    Error = weechat_sys::WEECHAT_CONFIG_OPTION_UNSET_ERROR as isize,
+}
+
+impl OptionUnset {
+    pub(crate) fn from_int(v: i32) -> OptionUnset {
+        use OptionUnset::*;
+        match v {
+            weechat_sys::WEECHAT_CONFIG_OPTION_UNSET_OK_NO_RESET => NoReset,
+            weechat_sys::WEECHAT_CONFIG_OPTION_UNSET_OK_RESET => Reset,
+            weechat_sys::WEECHAT_CONFIG_OPTION_UNSET_OK_REMOVED => Removed,
+            _ => Error,
+        }
+    }
+}
+
 /// Main Weechat struct that encapsulates common weechat API functions.
 /// It has a similar API as the weechat script API.
 pub struct Weechat {
@@ -102,8 +134,62 @@ impl Weechat {
         unsafe { &*self.ptr }
     }
 
+    /// Store the plugin pointer so it can be retrieved with
+    /// `Weechat::instance()`.
+    ///
+    /// This is called by the `weechat_plugin!` macro when the plugin is
+    /// loaded and should not be called otherwise.
+    ///
+    /// # Safety
+    ///
+    /// This needs to be called on the Weechat main thread with the pointer
+    /// Weechat passed to the plugin init function.
+    #[doc(hidden)]
+    pub unsafe fn init_from_ptr(ptr: *mut t_weechat_plugin) -> Weechat {
+        WEECHAT_PTR.with(|weechat_ptr| weechat_ptr.set(ptr));
+        Weechat::from_ptr(ptr)
+    }
+
+    /// Free the resources the crate holds for the plugin.
+    ///
+    /// This is called by the `weechat_plugin!` macro once the plugin is
+    /// unloaded and should not be called otherwise.
+    #[doc(hidden)]
+    pub fn free() {
+        crate::plugin::free_plugin();
+
+        #[cfg(feature = "async")]
+        crate::executor::free();
+
+        WEECHAT_PTR.with(|weechat_ptr| weechat_ptr.set(ptr::null_mut()));
+    }
+
+    /// Get the Weechat object of the plugin.
+    ///
+    /// # Panics
+    ///
+    /// Panics if it's called outside of the Weechat main thread or if the
+    /// plugin wasn't initialized by the `weechat_plugin!` macro.
+    pub fn instance() -> Weechat {
+        let ptr = WEECHAT_PTR.with(|weechat_ptr| weechat_ptr.get());
+
+        if ptr.is_null() {
+            panic!(
+                "Weechat::instance() can only be used on the Weechat main \
+                 thread while the plugin is loaded"
+            );
+        }
+
+        Weechat { ptr }
+    }
+
     /// Write a message in WeeChat log file (weechat.log).
     pub fn log(&self, msg: &str) {
+        #[cfg(feature = "testing")]
+        if crate::testing::print_variadic(self.ptr, None, msg) {
+            return;
+        }
+
         let log_printf = self.get().log_printf.unwrap();
 
         let fmt = LossyCString::new("%s");
@@ -116,18 +202,35 @@ impl Weechat {
 
     /// Display a message on the core weechat buffer.
     pub fn print(&self, msg: &str) {
+        self.print_date_tags(ptr::null_mut(), 0, None, msg);
+    }
+
+    /// Display a message on a buffer, a null buffer is the core buffer.
+    pub(crate) fn print_date_tags(
+        &self,
+        buffer: *mut t_gui_buffer,
+        date: time_t,
+        tags: Option<&str>,
+        message: &str,
+    ) {
+        #[cfg(feature = "testing")]
+        if crate::testing::print_variadic(self.ptr, Some(buffer), message) {
+            return;
+        }
+
         let printf_date_tags = self.get().printf_date_tags.unwrap();
 
         let fmt = LossyCString::new("%s");
-        let msg = LossyCString::new(msg);
+        let tags = tags.map(LossyCString::new);
+        let message = LossyCString::new(message);
 
         unsafe {
             printf_date_tags(
-                ptr::null_mut(),
-                0,
-                ptr::null(),
+                buffer,
+                date,
+                tags.as_ref().map_or(ptr::null(), |tags| tags.as_ptr()),
                 fmt.as_ptr(),
-                msg.as_ptr(),
+                message.as_ptr(),
             );
         }
     }
@@ -224,27 +327,10 @@ impl Weechat {
         }
     }
 
-    /// Evaluate a weechat expression and return the result
-    //
-    // TODO: Add hashtable options
+    /// Evaluate a weechat expression and return the result.
+    /// See `Weechat::eval_builder()` to evaluate an expression with pointers,
+    /// extra variables or options.
     pub fn eval_string_expression(&self, expr: &str) -> Option<Cow<str>> {
-        let string_eval_expression = self.get().string_eval_expression.unwrap();
-
-        let expr = LossyCString::new(expr);
-
-        unsafe {
-            let result = string_eval_expression(
-                expr.as_ptr(),
-                ptr::null_mut(),
-                ptr::null_mut(),
-                ptr::null_mut(),
-            );
-
-            if result.is_null() {
-                None
-            } else {
-                Some(CStr::from_ptr(result).to_string_lossy())
-            }
-        }
+        self.eval_builder(expr).evaluate().map(Cow::from)
     }
 }
@@ -0,0 +1,173 @@
+//! Weechat Window module containing the Window type.
+use std::borrow::Cow;
+use std::ffi::CStr;
+use std::os::raw::c_void;
+use std::ptr;
+use weechat_sys::{t_gui_buffer, t_gui_window, t_weechat_plugin};
+
+use crate::{Buffer, LossyCString, Weechat};
+
+/// A high level Window type encapsulating weechats C window pointer.
+#[derive(Debug, Eq)]
+pub struct Window {
+    pub(crate) weechat: *mut t_weechat_plugin,
+    pub(crate) ptr: *mut t_gui_window,
+}
+
+impl PartialEq for Window {
+    fn eq(&self, other: &Window) -> bool {
+        self.ptr == other.ptr
+    }
+}
+
+impl Weechat {
+    /// Search for the window that displays a buffer.
+    /// * `buffer` - The buffer that the window should display.
+    /// Returns the window if the buffer is displayed, otherwise None.
+    pub fn window_search_with_buffer(&self, buffer: &Buffer) -> Option<Window> {
+        let window_search_with_buffer =
+            self.get().window_search_with_buffer.unwrap();
+
+        let ptr = unsafe { window_search_with_buffer(buffer.ptr) };
+
+        Window::from_raw(self.ptr, ptr)
+    }
+
+    /// Get the currently active window.
+    pub fn current_window(&self) -> Option<Window> {
+        let window_get_pointer = self.get().window_get_pointer.unwrap();
+        let property = LossyCString::new("current");
+
+        let ptr = unsafe {
+            window_get_pointer(ptr::null_mut(), property.as_ptr())
+                as *mut t_gui_window
+        };
+
+        Window::from_raw(self.ptr, ptr)
+    }
+
+    /// Set the title of the terminal Weechat is running in.
+    /// * `title` - The new title, the title is evaluated so it can contain
+    ///     expressions like `${info:version}`.
+    pub fn set_window_title(&self, title: &str) {
+        let window_set_title = self.get().window_set_title.unwrap();
+        let title = LossyCString::new(title);
+
+        unsafe { window_set_title(title.as_ptr()) };
+    }
+}
+
+impl Window {
+    /// Create a high level Window object from a C plugin pointer and the
+    /// window pointer, returns None if the window pointer is null.
+    pub(crate) fn from_raw(
+        weechat_ptr: *mut t_weechat_plugin,
+        window_ptr: *mut t_gui_window,
+    ) -> Option<Window> {
+        if window_ptr.is_null() {
+            None
+        } else {
+            Some(Window {
+                weechat: weechat_ptr,
+                ptr: window_ptr,
+            })
+        }
+    }
+
+    /// Get the Weechat plugin object from a Window object.
+    pub fn get_weechat(&self) -> Weechat {
+        Weechat::from_ptr(self.weechat)
+    }
+
+    /// Get an integer property of the window.
+    /// * `property` - The name of the property, this can be one of number,
+    ///     win_x, win_y, win_width, win_height, win_width_pct,
+    ///     win_height_pct, win_chat_x, win_chat_y, win_chat_width,
+    ///     win_chat_height, first_line_displayed, scrolling or lines_after.
+    pub fn get_integer(&self, property: &str) -> i32 {
+        let weechat = self.get_weechat();
+        let window_get_integer = weechat.get().window_get_integer.unwrap();
+        let property = LossyCString::new(property);
+
+        unsafe { window_get_integer(self.ptr, property.as_ptr()) }
+    }
+
+    /// Get a string property of the window.
+    /// * `property` - The name of the property.
+    /// Returns None if the window doesn't have such a property.
+    pub fn get_string(&self, property: &str) -> Option<Cow<str>> {
+        let weechat = self.get_weechat();
+        let window_get_string = weechat.get().window_get_string.unwrap();
+        let property = LossyCString::new(property);
+
+        unsafe {
+            let value = window_get_string(self.ptr, property.as_ptr());
+            if value.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(value).to_string_lossy())
+            }
+        }
+    }
+
+    fn get_pointer(&self, property: &str) -> *mut c_void {
+        let weechat = self.get_weechat();
+        let window_get_pointer = weechat.get().window_get_pointer.unwrap();
+        let property = LossyCString::new(property);
+
+        unsafe { window_get_pointer(self.ptr, property.as_ptr()) }
+    }
+
+    /// Get the number of the window.
+    pub fn get_number(&self) -> i32 {
+        self.get_integer("number")
+    }
+
+    /// Get the X coordinate of the window in the terminal.
+    pub fn get_x(&self) -> i32 {
+        self.get_integer("win_x")
+    }
+
+    /// Get the Y coordinate of the window in the terminal.
+    pub fn get_y(&self) -> i32 {
+        self.get_integer("win_y")
+    }
+
+    /// Get the width of the window in chars.
+    pub fn get_width(&self) -> i32 {
+        self.get_integer("win_width")
+    }
+
+    /// Get the height of the window in chars.
+    pub fn get_height(&self) -> i32 {
+        self.get_integer("win_height")
+    }
+
+    /// Get the width of the chat area of the window in chars.
+    pub fn get_chat_width(&self) -> i32 {
+        self.get_integer("win_chat_width")
+    }
+
+    /// Get the height of the chat area of the window in chars.
+    pub fn get_chat_height(&self) -> i32 {
+        self.get_integer("win_chat_height")
+    }
+
+    /// Is the window scrolled, false if the last line of the buffer is
+    /// displayed at the bottom of the window.
+    pub fn is_scrolled(&self) -> bool {
+        self.get_integer("scrolling") != 0
+    }
+
+    /// Get the number of lines that are not displayed towards the end of the
+    /// buffer.
+    pub fn get_lines_after(&self) -> i32 {
+        self.get_integer("lines_after")
+    }
+
+    /// Get the buffer that is displayed in the window.
+    pub fn get_buffer(&self) -> Buffer {
+        let ptr = self.get_pointer("buffer") as *mut t_gui_buffer;
+        Buffer::from_ptr(self.weechat, ptr)
+    }
+}
@@ -13,7 +13,45 @@ pub const WEECHAT_RC_OK: c_int = 0;
 pub const WEECHAT_RC_OK_EAT: c_int = 1;
 pub const WEECHAT_RC_ERROR: c_int = -1;
 
+/* types for hdata */
+pub const WEECHAT_HDATA_OTHER: c_int = 0;
+pub const WEECHAT_HDATA_CHAR: c_int = 1;
+pub const WEECHAT_HDATA_INTEGER: c_int = 2;
+pub const WEECHAT_HDATA_LONG: c_int = 3;
+pub const WEECHAT_HDATA_STRING: c_int = 4;
+pub const WEECHAT_HDATA_POINTER: c_int = 5;
+pub const WEECHAT_HDATA_TIME: c_int = 6;
+pub const WEECHAT_HDATA_HASHTABLE: c_int = 7;
+pub const WEECHAT_HDATA_SHARED_STRING: c_int = 8;
+
+/* return codes for hook_process */
+pub const WEECHAT_HOOK_PROCESS_RUNNING: c_int = -1;
+pub const WEECHAT_HOOK_PROCESS_ERROR: c_int = -2;
+pub const WEECHAT_HOOK_PROCESS_CHILD: c_int = -3;
+
+/* connect status for connection hooked */
+pub const WEECHAT_HOOK_CONNECT_OK: c_int = 0;
+pub const WEECHAT_HOOK_CONNECT_ADDRESS_NOT_FOUND: c_int = 1;
+pub const WEECHAT_HOOK_CONNECT_IP_ADDRESS_NOT_FOUND: c_int = 2;
+pub const WEECHAT_HOOK_CONNECT_CONNECTION_REFUSED: c_int = 3;
+pub const WEECHAT_HOOK_CONNECT_PROXY_ERROR: c_int = 4;
+pub const WEECHAT_HOOK_CONNECT_LOCAL_HOSTNAME_ERROR: c_int = 5;
+pub const WEECHAT_HOOK_CONNECT_GNUTLS_INIT_ERROR: c_int = 6;
+pub const WEECHAT_HOOK_CONNECT_GNUTLS_HANDSHAKE_ERROR: c_int = 7;
+pub const WEECHAT_HOOK_CONNECT_MEMORY_ERROR: c_int = 8;
+pub const WEECHAT_HOOK_CONNECT_TIMEOUT: c_int = 9;
+pub const WEECHAT_HOOK_CONNECT_SOCKET_ERROR: c_int = 10;
+
+pub const WEECHAT_CONFIG_WRITE_OK: c_int = 0;
+pub const WEECHAT_CONFIG_WRITE_ERROR: c_int = -1;
+pub const WEECHAT_CONFIG_WRITE_MEMORY_ERROR: c_int = -2;
+
 pub const WEECHAT_CONFIG_OPTION_SET_OK_CHANGED: c_int = 2;
 pub const WEECHAT_CONFIG_OPTION_SET_OK_SAME_VALUE: c_int = 1;
 pub const WEECHAT_CONFIG_OPTION_SET_ERROR: c_int = 0;
 pub const WEECHAT_CONFIG_OPTION_SET_OPTION_NOT_FOUND: c_int = -1;
+
+pub const WEECHAT_CONFIG_OPTION_UNSET_OK_NO_RESET: c_int = 0;
+pub const WEECHAT_CONFIG_OPTION_UNSET_OK_RESET: c_int = 1;
+pub const WEECHAT_CONFIG_OPTION_UNSET_OK_REMOVED: c_int = 2;
+pub const WEECHAT_CONFIG_OPTION_UNSET_ERROR: c_int = -1;